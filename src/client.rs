@@ -130,8 +130,12 @@ impl ApiClient {
             .clone()
             .context("Authentication not configured")?;
 
+        // `request_timeout_ms` caps a whole request, body included, and also
+        // how long any one read may stall: a download lifts the first (see
+        // `http_utils::DOWNLOAD_TIMEOUT`) and is held to the second alone.
         let http = reqwest::Client::builder()
             .timeout(Duration::from_millis(config.performance.request_timeout_ms))
+            .read_timeout(Duration::from_millis(config.performance.request_timeout_ms))
             .connect_timeout(Duration::from_secs(10))
             .build()
             .context("Failed to create HTTP client")?;
@@ -152,6 +156,7 @@ impl ApiClient {
     pub(crate) fn new_with_strategy(strategy: Arc<dyn AuthStrategy>, config: Config) -> Self {
         let http = reqwest::Client::builder()
             .timeout(Duration::from_millis(config.performance.request_timeout_ms))
+            .read_timeout(Duration::from_millis(config.performance.request_timeout_ms))
             .connect_timeout(Duration::from_secs(10))
             .build()
             .expect("test http client builds");
@@ -307,6 +307,12 @@ pub struct Config {
 
     #[serde(default)]
     pub optimization: OptimizationConfig,
+
+    /// Where lookups worth keeping between runs are stored (the Jira field
+    /// catalog). Runtime-only, set by `load`; `None` keeps them in memory
+    /// for the one invocation, which is what a config built in a test wants.
+    #[serde(skip)]
+    pub cache_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -326,6 +332,66 @@ pub struct JiraConfig {
 pub struct ConfluenceConfig {
     #[serde(default)]
     pub spaces_filter: Vec<String>,
+
+    /// How a macro renders in Markdown output, by macro name, in place of the
+    /// built-in rendering. A name not listed keeps the built-in one.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub macros: HashMap<String, MacroRule>,
+}
+
+/// One `[<profile>.confluence.macros.<name>]` table.
+///
+/// A Marketplace macro the converter has never heard of renders as
+/// `[Macro: name (…)]`, which is true but rarely what a team wants to read.
+/// A `template` says what it should read as instead; `drop = true` says it
+/// should not appear at all (a live-search box, a page-rating widget).
+/// One of the two, never both: a rule that does both says nothing about
+/// which the writer meant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "MacroRuleTable", into = "MacroRuleTable")]
+pub enum MacroRule {
+    /// Markdown with `{param:NAME}`, `{body}` and `{name}` filled in.
+    Template(String),
+    Drop,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MacroRuleTable {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    drop: bool,
+}
+
+impl TryFrom<MacroRuleTable> for MacroRule {
+    type Error = String;
+
+    fn try_from(table: MacroRuleTable) -> std::result::Result<Self, String> {
+        match (table.template, table.drop) {
+            (Some(template), false) => Ok(MacroRule::Template(template)),
+            (None, true) => Ok(MacroRule::Drop),
+            (Some(_), true) => {
+                Err("a macro rule takes `template` or `drop = true`, not both".to_string())
+            }
+            (None, false) => Err("a macro rule needs a `template` or `drop = true`".to_string()),
+        }
+    }
+}
+
+impl From<MacroRule> for MacroRuleTable {
+    fn from(rule: MacroRule) -> Self {
+        match rule {
+            MacroRule::Template(template) => MacroRuleTable {
+                template: Some(template),
+                drop: false,
+            },
+            MacroRule::Drop => MacroRuleTable {
+                template: None,
+                drop: true,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -531,6 +597,7 @@ impl Config {
     ) -> Result<Self> {
         let mut config = Self {
             profile: profile.map(String::as_str).unwrap_or("default").to_string(),
+            cache_dir: dirs::cache_dir().map(|dir| dir.join("atlassian-cli")),
             ..Self::default()
         };
         // Track whether the requested profile was found in any config file.
@@ -715,6 +782,9 @@ impl Config {
         if !other.confluence.spaces_filter.is_empty() {
             self.confluence.spaces_filter = other.confluence.spaces_filter;
         }
+        // Rule by rule: a project file that templates one macro should not
+        // take away the rules the global file has for the others.
+        self.confluence.macros.extend(other.confluence.macros);
 
         // Only overwrite performance if the child profile explicitly specified it.
         // This prevents silent data loss where a child without [performance] would
@@ -956,11 +1026,17 @@ impl Config {
 [default.jira]
 projects_filter = []
 # search_default_fields = ["key", "summary", "status", "assignee"]
-# search_custom_fields = ["customfield_10015"]
+# search_custom_fields = ["customfield_10015", "Story Points"]  # ids or names
 
 [default.confluence]
 spaces_filter = []
 
+# How a macro reads in --format markdown, in place of the built-in rendering:
+# [default.confluence.macros.roadmap]
+# template = "**Roadmap: {param:title}**\n\n{body}"
+# [default.confluence.macros.livesearch]
+# drop = true
+
 [default.performance]
 request_timeout_ms = 30000
 rate_limit_delay_ms = 200
@@ -1948,6 +2024,66 @@ domain = "x.atlassian.net"
         assert_eq!(config.performance.rate_limit_delay_ms, 500);
     }
 
+    #[test]
+    fn test_macro_rules_read_as_a_template_or_a_drop() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+[default.confluence.macros.roadmap]
+template = "**Roadmap** {param:title}"
+
+[default.confluence.macros.livesearch]
+drop = true
+"#,
+        )
+        .unwrap();
+        let macros = &file.default.confluence.macros;
+        assert_eq!(
+            macros["roadmap"],
+            MacroRule::Template("**Roadmap** {param:title}".into())
+        );
+        assert_eq!(macros["livesearch"], MacroRule::Drop);
+    }
+
+    #[test]
+    fn test_a_macro_rule_that_both_templates_and_drops_is_refused() {
+        let err = toml::from_str::<ConfigFile>(
+            "[default.confluence.macros.roadmap]\ntemplate = \"x\"\ndrop = true\n",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("not both"), "{err}");
+        assert!(
+            toml::from_str::<ConfigFile>("[default.confluence.macros.roadmap]\n").is_err(),
+            "an empty rule says nothing"
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_macro_rules_the_child_does_not_name() {
+        let mut config = create_basic_config();
+        config
+            .confluence
+            .macros
+            .insert("roadmap".into(), MacroRule::Drop);
+        config
+            .confluence
+            .macros
+            .insert("status".into(), MacroRule::Drop);
+
+        let mut profile = ConfigProfile::default();
+        profile
+            .confluence
+            .macros
+            .insert("status".into(), MacroRule::Template("{param:title}".into()));
+        config.merge(profile);
+
+        assert_eq!(config.confluence.macros["roadmap"], MacroRule::Drop);
+        assert_eq!(
+            config.confluence.macros["status"],
+            MacroRule::Template("{param:title}".into())
+        );
+    }
+
     #[test]
     fn test_load_from_file_returns_none_for_missing_profile() {
         use std::io::Write;
@@ -1,16 +1,25 @@
 use crate::client::{ApiClient, Service, extract_path_and_query};
-use crate::config::Config;
+use crate::config::{Config, MacroRule};
 use crate::confluence::fields::{apply_v2_filtering, build_search_expand};
 use crate::filter;
-use crate::http_utils::{content_type_for_filename, encode_path_segment};
-use crate::markdown::confluence_to_markdown;
+use crate::http_utils::{
+    DOWNLOAD_TIMEOUT, Destination, copy_response, encode_path_segment, file_part, local_file_name,
+    save_response, upload_name,
+};
+use crate::jira::adf::validate_adf;
+use crate::jira::users::UserResolver;
+use crate::markdown::{
+    Attachment, Attachments, UserNames, confluence_to_markdown_with, linked_attachments,
+    linked_users, with_front_matter,
+};
+use crate::output::Stream;
 use crate::query_utils::{clause_detector, inject_filter};
 use crate::response::{require_field, require_u64};
-use anyhow::Result;
+use anyhow::{Context, Result};
 use regex::Regex;
 use serde_json::{Value, json};
-use std::collections::HashSet;
-use std::io::{self, Write};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::LazyLock;
 use std::time::Duration;
 use tokio::time::sleep;
@@ -142,7 +151,20 @@ pub async fn search(
 
     let mut data: Value = response.json().await?;
 
-    let mut items = extract_content_from_results(&mut data, as_markdown)?;
+    let mut users = UserResolver::default();
+    let names = if as_markdown {
+        users
+            .resolve(linked_users_in_results(&data), client)
+            .await?
+    } else {
+        &UserNames::default()
+    };
+    let mut items = extract_content_from_results(
+        &mut data,
+        as_markdown,
+        names,
+        &client.config().confluence.macros,
+    )?;
     let total = require_u64(&data, "/totalSize", "search")?;
     // As on the `--all` path and the Jira side: the items are filtered, the
     // envelope this CLI promises is not.
@@ -162,7 +184,7 @@ pub async fn search_all(
     limit: u32,
     include_all_fields: Option<bool>,
     additional_expand: Option<Vec<String>>,
-    stream: bool,
+    mut stream: Option<&mut Stream>,
     as_markdown: bool,
     client: &ApiClient,
 ) -> Result<Value> {
@@ -184,6 +206,9 @@ pub async fn search_all(
     let mut total_size: u64 = 0;
     let mut trail = CursorTrail::default();
     let mut finished = false;
+    // One for the whole walk, so a person linked on every page of results is
+    // looked up on the first.
+    let mut users = UserResolver::default();
 
     for page_num in 1..=MAX_PAGES {
         let mut data = if let Some(ref url) = next_url {
@@ -200,7 +225,19 @@ pub async fn search_all(
             total_size = data["totalSize"].as_u64().unwrap_or(0);
         }
 
-        let mut items = extract_content_from_results(&mut data, as_markdown)?;
+        let names = if as_markdown {
+            users
+                .resolve(linked_users_in_results(&data), client)
+                .await?
+        } else {
+            &UserNames::default()
+        };
+        let mut items = extract_content_from_results(
+            &mut data,
+            as_markdown,
+            names,
+            &client.config().confluence.macros,
+        )?;
         // Apply response filtering per item so `--all` output matches the
         // single-page `search` envelope. Done before streaming so streamed
         // and accumulated items are filtered identically.
@@ -209,11 +246,8 @@ pub async fn search_all(
         }
         let count = items.len();
 
-        if stream {
-            for item in &items {
-                println!("{}", serde_json::to_string(item)?);
-            }
-            io::stdout().flush()?;
+        if let Some(stream) = stream.as_deref_mut() {
+            stream.write_page(&items)?;
         }
 
         all_items.extend(items);
@@ -265,7 +299,7 @@ pub async fn search_all(
 
     // See `jira::search_all` — Null signals `output_json` to skip, so the
     // trailing summary doesn't pollute the JSONL stream.
-    if stream {
+    if stream.is_some() {
         Ok(Value::Null)
     } else {
         Ok(json!({
@@ -308,16 +342,77 @@ async fn fetch_page(client: &ApiClient, path: &str) -> Result<Value> {
     response.json().await.map_err(Into::into)
 }
 
+/// How `get_page` returns the page body.
+///
+/// `Markdown` is rendered from storage rather than from ADF: storage is where
+/// Confluence keeps its macros by name and parameter, which the Markdown
+/// reader turns into code blocks and panels, while in ADF they are opaque
+/// extension nodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PageFormat {
+    /// Storage-format XHTML, as Confluence keeps it.
+    #[default]
+    Storage,
+    /// Storage rendered as Markdown.
+    Markdown,
+    /// The ADF document (`atlas_doc_format`) Jira bodies are written in,
+    /// returned as a JSON object rather than the string the API wraps it in.
+    Adf,
+}
+
+/// A page body to write, in the representation it is written in. Either way
+/// the content is the string the v2 API sends as `body.value`: storage XHTML,
+/// or an ADF document as JSON.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PageBody<'a> {
+    Storage(&'a str),
+    Adf(&'a str),
+}
+
+impl PageBody<'_> {
+    /// The `body` of a page write. ADF is parsed and checked to be a document
+    /// before anything is sent — malformed JSON would otherwise come back as
+    /// a 400 after the space lookup — and re-serialized, so the value sent is
+    /// compact whatever whitespace the caller's file carried.
+    fn to_json(self) -> Result<Value> {
+        match self {
+            PageBody::Storage(content) => Ok(json!({
+                "representation": "storage",
+                "value": content
+            })),
+            PageBody::Adf(content) => {
+                let document: Value = serde_json::from_str(content)
+                    .context("an ADF page body must be a JSON document")?;
+                validate_adf(&document)?;
+                Ok(json!({
+                    "representation": "atlas_doc_format",
+                    "value": document.to_string()
+                }))
+            }
+        }
+    }
+}
+
+/// Reads one page. `media_dir`, for a Markdown read, points the attachments
+/// the body shows at `<dir>/<filename>` rather than at the site.
 pub async fn get_page(
     page_id: &str,
     include_all_fields: Option<bool>,
     additional_includes: Option<Vec<String>>,
-    as_markdown: bool,
+    format: PageFormat,
+    media_dir: Option<&Path>,
     client: &ApiClient,
 ) -> Result<Value> {
     let url = format!("/wiki/api/v2/pages/{}", encode_path_segment(page_id));
 
-    let query_params = apply_v2_filtering(include_all_fields, additional_includes);
+    let mut query_params = apply_v2_filtering(include_all_fields, additional_includes);
+    if format == PageFormat::Adf {
+        for (name, value) in &mut query_params {
+            if name == "body-format" {
+                *value = "atlas_doc_format".to_string();
+            }
+        }
+    }
 
     let request = client
         .get(Service::Confluence, &url)
@@ -327,15 +422,139 @@ pub async fn get_page(
     let response = client.execute("get page", request).await?;
 
     let mut data: Value = response.json().await?;
+    // Read before the filter: `_links` is the kind of noise an exclude list
+    // names, and the attachment links need the site it gives.
+    let base = data["_links"]["base"].as_str().map(str::to_string);
     filter::apply(&mut data, client.config());
 
-    if as_markdown {
-        convert_page_to_markdown(&mut data);
+    match format {
+        PageFormat::Storage => {}
+        PageFormat::Markdown => {
+            let storage = data
+                .pointer("/body/storage/value")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            let mentioned = linked_users(storage);
+            let attachments = if linked_attachments(storage).is_empty() {
+                Attachments::default()
+            } else {
+                page_attachments(page_id, base.as_deref(), media_dir, client).await?
+            };
+            let mut users = UserResolver::default();
+            let names = users.resolve(mentioned, client).await?;
+            convert_page_to_markdown(
+                &mut data,
+                names,
+                &attachments,
+                &client.config().confluence.macros,
+            );
+        }
+        // Parsed after the filter, not before: the exclude list is aimed at
+        // API noise, and run over the document it would strip node attributes
+        // that happen to share a name with it.
+        PageFormat::Adf => parse_page_adf(&mut data)?,
     }
 
     Ok(data)
 }
 
+/// One page as a Markdown document: its metadata as YAML front matter, its
+/// body as Markdown under it.
+///
+/// The body is `get_page`'s Markdown read. The metadata comes from the v1
+/// content endpoint, which answers the space key, the creator, the ancestors
+/// by title and the labels in one request where v2 would take one apiece.
+pub async fn get_page_document(
+    page_id: &str,
+    media_dir: Option<&Path>,
+    client: &ApiClient,
+) -> Result<String> {
+    let page = get_page(page_id, None, None, PageFormat::Markdown, media_dir, client).await?;
+
+    let path = format!("/wiki/rest/api/content/{}", encode_path_segment(page_id));
+    let request = client
+        .get(Service::Confluence, &path)
+        .await?
+        .query(&[("expand", "space,version,history,ancestors,metadata.labels")])
+        .header("Accept", "application/json");
+    let response = client.execute("get page metadata", request).await?;
+    let meta: Value = response.json().await?;
+
+    let id = require_field(&meta, "/id", "get page metadata")?;
+    let text = |pointer: &str| meta.pointer(pointer).cloned().unwrap_or_default();
+    let names = |pointer: &str, key: &str| -> Value {
+        meta.pointer(pointer)
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().map(|item| item[key].clone()).collect())
+            .unwrap_or_else(|| json!([]))
+    };
+    let web_url = match (
+        meta["_links"]["base"].as_str(),
+        meta["_links"]["webui"].as_str(),
+    ) {
+        (Some(base), Some(webui)) => json!(format!("{}{}", base, webui)),
+        _ => Value::Null,
+    };
+
+    let front_matter = [
+        ("id", id),
+        ("title", text("/title")),
+        ("space", text("/space/key")),
+        ("version", text("/version/number")),
+        ("author", text("/history/createdBy/displayName")),
+        ("created", text("/history/createdDate")),
+        ("last_modified", text("/version/when")),
+        ("last_modified_by", text("/version/by/displayName")),
+        ("labels", names("/metadata/labels/results", "name")),
+        ("ancestors", names("/ancestors", "title")),
+        ("url", web_url),
+    ];
+    let body = page
+        .pointer("/body/storage/value")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    Ok(with_front_matter(&front_matter, body))
+}
+
+/// A page's attachments, for the Markdown body to link. Unfiltered, as
+/// `fetch_links` is on the Jira side: the links are built from
+/// `downloadLink`, and an exclude list is about display.
+///
+/// `downloadLink` is stated from the wiki root. The page response names that
+/// root in `_links.base`; without it the configured domain stands in, and
+/// without that the link stays site-relative, which still says which file.
+async fn page_attachments(
+    page_id: &str,
+    base: Option<&str>,
+    media_dir: Option<&Path>,
+    client: &ApiClient,
+) -> Result<Attachments> {
+    let items = attachment_items(page_id, &[], client).await?;
+
+    let base = match (base, client.config().domain.as_deref()) {
+        (Some(base), _) => base.trim_end_matches('/').to_string(),
+        (None, Some(domain)) => format!("https://{}/wiki", domain),
+        (None, None) => "/wiki".to_string(),
+    };
+    let mut attachments = Attachments::default();
+    for item in &items {
+        let (Some(filename), Some(link)) = (item["title"].as_str(), item["downloadLink"].as_str())
+        else {
+            continue;
+        };
+        attachments.insert(Attachment {
+            id: item["fileId"].as_str().unwrap_or_default().to_string(),
+            filename: filename.to_string(),
+            media_type: item["mediaType"].as_str().map(str::to_string),
+            href: format!("{}{}", base, link),
+        });
+    }
+    if let Some(dir) = media_dir {
+        attachments.localize(dir);
+    }
+    Ok(attachments)
+}
+
 pub async fn get_page_children(page_id: &str, client: &ApiClient) -> Result<Value> {
     let path = format!(
         "/wiki/api/v2/pages/{}/children",
@@ -569,12 +788,20 @@ impl<'a> ThreadWalk<'a> {
 /// Unlike `v2_list_envelope` this does not filter: `ThreadWalk::admit` already
 /// filtered each comment before stamping the fields the walk determined, and
 /// filtering again here would remove exactly those.
-fn comment_envelope(items: Vec<Value>, as_markdown: bool) -> Value {
+async fn comment_envelope(
+    items: Vec<Value>,
+    as_markdown: bool,
+    client: &ApiClient,
+) -> Result<Value> {
     let mut envelope = json!({ "items": items });
     if as_markdown && let Some(comments) = envelope["items"].as_array_mut() {
-        convert_comments_to_markdown(comments);
+        let mut users = UserResolver::default();
+        let names = users
+            .resolve(linked_users_in_comments(comments), client)
+            .await?;
+        convert_comments_to_markdown(comments, names, &client.config().confluence.macros);
     }
-    envelope
+    Ok(envelope)
 }
 
 /// List a page's comments, replies included.
@@ -607,7 +834,7 @@ pub async fn get_comments(
                 .await?,
         );
     }
-    Ok(comment_envelope(items, as_markdown))
+    comment_envelope(items, as_markdown, client).await
 }
 
 /// List everything below one comment.
@@ -631,7 +858,7 @@ pub async fn get_comment_replies(
     let items = ThreadWalk::new(client, family, None, true)
         .collect("get replies", roots, Some(comment_id))
         .await?;
-    Ok(comment_envelope(items, as_markdown))
+    comment_envelope(items, as_markdown, client).await
 }
 
 /// Fetch one comment by id.
@@ -657,7 +884,12 @@ pub async fn get_comment(
     let mut data: Value = response.json().await?;
     filter::apply(&mut data, client.config());
     if as_markdown {
-        convert_comments_to_markdown(std::slice::from_mut(&mut data));
+        let comment = std::slice::from_mut(&mut data);
+        let mut users = UserResolver::default();
+        let names = users
+            .resolve(linked_users_in_comments(comment), client)
+            .await?;
+        convert_comments_to_markdown(comment, names, &client.config().confluence.macros);
     }
     if let Some(object) = data.as_object_mut() {
         object.insert("location".into(), json!(family.label()));
@@ -668,12 +900,14 @@ pub async fn get_comment(
 pub async fn create_page(
     space_key: &str,
     title: &str,
-    content: &str,
+    body: PageBody<'_>,
     parent_id: Option<&str>,
     include_all_fields: Option<bool>,
     additional_includes: Option<Vec<String>>,
     client: &ApiClient,
 ) -> Result<Value> {
+    let page_body = body.to_json()?;
+
     // Resolve the space key to its numeric id via the shared helper (also used
     // by the `space` discovery commands).
     let space_id = resolve_space_id(space_key, client).await?;
@@ -686,10 +920,7 @@ pub async fn create_page(
     let mut body = json!({
         "spaceId": space_id,
         "title": title,
-        "body": {
-            "representation": "storage",
-            "value": content
-        }
+        "body": page_body
     });
     // `parentId` nests the new page under an existing page; omitting it creates
     // the page at the space root.
@@ -722,12 +953,13 @@ pub async fn create_page(
 pub async fn update_page(
     page_id: &str,
     title: &str,
-    content: &str,
+    body: PageBody<'_>,
     parent_id: Option<&str>,
     include_all_fields: Option<bool>,
     additional_includes: Option<Vec<String>>,
     client: &ApiClient,
 ) -> Result<Value> {
+    let page_body = body.to_json()?;
     let url = format!("/wiki/api/v2/pages/{}", encode_path_segment(page_id));
     let next_version = fetch_version_number(client, &url).await? + 1;
 
@@ -739,10 +971,7 @@ pub async fn update_page(
         "id": page_id,
         "status": "current",
         "title": title,
-        "body": {
-            "representation": "storage",
-            "value": content
-        },
+        "body": page_body,
         "version": {
             "number": next_version
         }
@@ -1212,22 +1441,40 @@ pub async fn get_space(space_key: &str, client: &ApiClient) -> Result<Value> {
 
 // --- Attachments ----------------------------------------------------------
 
-/// List the attachments on a page (v2).
+/// List the attachments on a page (v2). Each carries its `mediaType`,
+/// `fileSize`, and `version` — the number an upload of the same filename
+/// raises.
 pub async fn get_attachments(page_id: &str, client: &ApiClient) -> Result<Value> {
+    let items = attachment_items(page_id, &[], client).await?;
+    Ok(v2_list_envelope(items, client))
+}
+
+/// A page's attachments as the API gives them, before any filtering: the
+/// download walk reads `downloadLink`, which the filter is free to drop.
+async fn attachment_items(
+    page_id: &str,
+    query: &[(&str, &str)],
+    client: &ApiClient,
+) -> Result<Vec<Value>> {
     let path = format!(
         "/wiki/api/v2/pages/{}/attachments",
         encode_path_segment(page_id)
     );
-    let items = fetch_all_v2_results(client, "get attachments", &path, &[]).await?;
-    Ok(v2_list_envelope(items, client))
+    fetch_all_v2_results(client, "get attachments", &path, query).await
 }
 
-/// Upload a local file as an attachment on a page. v2 exposes no
-/// attachment-create endpoint, so this uses the stable v1 multipart API (the
-/// same v1 exception as label writes). `PUT` upserts by filename — a new file
-/// is created, an existing one gets a new version — so repeated calls are safe
-/// for agent retries. The `X-Atlassian-Token: nocheck` header is required by
-/// Confluence to bypass its XSRF check on multipart uploads.
+/// Upload a local file as an attachment on a page, or a new version of the
+/// attachment already there under the same filename. v2 exposes no
+/// attachment-write endpoint, so this uses the stable v1 multipart API (the
+/// same v1 exception as label writes). The `X-Atlassian-Token: nocheck`
+/// header is required by Confluence to bypass its XSRF check on multipart
+/// uploads.
+///
+/// Whether the file is new is asked first, by filename, rather than left to
+/// the collection `PUT`'s own upsert: a new version then goes to the
+/// attachment's `data` endpoint, the one write that names the attachment it
+/// replaces, so a refreshed diagram can never land beside the old one as a
+/// duplicate. The answer says which happened.
 ///
 /// Under OAuth this needs the `write:attachment:confluence` scope (basic-auth
 /// tokens carry the user's own permissions and are unaffected).
@@ -1239,27 +1486,10 @@ pub async fn upload_attachment(
     content_type: Option<&str>,
     client: &ApiClient,
 ) -> Result<Value> {
-    let bytes = std::fs::read(file_path)
-        .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", file_path, e))?;
-    // The displayed attachment name is the path's final component; this is
-    // deterministic path parsing, not content sniffing.
-    let file_name = std::path::Path::new(file_path)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .ok_or_else(|| anyhow::anyhow!("Could not derive a file name from '{}'", file_path))?
-        .to_string();
-
-    // An explicit `--content-type` wins; otherwise the type is mapped from the
-    // extension so images/PDFs render inline instead of becoming opaque
-    // `application/octet-stream` downloads.
-    let mime = content_type.unwrap_or_else(|| content_type_for_filename(&file_name));
-
     // `minorEdit` is always sent (the v1 endpoint expects it); `true` suppresses
     // the watcher notification that a re-upload would otherwise fire.
-    let part = reqwest::multipart::Part::bytes(bytes)
-        .file_name(file_name)
-        .mime_str(mime)
-        .map_err(|e| anyhow::anyhow!("Invalid content type '{}': {}", mime, e))?;
+    let part = file_part(file_path, content_type)?;
+    let file_name = upload_name(file_path)?;
     let mut form = reqwest::multipart::Form::new()
         .part("file", part)
         .text("minorEdit", minor_edit.to_string());
@@ -1267,24 +1497,164 @@ pub async fn upload_attachment(
         form = form.text("comment", c.to_string());
     }
 
-    let url = format!(
+    let existing = attachment_items(page_id, &[("filename", &file_name)], client)
+        .await?
+        .into_iter()
+        .find(|attachment| attachment["title"] == file_name.as_str());
+
+    let collection = format!(
         "/wiki/rest/api/content/{}/child/attachment",
         encode_path_segment(page_id)
     );
-
-    let request = client
-        .put(Service::Confluence, &url)
-        .await?
+    let (request, operation, at) = match &existing {
+        Some(attachment) => {
+            let id = require_field(attachment, "/id", "get attachments")?;
+            let url = format!(
+                "{collection}/{}/data",
+                encode_path_segment(id.as_str().unwrap_or_default())
+            );
+            // The data endpoint answers with the attachment itself.
+            let request = client.post(Service::Confluence, &url).await?;
+            (request, "update attachment", "")
+        }
+        None => {
+            // v1 wraps the created attachment in a `results` array.
+            let request = client.put(Service::Confluence, &collection).await?;
+            (request, "upload attachment", "/results/0")
+        }
+    };
+    let request = request
         .header("X-Atlassian-Token", "nocheck")
         .multipart(form);
-    let response = client.execute("upload attachment", request).await?;
+    let response = client.execute(operation, request).await?;
 
-    // v1 wraps the created/updated attachment in a `results` array.
     let data: Value = response.json().await?;
-    Ok(json!({ "id": require_field(&data, "/results/0/id", "upload attachment")? }))
+    Ok(json!({
+        "id": require_field(&data, &format!("{at}/id"), operation)?,
+        "version": require_u64(&data, &format!("{at}/version/number"), operation)?,
+        "created": existing.is_none(),
+    }))
+}
+
+/// Save one attachment, by id: to a file, into a directory under its title,
+/// or to stdout.
+pub async fn download_attachment(
+    attachment_id: &str,
+    destination: Destination<'_>,
+    client: &ApiClient,
+) -> Result<Value> {
+    let url = format!(
+        "/wiki/api/v2/attachments/{}",
+        encode_path_segment(attachment_id)
+    );
+    let request = client
+        .get(Service::Confluence, &url)
+        .await?
+        .header("Accept", "application/json");
+    let response = client.execute("get attachment", request).await?;
+    let attachment: Value = response.json().await?;
+
+    let target = match destination {
+        Destination::Stdout => {
+            let response = attachment_content(&attachment, client).await?;
+            copy_response(response, &mut std::io::stdout()).await?;
+            // As a streamed listing: stdout already holds the answer.
+            return Ok(Value::Null);
+        }
+        Destination::File(path) => path.to_path_buf(),
+        Destination::Dir(dir) => dir.join(local_file_name(
+            attachment["title"].as_str().unwrap_or_default(),
+            attachment_id,
+        )),
+    };
+    let saved = save_attachment(&attachment, &target, client).await?;
+    Ok(json!({ "items": [saved] }))
+}
+
+/// Save every attachment on a page into `dir`, each under its title.
+/// Confluence keeps one attachment per title on a page, so no two can
+/// collide.
+pub async fn download_attachments(page_id: &str, dir: &Path, client: &ApiClient) -> Result<Value> {
+    let mut saved = Vec::new();
+    for attachment in attachment_items(page_id, &[], client).await? {
+        let id = require_field(&attachment, "/id", "get attachments")?;
+        let name = local_file_name(
+            attachment["title"].as_str().unwrap_or_default(),
+            id.as_str().unwrap_or_default(),
+        );
+        saved.push(save_attachment(&attachment, &dir.join(name), client).await?);
+    }
+    Ok(json!({ "items": saved }))
+}
+
+async fn save_attachment(attachment: &Value, target: &Path, client: &ApiClient) -> Result<Value> {
+    let id = require_field(attachment, "/id", "get attachment")?;
+    let response = attachment_content(attachment, client).await?;
+    let bytes = save_response(response, target).await.with_context(|| {
+        format!(
+            "Failed to download attachment {}",
+            id.as_str().unwrap_or_default()
+        )
+    })?;
+    Ok(json!({
+        "id": id,
+        "path": target.display().to_string(),
+        "size": bytes,
+    }))
+}
+
+/// The bytes of an attachment's current version. v2 states where they are as
+/// a `downloadLink` relative to the site's `/wiki` root, query string and all.
+async fn attachment_content(attachment: &Value, client: &ApiClient) -> Result<reqwest::Response> {
+    let link = require_field(attachment, "/downloadLink", "get attachment")?;
+    let url = format!("/wiki{}", link.as_str().unwrap_or_default());
+    let request = client
+        .get(Service::Confluence, &url)
+        .await?
+        .timeout(DOWNLOAD_TIMEOUT);
+    client.execute("download attachment", request).await
+}
+
+/// Move an attachment to the trash (v2 `DELETE`, recoverable like a page's).
+/// The CLI layer still requires an explicit `--yes`.
+pub async fn delete_attachment(attachment_id: &str, client: &ApiClient) -> Result<Value> {
+    let url = format!(
+        "/wiki/api/v2/attachments/{}",
+        encode_path_segment(attachment_id)
+    );
+    let request = client.delete(Service::Confluence, &url).await?;
+    client.execute("delete attachment", request).await?;
+    Ok(json!({}))
+}
+
+/// The users the storage bodies of a search's results link to, for one
+/// lookup ahead of rendering them.
+fn linked_users_in_results(data: &Value) -> Vec<String> {
+    data["results"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.pointer("/content/body/storage/value"))
+        .filter_map(|v| v.as_str())
+        .flat_map(linked_users)
+        .collect()
+}
+
+fn linked_users_in_comments(comments: &[Value]) -> Vec<String> {
+    comments
+        .iter()
+        .filter_map(|comment| comment.pointer("/body/storage/value"))
+        .filter_map(|v| v.as_str())
+        .flat_map(linked_users)
+        .collect()
 }
 
-fn extract_content_from_results(data: &mut Value, as_markdown: bool) -> Result<Vec<Value>> {
+fn extract_content_from_results(
+    data: &mut Value,
+    as_markdown: bool,
+    names: &UserNames,
+    macros: &HashMap<String, MacroRule>,
+) -> Result<Vec<Value>> {
     // A present-but-empty `results` is a legitimate zero-match search; a
     // *missing* `results` on a 2xx is schema drift, so bail rather than report
     // an empty page (the same distinction `fetch_all_v2_results` enforces).
@@ -1311,7 +1681,12 @@ fn extract_content_from_results(data: &mut Value, as_markdown: bool) -> Result<V
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string())
             {
-                content["body"]["storage"]["value"] = Value::String(confluence_to_markdown(&html));
+                content["body"]["storage"]["value"] = Value::String(confluence_to_markdown_with(
+                    &html,
+                    names,
+                    &Attachments::default(),
+                    macros,
+                ));
             }
 
             content
@@ -1319,7 +1694,12 @@ fn extract_content_from_results(data: &mut Value, as_markdown: bool) -> Result<V
         .collect())
 }
 
-fn convert_page_to_markdown(data: &mut Value) {
+fn convert_page_to_markdown(
+    data: &mut Value,
+    names: &UserNames,
+    attachments: &Attachments,
+    macros: &HashMap<String, MacroRule>,
+) {
     let Some(body) = data
         .get_mut("body")
         .and_then(|b| b.get_mut("storage"))
@@ -1328,11 +1708,37 @@ fn convert_page_to_markdown(data: &mut Value) {
         return;
     };
     if let Some(html) = body.as_str().map(|s| s.to_string()) {
-        *body = Value::String(confluence_to_markdown(&html));
+        *body = Value::String(confluence_to_markdown_with(
+            &html,
+            names,
+            attachments,
+            macros,
+        ));
+    }
+}
+
+/// Replaces the ADF string the v2 API returns under
+/// `body.atlas_doc_format.value` with the document it encodes, so the output
+/// can be piped straight into a write or into `jira adf validate`. A page
+/// with no body (an include that left it out) is returned as it came; a value
+/// that is not JSON is schema drift and fails rather than passing a string on
+/// as though it were the document.
+fn parse_page_adf(data: &mut Value) -> Result<()> {
+    let Some(value) = data.pointer_mut("/body/atlas_doc_format/value") else {
+        return Ok(());
+    };
+    if let Some(raw) = value.as_str() {
+        *value = serde_json::from_str(raw)
+            .context("get page returned an atlas_doc_format body that is not JSON")?;
     }
+    Ok(())
 }
 
-fn convert_comments_to_markdown(comments: &mut [Value]) {
+fn convert_comments_to_markdown(
+    comments: &mut [Value],
+    names: &UserNames,
+    macros: &HashMap<String, MacroRule>,
+) {
     for item in comments {
         let Some(body) = item
             .get_mut("body")
@@ -1342,7 +1748,12 @@ fn convert_comments_to_markdown(comments: &mut [Value]) {
             continue;
         };
         if let Some(html) = body.as_str().map(|s| s.to_string()) {
-            *body = Value::String(confluence_to_markdown(&html));
+            *body = Value::String(confluence_to_markdown_with(
+                &html,
+                names,
+                &Attachments::default(),
+                macros,
+            ));
         }
     }
 }
@@ -1350,7 +1761,9 @@ fn convert_comments_to_markdown(comments: &mut [Value]) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::{create_test_config_with_filters, mock_client};
+    use crate::test_utils::{
+        create_test_config_with_filters, mock_client, mock_client_with_config, slow_body_server,
+    };
     use wiremock::matchers::{
         body_json, body_string_contains, header, method, path, query_param, query_param_is_missing,
     };
@@ -1385,7 +1798,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = search_all("type = page", 10, None, None, false, false, &client)
+        let result = search_all("type = page", 10, None, None, None, false, &client)
             .await
             .unwrap();
         assert_eq!(result["total"], 0);
@@ -1412,6 +1825,41 @@ mod tests {
         assert_eq!(result["items"][0]["id"], "1");
     }
 
+    /// The macro rules in the profile reach a search's bodies, not only a
+    /// single page's.
+    #[tokio::test]
+    async fn integ_search_markdown_applies_the_configured_macro_rules() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/rest/api/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "results": [{ "content": { "id": "1", "body": { "storage": {
+                    "value": r#"<p>Keep</p><ac:structured-macro ac:name="livesearch" /><ac:structured-macro ac:name="roadmap"><ac:parameter ac:name="title">Q3</ac:parameter></ac:structured-macro>"#
+                }}}}],
+                "totalSize": 1
+            })))
+            .mount(&server)
+            .await;
+
+        let mut config = create_test_config(vec![]);
+        config
+            .confluence
+            .macros
+            .insert("livesearch".into(), MacroRule::Drop);
+        config.confluence.macros.insert(
+            "roadmap".into(),
+            MacroRule::Template("Roadmap {param:title}".into()),
+        );
+        let client = crate::test_utils::mock_client_with_config(server.uri(), config);
+        let result = search("type = page", 10, None, None, true, &client)
+            .await
+            .unwrap();
+        assert_eq!(
+            result["items"][0]["body"]["storage"]["value"],
+            "Keep\n\nRoadmap Q3"
+        );
+    }
+
     #[tokio::test]
     async fn integ_search_bails_on_missing_results() {
         // A 2xx that omits `results` is schema drift, not a zero-match search.
@@ -1522,12 +1970,251 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = get_page("12 345", None, None, false, &client)
+        let result = get_page("12 345", None, None, PageFormat::Storage, None, &client)
             .await
             .unwrap();
         assert_eq!(result["id"], "12 345");
     }
 
+    /// `--format adf` asks for `atlas_doc_format` and hands back the document
+    /// itself, not the JSON string the API wraps it in.
+    #[tokio::test]
+    async fn integ_get_page_as_adf_returns_the_document() {
+        let server = MockServer::start().await;
+        let document = json!({
+            "type": "doc",
+            "version": 1,
+            "content": [{"type": "paragraph", "content": [{"type": "text", "text": "hi"}]}]
+        });
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42"))
+            .and(query_param("body-format", "atlas_doc_format"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "42",
+                "body": {"atlas_doc_format": {
+                    "representation": "atlas_doc_format",
+                    "value": document.to_string()
+                }}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = get_page("42", None, None, PageFormat::Adf, None, &client)
+            .await
+            .unwrap();
+        assert_eq!(result["body"]["atlas_doc_format"]["value"], document);
+    }
+
+    #[tokio::test]
+    async fn integ_get_page_as_adf_fails_on_a_body_that_is_not_json() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "42",
+                "body": {"atlas_doc_format": {"value": "<p>not adf</p>"}}
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let err = get_page("42", None, None, PageFormat::Adf, None, &client)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("atlas_doc_format"), "{err}");
+    }
+
+    /// A user link with no body of its own reads as the person's name, looked
+    /// up through the Jira bulk endpoint the Confluence site shares.
+    #[tokio::test]
+    async fn integ_get_page_as_markdown_names_a_linked_user() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "42",
+                "body": {"storage": {
+                    "representation": "storage",
+                    "value": r#"<p>Owner: <ac:link><ri:user ri:account-id="u1" /></ac:link></p>"#
+                }}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/bulk"))
+            .and(query_param("accountId", "u1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "values": [{"accountId": "u1", "displayName": "Jane Doe"}],
+                "isLast": true
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = get_page("42", None, None, PageFormat::Markdown, None, &client)
+            .await
+            .unwrap();
+        let body = result["body"]["storage"]["value"].as_str().unwrap();
+        assert!(body.contains("[@Jane Doe](@u1)"), "{body}");
+    }
+
+    /// The metadata comes from the v1 content read in one request; the body is
+    /// the same Markdown `get --format markdown` gives.
+    #[tokio::test]
+    async fn integ_page_document_has_front_matter_and_the_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "42",
+                "body": {"storage": {"value": "<h1>Runbook</h1><p>Restart it.</p>"}}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/rest/api/content/42"))
+            .and(query_param(
+                "expand",
+                "space,version,history,ancestors,metadata.labels",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "42",
+                "title": "Runbook",
+                "space": {"key": "OPS"},
+                "version": {"number": 7, "when": "2026-02-01T10:00:00.000Z", "by": {"displayName": "Ann"}},
+                "history": {"createdBy": {"displayName": "Bo"}, "createdDate": "2025-12-01T10:00:00.000Z"},
+                "ancestors": [{"id": "1", "title": "Home"}, {"id": "2", "title": "Ops"}],
+                "metadata": {"labels": {"results": [{"name": "runbook"}]}},
+                "_links": {"base": "https://site.atlassian.net/wiki", "webui": "/spaces/OPS/pages/42/Runbook"}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let document = get_page_document("42", None, &client).await.unwrap();
+        assert_eq!(
+            document,
+            "---\nid: \"42\"\ntitle: \"Runbook\"\nspace: \"OPS\"\nversion: 7\nauthor: \"Bo\"\ncreated: \"2025-12-01T10:00:00.000Z\"\nlast_modified: \"2026-02-01T10:00:00.000Z\"\nlast_modified_by: \"Ann\"\nlabels:\n  - \"runbook\"\nancestors:\n  - \"Home\"\n  - \"Ops\"\nurl: \"https://site.atlassian.net/wiki/spaces/OPS/pages/42/Runbook\"\n---\n\n# Runbook\n\nRestart it.\n"
+        );
+    }
+
+    /// Without Browse-users permission the page still reads, with the id
+    /// standing in for the name.
+    #[tokio::test]
+    async fn integ_get_page_as_markdown_shows_the_id_when_the_lookup_is_refused() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "42",
+                "body": {"storage": {
+                    "value": r#"<p><ac:link><ri:user ri:account-id="u1" /></ac:link></p>"#
+                }}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/bulk"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = get_page("42", None, None, PageFormat::Markdown, None, &client)
+            .await
+            .unwrap();
+        let body = result["body"]["storage"]["value"].as_str().unwrap();
+        assert!(body.contains("@u1"), "{body}");
+        assert!(!body.contains("Jane"), "{body}");
+    }
+
+    /// An attached image is shown from the download link the attachment list
+    /// gives, stated from the site root the page response names.
+    #[tokio::test]
+    async fn integ_get_page_as_markdown_links_attached_images() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "42",
+                "body": {"storage": {
+                    "value": r#"<ac:image><ri:attachment ri:filename="arch.png" /></ac:image>"#
+                }},
+                "_links": {"base": "https://site.atlassian.net/wiki"}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42/attachments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "results": [{
+                    "id": "att7",
+                    "title": "arch.png",
+                    "mediaType": "image/png",
+                    "fileId": "f-7",
+                    "downloadLink": "/download/attachments/42/arch.png?version=2&api=v2"
+                }],
+                "_links": {}
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = get_page("42", None, None, PageFormat::Markdown, None, &client)
+            .await
+            .unwrap();
+        assert_eq!(
+            result["body"]["storage"]["value"],
+            "![arch.png](https://site.atlassian.net/wiki/download/attachments/42/arch.png?version=2&api=v2)"
+        );
+
+        let local = get_page(
+            "42",
+            None,
+            None,
+            PageFormat::Markdown,
+            Some(Path::new("media")),
+            &client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            local["body"]["storage"]["value"],
+            "![arch.png](media/arch.png)"
+        );
+    }
+
+    /// A body that shows no attachment does not cost a request for the list.
+    #[tokio::test]
+    async fn integ_get_page_as_markdown_without_attachments_skips_the_list() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "42",
+                "body": {"storage": {"value": "<p>plain</p>"}}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42/attachments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"results": []})))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = get_page("42", None, None, PageFormat::Markdown, None, &client)
+            .await
+            .unwrap();
+        assert_eq!(result["body"]["storage"]["value"], "plain");
+    }
+
     #[tokio::test]
     async fn integ_delete_page_encodes_id() {
         let server = MockServer::start().await;
@@ -1780,9 +2467,17 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = create_page("ENG", "Spec", "<p>x</p>", None, None, None, &client)
-            .await
-            .unwrap();
+        let result = create_page(
+            "ENG",
+            "Spec",
+            PageBody::Storage("<p>x</p>"),
+            None,
+            None,
+            None,
+            &client,
+        )
+        .await
+        .unwrap();
         assert_eq!(result, json!({ "id": "pid", "title": "Spec" }));
     }
 
@@ -1806,9 +2501,17 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = create_page("ENG", "Spec", "<p>x</p>", None, None, None, &client)
-            .await
-            .unwrap();
+        let result = create_page(
+            "ENG",
+            "Spec",
+            PageBody::Storage("<p>x</p>"),
+            None,
+            None,
+            None,
+            &client,
+        )
+        .await
+        .unwrap();
         assert_eq!(result, json!({ "id": "pid" }));
     }
 
@@ -1842,10 +2545,61 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = create_page("ENG", "Child", "<p>x</p>", Some("999"), None, None, &client)
+        let result = create_page(
+            "ENG",
+            "Child",
+            PageBody::Storage("<p>x</p>"),
+            Some("999"),
+            None,
+            None,
+            &client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["id"], "pid");
+    }
+
+    /// An ADF body is sent in its own representation, compacted; one that is
+    /// not a document is refused before the space is even looked up.
+    #[tokio::test]
+    async fn integ_create_page_sends_an_adf_body_as_atlas_doc_format() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/spaces"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "results": [{ "id": "sid", "key": "ENG" }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/wiki/api/v2/pages"))
+            .and(body_json(json!({
+                "spaceId": "sid",
+                "title": "Spec",
+                "body": {
+                    "representation": "atlas_doc_format",
+                    "value": r#"{"content":[],"type":"doc","version":1}"#
+                }
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": "pid" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let adf = "{\n  \"type\": \"doc\",\n  \"version\": 1,\n  \"content\": []\n}";
+        let result = create_page("ENG", "Spec", PageBody::Adf(adf), None, None, None, &client)
             .await
             .unwrap();
         assert_eq!(result["id"], "pid");
+
+        for bad in ["<p>storage</p>", r#"{"type": "paragraph"}"#] {
+            let err = create_page("ENG", "Spec", PageBody::Adf(bad), None, None, None, &client)
+                .await
+                .unwrap_err();
+            assert!(format!("{err:#}").contains("ADF"), "{bad}: {err:#}");
+        }
     }
 
     #[tokio::test]
@@ -1876,9 +2630,17 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = update_page("12345", "Updated", "<p>y</p>", None, None, None, &client)
-            .await
-            .unwrap();
+        let result = update_page(
+            "12345",
+            "Updated",
+            PageBody::Storage("<p>y</p>"),
+            None,
+            None,
+            None,
+            &client,
+        )
+        .await
+        .unwrap();
         assert_eq!(result, json!({ "id": "12345", "version": 6 }));
     }
 
@@ -1915,7 +2677,7 @@ mod tests {
         let result = update_page(
             "12345",
             "Updated",
-            "<p>y</p>",
+            PageBody::Storage("<p>y</p>"),
             Some("777"),
             None,
             None,
@@ -2254,7 +3016,10 @@ mod tests {
         Mock::given(method("GET"))
             .and(path("/wiki/api/v2/pages/123/attachments"))
             .respond_with(ResponseTemplate::new(200).set_body_json(json!({
-                "results": [{ "id": "att1", "title": "spec.pdf" }]
+                "results": [{
+                    "id": "att1", "title": "spec.pdf", "mediaType": "application/pdf",
+                    "version": { "number": 3 }
+                }]
             })))
             .expect(1)
             .mount(&server)
@@ -2263,11 +3028,28 @@ mod tests {
         let client = mock_client(server.uri());
         let result = get_attachments("123", &client).await.unwrap();
         assert_eq!(result["items"][0]["title"], "spec.pdf");
+        assert_eq!(result["items"][0]["mediaType"], "application/pdf");
+        assert_eq!(result["items"][0]["version"]["number"], 3);
+    }
+
+    /// Mount the filename lookup an upload makes first.
+    async fn mount_attachment_lookup(server: &MockServer, filename: &str, results: Value) {
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/123/attachments"))
+            .and(query_param("filename", filename))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "results": results })))
+            .expect(1)
+            .mount(server)
+            .await;
     }
 
     #[tokio::test]
     async fn integ_upload_attachment_puts_multipart_with_token_header() {
         let server = MockServer::start().await;
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), b"hello").unwrap();
+        let name = tmp.path().file_name().unwrap().to_str().unwrap();
+        mount_attachment_lookup(&server, name, json!([])).await;
         // The v1 upload is a multipart PUT guarded by the XSRF-bypass header. The
         // body itself must carry the file bytes, the comment, and minorEdit —
         // assert on the multipart payload so a dropped part fails the test.
@@ -2281,15 +3063,12 @@ mod tests {
             .and(body_string_contains("name=\"minorEdit\""))
             .and(body_string_contains("true"))
             .respond_with(ResponseTemplate::new(200).set_body_json(json!({
-                "results": [{ "id": "att9" }]
+                "results": [{ "id": "att9", "version": { "number": 1 } }]
             })))
             .expect(1)
             .mount(&server)
             .await;
 
-        let tmp = tempfile::NamedTempFile::new().unwrap();
-        std::fs::write(tmp.path(), b"hello").unwrap();
-
         let client = mock_client(server.uri());
         let result = upload_attachment(
             "123",
@@ -2301,7 +3080,105 @@ mod tests {
         )
         .await
         .unwrap();
-        assert_eq!(result, json!({ "id": "att9" }));
+        assert_eq!(
+            result,
+            json!({ "id": "att9", "version": 1, "created": true })
+        );
+    }
+
+    #[tokio::test]
+    async fn integ_upload_attachment_versions_the_file_already_on_the_page() {
+        let server = MockServer::start().await;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("diagram.png");
+        std::fs::write(&file, b"new").unwrap();
+        mount_attachment_lookup(
+            &server,
+            "diagram.png",
+            json!([{ "id": "att7", "title": "diagram.png" }]),
+        )
+        .await;
+        Mock::given(method("POST"))
+            .and(path(
+                "/wiki/rest/api/content/123/child/attachment/att7/data",
+            ))
+            .and(header("X-Atlassian-Token", "nocheck"))
+            .and(body_string_contains("image/png"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "att7", "version": { "number": 4 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = upload_attachment("123", file.to_str().unwrap(), None, false, None, &client)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            json!({ "id": "att7", "version": 4, "created": false })
+        );
+    }
+
+    #[tokio::test]
+    async fn integ_download_attachments_saves_each_under_its_title() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/123/attachments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "results": [
+                    { "id": "att1", "title": "spec.pdf",
+                      "downloadLink": "/download/attachments/123/spec.pdf?version=2&api=v2" },
+                    { "id": "att2", "title": "../diagram.png",
+                      "downloadLink": "/download/attachments/123/diagram.png?version=1&api=v2" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        for (file, body) in [("spec.pdf", "pdf bytes"), ("diagram.png", "png")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/wiki/download/attachments/123/{file}")))
+                .and(query_param("api", "v2"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(body))
+                .mount(&server)
+                .await;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = mock_client(server.uri());
+        let result = download_attachments("123", dir.path(), &client)
+            .await
+            .unwrap();
+
+        let read = |name: &str| std::fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("spec.pdf"), "pdf bytes");
+        assert_eq!(read("diagram.png"), "png");
+        assert_eq!(result["items"][0]["size"], 9);
+    }
+
+    #[tokio::test]
+    async fn integ_save_attachment_outlasts_the_request_timeout_while_the_body_flows() {
+        const CHUNKS: &[&str] = &["0123456789"; 10];
+        let base_url = slow_body_server(CHUNKS, std::time::Duration::from_millis(100)).await;
+        let mut config = create_test_config(vec![]);
+        config.performance.request_timeout_ms = 300;
+        let client = mock_client_with_config(base_url, config);
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("big.bin");
+        let attachment = json!({ "id": "att1", "downloadLink": "/download/attachments/1/big.bin" });
+        let saved = save_attachment(&attachment, &target, &client)
+            .await
+            .unwrap();
+
+        assert_eq!(saved["size"], 100);
+        assert_eq!(std::fs::read(&target).unwrap().len(), 100);
     }
 
     #[tokio::test]
@@ -2966,7 +3843,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = search_all("type = page", 50, None, None, false, false, &client)
+        let result = search_all("type = page", 50, None, None, None, false, &client)
             .await
             .unwrap();
         let ids: Vec<&str> = result["items"]
@@ -3010,7 +3887,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = search_all("type = page", 50, None, None, false, false, &client)
+        let result = search_all("type = page", 50, None, None, None, false, &client)
             .await
             .unwrap();
         assert_eq!(result["items"][0]["id"], "7", "{result}");
@@ -3038,7 +3915,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let err = search_all("type = page", 50, None, None, false, false, &client)
+        let err = search_all("type = page", 50, None, None, None, false, &client)
             .await
             .unwrap_err()
             .to_string();
@@ -3128,9 +4005,17 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        create_page("OLDKEY", "T", "<p>x</p>", None, None, None, &client)
-            .await
-            .expect("a historical alias names neither field and still resolves");
+        create_page(
+            "OLDKEY",
+            "T",
+            PageBody::Storage("<p>x</p>"),
+            None,
+            None,
+            None,
+            &client,
+        )
+        .await
+        .expect("a historical alias names neither field and still resolves");
     }
 
     /// A property id is overwritten by `set_property` and removed by
@@ -3214,7 +4099,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let err = search_all("type = page", 50, None, None, false, false, &client)
+        let err = search_all("type = page", 50, None, None, None, false, &client)
             .await
             .unwrap_err()
             .to_string();
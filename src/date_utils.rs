@@ -0,0 +1,12 @@
+//! Cross-cutting date helpers shared by the commands that take a day.
+
+/// Whether `s` is a day as `YYYY-MM-DD`, the one date shape Jira's date
+/// fields and this tool's day filters take.
+pub(crate) fn is_day(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
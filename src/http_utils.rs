@@ -1,6 +1,10 @@
 //! Cross-cutting HTTP helpers shared by the Jira and Confluence API layers.
 
+use anyhow::{Context, Result};
 use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
 
 /// Complement of RFC 3986 `pchar` (`unreserved / pct-encoded / sub-delims /
 /// ":" / "@"`) — every byte we must percent-encode when interpolating user
@@ -85,6 +89,111 @@ pub(crate) fn content_type_for_filename(filename: &str) -> &'static str {
     }
 }
 
+/// The multipart `file` part both products' attachment uploads send: the
+/// file's bytes, named by [`upload_name`], typed by `content_type` or else by
+/// [`content_type_for_filename`].
+pub(crate) fn file_part(
+    file_path: &str,
+    content_type: Option<&str>,
+) -> Result<reqwest::multipart::Part> {
+    let bytes = std::fs::read(file_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", file_path, e))?;
+    let file_name = upload_name(file_path)?;
+
+    // An explicit `--content-type` wins; otherwise the type is mapped from the
+    // extension so images/PDFs render inline instead of becoming opaque
+    // `application/octet-stream` downloads.
+    let mime = content_type.unwrap_or_else(|| content_type_for_filename(&file_name));
+    reqwest::multipart::Part::bytes(bytes)
+        .file_name(file_name)
+        .mime_str(mime)
+        .map_err(|e| anyhow::anyhow!("Invalid content type '{}': {}", mime, e))
+}
+
+/// The name an uploaded file is shown under: the path's final component.
+///
+/// This is deterministic path parsing, not content sniffing, and a name that
+/// cannot be derived (a path ending in `..`) is refused rather than guessed at.
+pub(crate) fn upload_name(file_path: &str) -> Result<String> {
+    Path::new(file_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Could not derive a file name from '{}'", file_path))
+}
+
+/// A name a server gave a file, made safe to create inside a directory of
+/// the caller's choosing: only its final component is kept, so a name like
+/// `../../.bashrc` cannot write outside that directory.
+pub(crate) fn local_file_name(name: &str, fallback: &str) -> String {
+    name.rsplit(['/', '\\'])
+        .next()
+        .filter(|n| !n.is_empty() && *n != "." && *n != "..")
+        .unwrap_or(fallback)
+        .to_string()
+}
+
+/// Where a download goes: one attachment to a path of the caller's naming,
+/// to a directory under the name it was uploaded with, or to stdout for a
+/// pipe. Jira and Confluence downloads go to the same places.
+pub enum Destination<'a> {
+    File(&'a Path),
+    Dir(&'a Path),
+    Stdout,
+}
+
+/// The overall limit on a download request, in place of the client's
+/// `request_timeout_ms`.
+///
+/// That timeout bounds a whole request, body included — right for an API
+/// answer, too short for a large file on a slow link, which would be cut off
+/// however steadily it was arriving. A download is instead held to the
+/// client's read timeout, which restarts with every chunk received, so only
+/// a transfer that stalls is given up on; this cap is there so that even a
+/// trickle cannot keep the command running for ever.
+pub(crate) const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Write a response body to `target` as it arrives, chunk by chunk, and
+/// return the number of bytes written.
+///
+/// Attachments can be far larger than anything else this tool reads, so the
+/// body is never held whole in memory. It is written to a temporary file
+/// beside `target` and renamed over it only once complete: an interrupted
+/// download leaves no half-written file behind under the real name, and an
+/// existing file is replaced only by a whole one. A missing directory is
+/// created.
+pub(crate) async fn save_response(response: reqwest::Response, target: &Path) -> Result<u64> {
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory '{}'", dir.display()))?;
+    let mut file = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create a file in '{}'", dir.display()))?;
+    let written = copy_response(response, &mut file)
+        .await
+        .with_context(|| format!("Failed to write '{}'", target.display()))?;
+    file.persist(target)
+        .with_context(|| format!("Failed to save '{}'", target.display()))?;
+    Ok(written)
+}
+
+/// Write a response body to `out` as it arrives — to stdout, say — and
+/// return the number of bytes written.
+pub(crate) async fn copy_response(
+    mut response: reqwest::Response,
+    out: &mut impl Write,
+) -> Result<u64> {
+    let mut written = 0u64;
+    while let Some(chunk) = response.chunk().await? {
+        out.write_all(&chunk)?;
+        written += chunk.len() as u64;
+    }
+    out.flush()?;
+    Ok(written)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -165,4 +274,13 @@ mod tests {
         );
         assert_eq!(content_type_for_filename(""), "application/octet-stream");
     }
+
+    #[test]
+    fn local_file_name_keeps_only_the_final_component() {
+        assert_eq!(local_file_name("report.pdf", "1"), "report.pdf");
+        assert_eq!(local_file_name("../../.bashrc", "1"), ".bashrc");
+        assert_eq!(local_file_name("a\\b.txt", "1"), "b.txt");
+        assert_eq!(local_file_name("..", "10042"), "10042");
+        assert_eq!(local_file_name("", "10042"), "10042");
+    }
 }
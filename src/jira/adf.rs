@@ -1,15 +1,21 @@
+mod schema;
+
+pub use schema::{Violation, check_schema};
+
 use anyhow::Result;
 use serde_json::{Value, json};
 
-/// Validates that a Value is a valid ADF (Atlassian Document Format) document.
+/// Validates that a Value is shaped like an ADF (Atlassian Document Format)
+/// document at the top level.
 ///
 /// A valid ADF document must have:
 /// - type: must be exactly "doc"
 /// - version: must be integer 1
 /// - content: must be an array (can be empty)
 ///
-/// This function performs minimal validation - only checking the top-level document structure.
-/// Full content node validation is delegated to the Jira API for performance.
+/// This is the cheap test for "is this meant as ADF at all", which is what
+/// tells a pasted document from prose. `validate_document` adds the node-level
+/// schema on top for a body about to be sent.
 pub fn validate_adf(value: &Value) -> Result<()> {
     // Check if value is an object
     let obj = value
@@ -48,6 +54,20 @@ pub fn validate_adf(value: &Value) -> Result<()> {
     Ok(())
 }
 
+/// Validates a caller's ADF document against the full schema before it is
+/// sent: the top-level shape first, so its long-standing messages stay as
+/// they were, then every node, attribute and mark, with each violation named
+/// by its JSON pointer rather than left to Jira's single opaque 400.
+pub fn validate_document(value: &Value) -> Result<()> {
+    validate_adf(value)?;
+    let violations = check_schema(value);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let listed: Vec<String> = violations.iter().map(Violation::to_string).collect();
+    anyhow::bail!("Invalid ADF: {}", listed.join("; "))
+}
+
 /// Converts plain text to a simple ADF document with a single paragraph.
 ///
 /// This is the standard conversion for backward compatibility when users
@@ -85,22 +105,26 @@ pub fn text_to_adf(text: &str) -> Value {
 pub fn process_adf_input(value: Value, field_name: &str) -> Result<Value> {
     match value {
         Value::String(text) => {
-            // String input is total: a string that is itself a valid ADF
-            // document is used as-is; everything else — including JSON-looking
-            // prose like `{"status":"done"}` — is the literal body. A string
-            // argument therefore never errors, so any text can always be posted.
+            // A string that is itself an ADF document is used as-is, and held
+            // to the schema like any other document; everything else —
+            // including JSON-looking prose like `{"status":"done"}` — is the
+            // literal body. Only the top-level shape decides which it is, so
+            // prose never errors and any text can always be posted, while a
+            // pasted document with a bad node is refused rather than posted
+            // as its own source.
             let trimmed = text.trim();
             if trimmed.starts_with('{')
                 && let Ok(parsed) = serde_json::from_str::<Value>(trimmed)
                 && parsed.is_object()
                 && validate_adf(&parsed).is_ok()
             {
+                validate_document(&parsed)?;
                 return Ok(parsed);
             }
             Ok(text_to_adf(&text))
         }
         Value::Object(_) => {
-            validate_adf(&value)?;
+            validate_document(&value)?;
             Ok(value)
         }
         Value::Null => Ok(text_to_adf("")),
@@ -455,6 +479,34 @@ mod tests {
         assert_eq!(result["content"][2]["type"], "codeBlock");
     }
 
+    #[test]
+    fn test_process_adf_input_rejects_a_nested_schema_violation() {
+        let input = json!({
+            "type": "doc",
+            "version": 1,
+            "content": [{"type": "panel", "attrs": {"panelType": "shiny"}, "content": [
+                {"type": "paragraph"}
+            ]}]
+        });
+
+        let err = process_adf_input(input, "description")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("/content/0/attrs/panelType"), "{err}");
+    }
+
+    #[test]
+    fn test_process_adf_input_pasted_document_is_held_to_the_schema() {
+        // A string that is a document is used as one, so a bad node in it is
+        // refused instead of being posted as the document's own JSON source.
+        let input = json!(
+            r#"{"type":"doc","version":1,"content":[{"type":"paragraph","content":[{"type":"text","text":""}]}]}"#
+        );
+
+        let err = process_adf_input(input, "comment").unwrap_err().to_string();
+        assert!(err.contains("/content/0/content/0/text"), "{err}");
+    }
+
     #[test]
     fn test_process_adf_input_null() {
         let input = json!(null);
@@ -818,7 +870,8 @@ mod tests {
 
     #[test]
     fn test_process_adf_input_multiple_text_marks() {
-        // Test text with multiple marks (bold + italic + code)
+        // Test text with multiple marks (bold + italic + link); `code` is left
+        // out because the schema lets it combine only with a link
         let input = json!({
             "type": "doc",
             "version": 1,
@@ -830,7 +883,7 @@ mod tests {
                     "marks": [
                         {"type": "strong"},
                         {"type": "em"},
-                        {"type": "code"}
+                        {"type": "link", "attrs": {"href": "https://example.com"}}
                     ]
                 }]
             }]
@@ -0,0 +1,1030 @@
+//! The ADF schema, node by node: which children each node takes, which
+//! attributes it requires and in what shape, and which marks it may carry.
+//!
+//! Jira answers a body it will not store with one 400 and a message that
+//! rarely names the node at fault, so a nested mistake — a heading inside a
+//! quote, a panel type Jira has never heard of, a `code` mark stacked on
+//! `strong` — is found by bisecting the document by hand. Checked here, every
+//! violation comes back at once with the JSON pointer of the node, attribute
+//! or mark it concerns, before anything is sent.
+//!
+//! The rules follow the published ADF JSON schema. Attributes the schema
+//! marks optional are checked only for shape when present, and attributes it
+//! does not list are left alone: Jira ignores what it does not know there, and
+//! refusing them here would refuse documents Jira itself accepts.
+
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// One way a document breaks the schema, at the JSON pointer it concerns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "(document)"
+        } else {
+            &self.path
+        };
+        write!(f, "{path}: {}", self.message)
+    }
+}
+
+/// Every schema violation in `doc`, in document order. Empty means Jira will
+/// accept the document's shape; it says nothing of ids that point nowhere.
+pub fn check_schema(doc: &Value) -> Vec<Violation> {
+    let mut checker = Checker::default();
+    checker.root(doc);
+    checker.violations
+}
+
+const BLOCK: &[&str] = &[
+    "paragraph",
+    "heading",
+    "bulletList",
+    "orderedList",
+    "taskList",
+    "decisionList",
+    "codeBlock",
+    "blockquote",
+    "rule",
+    "panel",
+    "mediaSingle",
+    "mediaGroup",
+    "table",
+    "expand",
+    "layoutSection",
+    "extension",
+    "bodiedExtension",
+    "embedCard",
+    "blockCard",
+];
+
+const INLINE: &[&str] = &[
+    "text",
+    "hardBreak",
+    "mention",
+    "emoji",
+    "date",
+    "status",
+    "inlineCard",
+    "mediaInline",
+    "placeholder",
+    "inlineExtension",
+];
+
+const LIST_ITEM_FIRST: &[&str] = &["paragraph", "mediaSingle", "codeBlock"];
+
+const LIST_ITEM: &[&str] = &[
+    "paragraph",
+    "bulletList",
+    "orderedList",
+    "taskList",
+    "codeBlock",
+    "mediaSingle",
+];
+
+const BLOCKQUOTE: &[&str] = &[
+    "paragraph",
+    "orderedList",
+    "bulletList",
+    "codeBlock",
+    "mediaSingle",
+    "mediaGroup",
+    "extension",
+];
+
+const PANEL: &[&str] = &[
+    "paragraph",
+    "heading",
+    "bulletList",
+    "orderedList",
+    "blockCard",
+    "mediaGroup",
+    "mediaSingle",
+    "codeBlock",
+    "taskList",
+    "rule",
+    "decisionList",
+    "extension",
+];
+
+const TABLE_CELL: &[&str] = &[
+    "paragraph",
+    "panel",
+    "blockquote",
+    "orderedList",
+    "bulletList",
+    "rule",
+    "heading",
+    "codeBlock",
+    "mediaGroup",
+    "mediaSingle",
+    "decisionList",
+    "taskList",
+    "blockCard",
+    "embedCard",
+    "extension",
+    "nestedExpand",
+];
+
+const EXPAND: &[&str] = &[
+    "paragraph",
+    "panel",
+    "blockquote",
+    "orderedList",
+    "bulletList",
+    "rule",
+    "heading",
+    "codeBlock",
+    "mediaGroup",
+    "mediaSingle",
+    "decisionList",
+    "taskList",
+    "table",
+    "blockCard",
+    "embedCard",
+    "extension",
+    "nestedExpand",
+];
+
+const NESTED_EXPAND: &[&str] = &[
+    "paragraph",
+    "heading",
+    "mediaGroup",
+    "mediaSingle",
+    "codeBlock",
+    "bulletList",
+    "orderedList",
+    "taskList",
+    "decisionList",
+    "rule",
+    "panel",
+    "blockquote",
+    "extension",
+];
+
+const LAYOUT_COLUMN: &[&str] = &[
+    "paragraph",
+    "heading",
+    "bulletList",
+    "orderedList",
+    "taskList",
+    "decisionList",
+    "codeBlock",
+    "blockquote",
+    "rule",
+    "panel",
+    "mediaSingle",
+    "mediaGroup",
+    "table",
+    "expand",
+    "extension",
+    "bodiedExtension",
+    "embedCard",
+    "blockCard",
+];
+
+const BODIED_EXTENSION: &[&str] = &[
+    "paragraph",
+    "panel",
+    "blockquote",
+    "orderedList",
+    "bulletList",
+    "rule",
+    "heading",
+    "codeBlock",
+    "mediaGroup",
+    "mediaSingle",
+    "decisionList",
+    "taskList",
+    "table",
+    "extension",
+    "blockCard",
+    "embedCard",
+];
+
+const TEXT_MARKS: &[&str] = &[
+    "strong",
+    "em",
+    "strike",
+    "underline",
+    "code",
+    "link",
+    "subsup",
+    "textColor",
+    "backgroundColor",
+    "annotation",
+];
+
+/// The marks `code` may share a text node with; Jira refuses the rest.
+const CODE_COMPANIONS: &[&str] = &["code", "link", "annotation"];
+
+const PANEL_TYPES: &[&str] = &[
+    "info", "note", "tip", "warning", "error", "success", "custom",
+];
+
+const MEDIA_LAYOUTS: &[&str] = &[
+    "wrap-left",
+    "center",
+    "wrap-right",
+    "wide",
+    "full-width",
+    "align-start",
+    "align-end",
+];
+
+const STATUS_COLORS: &[&str] = &["neutral", "purple", "blue", "red", "yellow", "green"];
+
+/// What a node's `content` may hold.
+enum Content {
+    /// No `content` at all: leaves like `rule`, `mention`, `text`.
+    Empty,
+    /// Inline nodes, possibly none.
+    Inline,
+    /// Unmarked text, possibly none: `codeBlock`.
+    PlainText,
+    /// Block children, counted. `first` narrows what may open the content
+    /// where the schema does (a list item starts with a paragraph).
+    Children {
+        first: &'static [&'static str],
+        rest: &'static [&'static str],
+        min: usize,
+        max: Option<usize>,
+    },
+}
+
+/// The shape an attribute value must have.
+enum Kind {
+    Str,
+    OneOf(&'static [&'static str]),
+    Int {
+        min: i64,
+        max: i64,
+    },
+    Number {
+        min: f64,
+        max: f64,
+    },
+    /// `#rrggbb`, the only colour form Jira stores.
+    Color,
+}
+
+struct Attr {
+    name: &'static str,
+    required: bool,
+    kind: Kind,
+}
+
+const fn required(name: &'static str, kind: Kind) -> Attr {
+    Attr {
+        name,
+        required: true,
+        kind,
+    }
+}
+
+const fn optional(name: &'static str, kind: Kind) -> Attr {
+    Attr {
+        name,
+        required: false,
+        kind,
+    }
+}
+
+struct Spec {
+    content: Content,
+    attrs: &'static [Attr],
+    marks: &'static [&'static str],
+}
+
+const fn children(rest: &'static [&'static str], min: usize) -> Content {
+    Content::Children {
+        first: rest,
+        rest,
+        min,
+        max: None,
+    }
+}
+
+fn spec(node_type: &str) -> Option<Spec> {
+    const NONE: &[Attr] = &[];
+    const LOCAL_ID: &[Attr] = &[required("localId", Kind::Str)];
+    const EXTENSION: &[Attr] = &[
+        required("extensionType", Kind::Str),
+        required("extensionKey", Kind::Str),
+    ];
+    const CELL: &[Attr] = &[
+        optional(
+            "colspan",
+            Kind::Int {
+                min: 1,
+                max: i64::MAX,
+            },
+        ),
+        optional(
+            "rowspan",
+            Kind::Int {
+                min: 1,
+                max: i64::MAX,
+            },
+        ),
+        optional("background", Kind::Str),
+    ];
+
+    let (content, attrs, marks): (Content, &'static [Attr], &'static [&'static str]) =
+        match node_type {
+            "paragraph" => (Content::Inline, NONE, &["alignment", "indentation"]),
+            "heading" => {
+                const ATTRS: &[Attr] = &[required("level", Kind::Int { min: 1, max: 6 })];
+                (Content::Inline, ATTRS, &["alignment", "indentation"])
+            }
+            "bulletList" => (children(&["listItem"], 1), NONE, &[]),
+            "orderedList" => {
+                const ATTRS: &[Attr] = &[optional(
+                    "order",
+                    Kind::Int {
+                        min: 0,
+                        max: i64::MAX,
+                    },
+                )];
+                (children(&["listItem"], 1), ATTRS, &[])
+            }
+            "listItem" => (
+                Content::Children {
+                    first: LIST_ITEM_FIRST,
+                    rest: LIST_ITEM,
+                    min: 1,
+                    max: None,
+                },
+                NONE,
+                &[],
+            ),
+            "taskList" => (
+                Content::Children {
+                    first: &["taskItem"],
+                    rest: &["taskItem", "taskList"],
+                    min: 1,
+                    max: None,
+                },
+                LOCAL_ID,
+                &[],
+            ),
+            "taskItem" => {
+                const ATTRS: &[Attr] = &[
+                    required("localId", Kind::Str),
+                    required("state", Kind::OneOf(&["TODO", "DONE"])),
+                ];
+                (Content::Inline, ATTRS, &[])
+            }
+            "decisionList" => (children(&["decisionItem"], 1), LOCAL_ID, &[]),
+            "decisionItem" => {
+                const ATTRS: &[Attr] = &[
+                    required("localId", Kind::Str),
+                    required("state", Kind::OneOf(&["DECIDED", "UNDECIDED"])),
+                ];
+                (Content::Inline, ATTRS, &[])
+            }
+            "codeBlock" => {
+                const ATTRS: &[Attr] = &[optional("language", Kind::Str)];
+                (Content::PlainText, ATTRS, &["breakout"])
+            }
+            "blockquote" => (children(BLOCKQUOTE, 1), NONE, &[]),
+            "rule" | "hardBreak" => (Content::Empty, NONE, &[]),
+            "panel" => {
+                const ATTRS: &[Attr] = &[required("panelType", Kind::OneOf(PANEL_TYPES))];
+                (children(PANEL, 1), ATTRS, &[])
+            }
+            "mediaSingle" => {
+                const ATTRS: &[Attr] = &[
+                    optional("layout", Kind::OneOf(MEDIA_LAYOUTS)),
+                    optional(
+                        "width",
+                        Kind::Number {
+                            min: 0.0,
+                            max: f64::MAX,
+                        },
+                    ),
+                ];
+                let content = Content::Children {
+                    first: &["media"],
+                    rest: &["caption"],
+                    min: 1,
+                    max: Some(2),
+                };
+                (content, ATTRS, &["link"])
+            }
+            "caption" => (Content::Inline, NONE, &[]),
+            "mediaGroup" => (children(&["media"], 1), NONE, &[]),
+            "media" => {
+                const ATTRS: &[Attr] = &[
+                    required("type", Kind::OneOf(&["file", "link", "external"])),
+                    optional("id", Kind::Str),
+                    optional("collection", Kind::Str),
+                    optional("url", Kind::Str),
+                    optional("alt", Kind::Str),
+                ];
+                (Content::Empty, ATTRS, &["link", "annotation", "border"])
+            }
+            "table" => (children(&["tableRow"], 1), NONE, &[]),
+            "tableRow" => (children(&["tableCell", "tableHeader"], 1), NONE, &[]),
+            "tableCell" | "tableHeader" => (children(TABLE_CELL, 1), CELL, &[]),
+            "expand" => {
+                const ATTRS: &[Attr] = &[optional("title", Kind::Str)];
+                (children(EXPAND, 1), ATTRS, &["breakout"])
+            }
+            "nestedExpand" => {
+                const ATTRS: &[Attr] = &[optional("title", Kind::Str)];
+                (children(NESTED_EXPAND, 1), ATTRS, &[])
+            }
+            "layoutSection" => {
+                let content = Content::Children {
+                    first: &["layoutColumn"],
+                    rest: &["layoutColumn"],
+                    min: 1,
+                    max: Some(3),
+                };
+                (content, NONE, &["breakout"])
+            }
+            "layoutColumn" => {
+                const ATTRS: &[Attr] = &[required(
+                    "width",
+                    Kind::Number {
+                        min: 0.0,
+                        max: 100.0,
+                    },
+                )];
+                (children(LAYOUT_COLUMN, 1), ATTRS, &[])
+            }
+            "extension" | "inlineExtension" => (Content::Empty, EXTENSION, &[]),
+            "bodiedExtension" => (children(BODIED_EXTENSION, 1), EXTENSION, &[]),
+            "embedCard" => {
+                const ATTRS: &[Attr] = &[
+                    required("url", Kind::Str),
+                    required("layout", Kind::OneOf(MEDIA_LAYOUTS)),
+                ];
+                (Content::Empty, ATTRS, &[])
+            }
+            // `url` or `data`, one of them; checked in `Checker::node`.
+            "blockCard" | "inlineCard" => (Content::Empty, NONE, &[]),
+            "text" => (Content::Empty, NONE, TEXT_MARKS),
+            "mention" => {
+                const ATTRS: &[Attr] = &[required("id", Kind::Str), optional("text", Kind::Str)];
+                (Content::Empty, ATTRS, &[])
+            }
+            "emoji" => {
+                const ATTRS: &[Attr] = &[required("shortName", Kind::Str)];
+                (Content::Empty, ATTRS, &[])
+            }
+            "date" => {
+                const ATTRS: &[Attr] = &[required("timestamp", Kind::Str)];
+                (Content::Empty, ATTRS, &[])
+            }
+            "status" => {
+                const ATTRS: &[Attr] = &[
+                    required("text", Kind::Str),
+                    required("color", Kind::OneOf(STATUS_COLORS)),
+                ];
+                (Content::Empty, ATTRS, &[])
+            }
+            "mediaInline" => {
+                const ATTRS: &[Attr] = &[required("id", Kind::Str)];
+                (Content::Empty, ATTRS, &["link", "annotation", "border"])
+            }
+            "placeholder" => {
+                const ATTRS: &[Attr] = &[required("text", Kind::Str)];
+                (Content::Empty, ATTRS, &[])
+            }
+            _ => return None,
+        };
+    Some(Spec {
+        content,
+        attrs,
+        marks,
+    })
+}
+
+/// The attributes a mark requires, or `None` for a mark the schema does not
+/// have.
+fn mark_attrs(mark_type: &str) -> Option<&'static [Attr]> {
+    const NONE: &[Attr] = &[];
+    Some(match mark_type {
+        "strong" | "em" | "strike" | "underline" | "code" => NONE,
+        "link" => {
+            const ATTRS: &[Attr] = &[required("href", Kind::Str), optional("title", Kind::Str)];
+            ATTRS
+        }
+        "subsup" => {
+            const ATTRS: &[Attr] = &[required("type", Kind::OneOf(&["sub", "sup"]))];
+            ATTRS
+        }
+        "textColor" | "backgroundColor" => {
+            const ATTRS: &[Attr] = &[required("color", Kind::Color)];
+            ATTRS
+        }
+        "annotation" => {
+            const ATTRS: &[Attr] = &[
+                required("id", Kind::Str),
+                required("annotationType", Kind::OneOf(&["inlineComment"])),
+            ];
+            ATTRS
+        }
+        "alignment" => {
+            const ATTRS: &[Attr] = &[required("align", Kind::OneOf(&["center", "end"]))];
+            ATTRS
+        }
+        "indentation" => {
+            const ATTRS: &[Attr] = &[required("level", Kind::Int { min: 1, max: 6 })];
+            ATTRS
+        }
+        "breakout" => {
+            const ATTRS: &[Attr] = &[required("mode", Kind::OneOf(&["wide", "full-width"]))];
+            ATTRS
+        }
+        "border" => {
+            const ATTRS: &[Attr] = &[
+                required("size", Kind::Int { min: 1, max: 3 }),
+                required("color", Kind::Str),
+            ];
+            ATTRS
+        }
+        _ => return None,
+    })
+}
+
+#[derive(Default)]
+struct Checker {
+    violations: Vec<Violation>,
+}
+
+impl Checker {
+    fn report(&mut self, path: &str, message: impl Into<String>) {
+        self.violations.push(Violation {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn root(&mut self, doc: &Value) {
+        let Some(obj) = doc.as_object() else {
+            self.report("", "must be an object");
+            return;
+        };
+        match obj.get("type") {
+            Some(Value::String(t)) if t == "doc" => {}
+            Some(Value::String(t)) => self.report("/type", format!("must be `doc`, got `{t}`")),
+            _ => self.report("/type", "required `doc` is missing"),
+        }
+        if obj.get("version").and_then(Value::as_i64) != Some(1) {
+            self.report("/version", "must be the integer 1");
+        }
+        match obj.get("content") {
+            Some(Value::Array(content)) => self.children(content, "doc", "", BLOCK, BLOCK),
+            Some(_) => self.report("/content", "must be an array"),
+            None => self.report("/content", "required array is missing"),
+        }
+    }
+
+    /// Each child of `parent`, checked against what `parent` takes.
+    fn children(
+        &mut self,
+        content: &[Value],
+        parent: &str,
+        path: &str,
+        first: &[&str],
+        rest: &[&str],
+    ) {
+        for (i, child) in content.iter().enumerate() {
+            let allowed = if i == 0 { first } else { rest };
+            self.node(
+                child,
+                parent,
+                &format!("{path}/content/{i}"),
+                allowed,
+                false,
+            );
+        }
+    }
+
+    fn node(&mut self, value: &Value, parent: &str, path: &str, allowed: &[&str], plain: bool) {
+        let Some(obj) = value.as_object() else {
+            self.report(path, "must be a node object");
+            return;
+        };
+        let Some(node_type) = obj.get("type").and_then(Value::as_str) else {
+            self.report(&format!("{path}/type"), "required string is missing");
+            return;
+        };
+        let Some(spec) = spec(node_type) else {
+            self.report(
+                &format!("{path}/type"),
+                format!("unknown node type `{node_type}`"),
+            );
+            return;
+        };
+        if !allowed.contains(&node_type) {
+            self.report(
+                path,
+                format!("`{node_type}` is not allowed here in `{parent}`"),
+            );
+        }
+
+        self.attrs(obj, path, spec.attrs);
+        match node_type {
+            "text" => match obj.get("text") {
+                Some(Value::String(text)) if !text.is_empty() => {}
+                _ => self.report(&format!("{path}/text"), "must be a non-empty string"),
+            },
+            "media" => self.media(obj, path),
+            "blockCard" | "inlineCard" => {
+                let attrs = obj.get("attrs");
+                let has = |key| attrs.and_then(|a| a.get(key)).is_some();
+                if !has("url") && !has("data") {
+                    self.report(&format!("{path}/attrs"), "needs `url` or `data`");
+                }
+            }
+            _ => {}
+        }
+
+        let marks: &[&str] = if plain { &[] } else { spec.marks };
+        self.marks(obj, path, node_type, parent, marks);
+        self.content(obj, path, node_type, &spec.content);
+    }
+
+    fn content(&mut self, obj: &Map<String, Value>, path: &str, node_type: &str, spec: &Content) {
+        let content_path = format!("{path}/content");
+        let content = match obj.get("content") {
+            None => &[][..],
+            Some(Value::Array(items)) => items.as_slice(),
+            Some(_) => {
+                self.report(&content_path, "must be an array");
+                return;
+            }
+        };
+        match *spec {
+            Content::Empty => {
+                if !content.is_empty() {
+                    self.report(&content_path, format!("`{node_type}` takes no content"));
+                }
+            }
+            Content::Inline => self.children(content, node_type, path, INLINE, INLINE),
+            Content::PlainText => {
+                for (i, child) in content.iter().enumerate() {
+                    let child_path = format!("{content_path}/{i}");
+                    self.node(child, node_type, &child_path, &["text"], true);
+                }
+            }
+            Content::Children {
+                first,
+                rest,
+                min,
+                max,
+            } => {
+                if content.len() < min {
+                    self.report(
+                        &content_path,
+                        format!("`{node_type}` needs at least {min} child node(s)"),
+                    );
+                }
+                if let Some(max) = max
+                    && content.len() > max
+                {
+                    self.report(
+                        &content_path,
+                        format!("`{node_type}` takes at most {max} child node(s)"),
+                    );
+                }
+                self.children(content, node_type, path, first, rest);
+            }
+        }
+    }
+
+    fn attrs(&mut self, obj: &Map<String, Value>, path: &str, spec: &[Attr]) {
+        let attrs = match obj.get("attrs") {
+            None => None,
+            Some(Value::Object(attrs)) => Some(attrs),
+            Some(_) => {
+                self.report(&format!("{path}/attrs"), "must be an object");
+                return;
+            }
+        };
+        for attr in spec {
+            let attr_path = format!("{path}/attrs/{}", attr.name);
+            match attrs.and_then(|a| a.get(attr.name)) {
+                None | Some(Value::Null) if attr.required => {
+                    self.report(&attr_path, "required attribute is missing")
+                }
+                None | Some(Value::Null) => {}
+                Some(value) => {
+                    if let Err(message) = check_kind(value, &attr.kind) {
+                        self.report(&attr_path, message);
+                    }
+                }
+            }
+        }
+    }
+
+    /// A file or link needs the id Jira looks it up by; an external image is
+    /// only its URL.
+    fn media(&mut self, obj: &Map<String, Value>, path: &str) {
+        let attrs = obj.get("attrs");
+        let needed: &[&str] = match attrs.and_then(|a| a["type"].as_str()) {
+            Some("file") => &["id", "collection"],
+            Some("link") => &["id"],
+            Some("external") => &["url"],
+            _ => return,
+        };
+        for key in needed {
+            if attrs.and_then(|a| a.get(*key)).is_none() {
+                self.report(
+                    &format!("{path}/attrs/{key}"),
+                    "required attribute is missing",
+                );
+            }
+        }
+    }
+
+    fn marks(
+        &mut self,
+        obj: &Map<String, Value>,
+        path: &str,
+        node_type: &str,
+        parent: &str,
+        allowed: &[&str],
+    ) {
+        let marks = match obj.get("marks") {
+            None => return,
+            Some(Value::Array(marks)) => marks,
+            Some(_) => {
+                self.report(&format!("{path}/marks"), "must be an array");
+                return;
+            }
+        };
+
+        let mut seen: Vec<&str> = Vec::new();
+        for (i, mark) in marks.iter().enumerate() {
+            let mark_path = format!("{path}/marks/{i}");
+            let Some(mark_type) = mark.get("type").and_then(Value::as_str) else {
+                self.report(&format!("{mark_path}/type"), "required string is missing");
+                continue;
+            };
+            let Some(attrs) = mark_attrs(mark_type) else {
+                self.report(&mark_path, format!("unknown mark `{mark_type}`"));
+                continue;
+            };
+            if !allowed.contains(&mark_type) {
+                self.report(
+                    &mark_path,
+                    format!("mark `{mark_type}` is not allowed on `{node_type}` in `{parent}`"),
+                );
+            }
+            if seen.contains(&mark_type) {
+                self.report(&mark_path, format!("mark `{mark_type}` is repeated"));
+            }
+            seen.push(mark_type);
+            if let Some(mark) = mark.as_object() {
+                self.attrs(mark, &mark_path, attrs);
+            }
+        }
+
+        if seen.contains(&"code")
+            && let Some(other) = seen.iter().find(|m| !CODE_COMPANIONS.contains(m))
+        {
+            self.report(
+                &format!("{path}/marks"),
+                format!("mark `code` cannot be combined with `{other}`"),
+            );
+        }
+    }
+}
+
+fn check_kind(value: &Value, kind: &Kind) -> Result<(), String> {
+    match kind {
+        Kind::Str => match value.as_str() {
+            Some(_) => Ok(()),
+            None => Err("must be a string".to_string()),
+        },
+        Kind::OneOf(options) => match value.as_str() {
+            Some(s) if options.contains(&s) => Ok(()),
+            _ => Err(format!("must be one of {}", options.join(", "))),
+        },
+        Kind::Int { min, max } => match value.as_i64() {
+            Some(n) if (*min..=*max).contains(&n) => Ok(()),
+            _ if *max == i64::MAX => Err(format!("must be an integer of at least {min}")),
+            _ => Err(format!("must be an integer from {min} to {max}")),
+        },
+        Kind::Number { min, max } => match value.as_f64() {
+            Some(n) if (*min..=*max).contains(&n) => Ok(()),
+            _ if *max == f64::MAX => Err(format!("must be a number of at least {min}")),
+            _ => Err(format!("must be a number from {min} to {max}")),
+        },
+        Kind::Color => match value.as_str() {
+            Some(s)
+                if s.len() == 7
+                    && s.starts_with('#')
+                    && s[1..].chars().all(|c| c.is_ascii_hexdigit()) =>
+            {
+                Ok(())
+            }
+            _ => Err("must be a `#rrggbb` colour".to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc(content: Value) -> Value {
+        json!({"type": "doc", "version": 1, "content": content})
+    }
+
+    fn paths(doc: &Value) -> Vec<String> {
+        check_schema(doc).into_iter().map(|v| v.path).collect()
+    }
+
+    #[test]
+    fn a_well_formed_document_has_no_violations() {
+        let adf = doc(json!([
+            {"type": "heading", "attrs": {"level": 2}, "content": [{"type": "text", "text": "Title"}]},
+            {"type": "paragraph", "content": [
+                {"type": "text", "text": "see ", "marks": [{"type": "strong"}]},
+                {"type": "text", "text": "x", "marks": [{"type": "code"}, {"type": "link", "attrs": {"href": "https://e.com"}}]},
+                {"type": "mention", "attrs": {"id": "abc"}},
+                {"type": "hardBreak"}
+            ]},
+            {"type": "bulletList", "content": [
+                {"type": "listItem", "content": [
+                    {"type": "paragraph"},
+                    {"type": "orderedList", "attrs": {"order": 3}, "content": [
+                        {"type": "listItem", "content": [{"type": "paragraph"}]}
+                    ]}
+                ]}
+            ]},
+            {"type": "panel", "attrs": {"panelType": "warning"}, "content": [{"type": "paragraph"}]},
+            {"type": "codeBlock", "attrs": {"language": "rust"}, "content": [{"type": "text", "text": "fn x() {}"}]},
+            {"type": "table", "content": [
+                {"type": "tableRow", "content": [
+                    {"type": "tableHeader", "attrs": {"colspan": 2}, "content": [{"type": "paragraph"}]}
+                ]}
+            ]},
+            {"type": "mediaSingle", "attrs": {"layout": "center"}, "content": [
+                {"type": "media", "attrs": {"type": "file", "id": "f1", "collection": "c"}}
+            ]},
+            {"type": "taskList", "attrs": {"localId": "l"}, "content": [
+                {"type": "taskItem", "attrs": {"localId": "t", "state": "DONE"}, "content": [{"type": "text", "text": "do"}]}
+            ]}
+        ]));
+        assert_eq!(check_schema(&adf), vec![]);
+    }
+
+    #[test]
+    fn the_document_root_is_checked() {
+        let adf = json!({"type": "paragraph", "version": 2, "content": {}});
+        assert_eq!(paths(&adf), ["/type", "/version", "/content"]);
+        assert_eq!(paths(&json!([])), [""]);
+    }
+
+    #[test]
+    fn every_violation_is_reported_at_its_own_pointer() {
+        let adf = doc(json!([
+            {"type": "heading", "attrs": {"level": 7}, "content": [{"type": "text", "text": "x"}]},
+            {"type": "panel", "content": [{"type": "paragraph"}]},
+            {"type": "paragraph", "content": [{"type": "text", "text": ""}]}
+        ]));
+        assert_eq!(
+            paths(&adf),
+            [
+                "/content/0/attrs/level",
+                "/content/1/attrs/panelType",
+                "/content/2/content/0/text"
+            ]
+        );
+    }
+
+    #[test]
+    fn a_child_the_parent_does_not_take_is_named() {
+        let adf = doc(json!([
+            {"type": "blockquote", "content": [
+                {"type": "heading", "attrs": {"level": 1}, "content": []}
+            ]},
+            {"type": "listItem", "content": [{"type": "paragraph"}]}
+        ]));
+        let violations = check_schema(&adf);
+        assert_eq!(violations.len(), 2, "{violations:?}");
+        assert_eq!(violations[0].path, "/content/0/content/0");
+        assert!(violations[0].message.contains("`heading`"));
+        assert!(violations[0].message.contains("`blockquote`"));
+        assert_eq!(violations[1].path, "/content/1");
+    }
+
+    #[test]
+    fn a_list_item_must_open_with_a_paragraph() {
+        let adf = doc(json!([
+            {"type": "bulletList", "content": [
+                {"type": "listItem", "content": [
+                    {"type": "bulletList", "content": [
+                        {"type": "listItem", "content": [{"type": "paragraph"}]}
+                    ]}
+                ]}
+            ]},
+            {"type": "bulletList", "content": []}
+        ]));
+        assert_eq!(
+            paths(&adf),
+            ["/content/0/content/0/content/0", "/content/1/content"]
+        );
+    }
+
+    #[test]
+    fn unknown_nodes_and_marks_are_violations() {
+        let adf = doc(json!([
+            {"type": "futureBlock"},
+            {"type": "paragraph", "content": [
+                {"type": "text", "text": "x", "marks": [{"type": "sparkle"}]}
+            ]}
+        ]));
+        let violations = check_schema(&adf);
+        assert_eq!(violations[0].path, "/content/0/type");
+        assert!(violations[0].message.contains("futureBlock"));
+        assert_eq!(violations[1].path, "/content/1/content/0/marks/0");
+        assert!(violations[1].message.contains("sparkle"));
+    }
+
+    #[test]
+    fn marks_are_checked_per_node_and_in_combination() {
+        let adf = doc(json!([
+            {"type": "codeBlock", "content": [
+                {"type": "text", "text": "x", "marks": [{"type": "strong"}]}
+            ]},
+            {"type": "paragraph", "content": [
+                {"type": "text", "text": "y", "marks": [{"type": "code"}, {"type": "strong"}]},
+                {"type": "text", "text": "z", "marks": [{"type": "em"}, {"type": "em"}]},
+                {"type": "text", "text": "w", "marks": [{"type": "textColor", "attrs": {"color": "red"}}]},
+                {"type": "text", "text": "v", "marks": [{"type": "link"}]}
+            ]},
+            {"type": "heading", "attrs": {"level": 1}, "marks": [{"type": "breakout", "attrs": {"mode": "wide"}}]}
+        ]));
+        assert_eq!(
+            paths(&adf),
+            [
+                "/content/0/content/0/marks/0",
+                "/content/1/content/0/marks",
+                "/content/1/content/1/marks/1",
+                "/content/1/content/2/marks/0/attrs/color",
+                "/content/1/content/3/marks/0/attrs/href",
+                "/content/2/marks/0"
+            ]
+        );
+    }
+
+    #[test]
+    fn media_needs_the_ids_its_type_is_looked_up_by() {
+        let adf = doc(json!([
+            {"type": "mediaGroup", "content": [
+                {"type": "media", "attrs": {"type": "file", "id": "f"}},
+                {"type": "media", "attrs": {"type": "external", "url": "https://e.com/a.png"}}
+            ]},
+            {"type": "mediaSingle", "content": []}
+        ]));
+        assert_eq!(
+            paths(&adf),
+            [
+                "/content/0/content/0/attrs/collection",
+                "/content/1/content"
+            ]
+        );
+    }
+
+    #[test]
+    fn a_leaf_with_content_and_a_wrongly_typed_content_are_violations() {
+        let adf = doc(json!([
+            {"type": "rule", "content": [{"type": "paragraph"}]},
+            {"type": "paragraph", "content": "text"}
+        ]));
+        assert_eq!(paths(&adf), ["/content/0/content", "/content/1/content"]);
+    }
+
+    #[test]
+    fn a_violation_displays_as_pointer_and_message() {
+        let v = Violation {
+            path: "/content/0".to_string(),
+            message: "bad".to_string(),
+        };
+        assert_eq!(v.to_string(), "/content/0: bad");
+    }
+
+    #[test]
+    fn markdown_converted_bodies_satisfy_the_schema() {
+        let markdown = "# Title\n\nSome **bold**, `code` and [a link](https://e.com).\n\n\
+                        - one\n  1. nested\n- [x] done\n\n> [!WARNING]\n> careful\n\n\
+                        | a | b |\n|---|---|\n| 1 | 2 |\n\n```rust\nfn x() {}\n```\n\n---\n";
+        let adf = crate::markdown::markdown_to_adf(markdown);
+        assert_eq!(check_schema(&adf), vec![], "{adf:#}");
+    }
+}
@@ -1,16 +1,22 @@
-use crate::client::{ApiClient, Service};
+use crate::client::{ApiClient, ApiError, Service};
 use crate::config::Config;
 use crate::filter;
 use crate::http_utils::encode_path_segment;
 use crate::jira::adf;
+use crate::jira::field_names::FieldResolver;
 use crate::jira::fields;
-use crate::markdown::adf_to_markdown;
+use crate::jira::users::{UserLookup, UserResolver};
+use crate::markdown::{
+    Attachment, Attachments, MarkdownMode, UserNames, has_media, link_media, name_mentions,
+    unnamed_mentions, with_front_matter,
+};
+use crate::output::Stream;
 use crate::query_utils::{clause_detector, inject_filter};
 use crate::response::{WHOLE_BODY, require_array, require_field};
 use anyhow::{Context, Result};
 use regex::Regex;
 use serde_json::{Value, json};
-use std::io::{self, Write};
+use std::path::Path;
 use std::sync::LazyLock;
 use std::time::Duration;
 use tokio::time::sleep;
@@ -20,7 +26,12 @@ use tokio::time::sleep;
 /// coverage and word-boundary rationale.
 static PROJECT_CLAUSE_RE: LazyLock<Regex> = LazyLock::new(|| clause_detector("project"));
 
-fn convert_issue_to_markdown(issue: &mut Value) {
+fn convert_issue_to_markdown(
+    issue: &mut Value,
+    mode: MarkdownMode,
+    names: &UserNames,
+    attachments: &Attachments,
+) {
     let Some(fields) = issue.get_mut("fields") else {
         return;
     };
@@ -28,23 +39,75 @@ fn convert_issue_to_markdown(issue: &mut Value) {
         return;
     };
     if desc.is_object() {
-        *desc = Value::String(adf_to_markdown(desc));
+        render_body(desc, mode, names, attachments);
+    }
+}
+
+/// An ADF body as the Markdown `mode` asks for. Mentions are named and media
+/// pointed at their files for a readable render only: a lossless one is
+/// written back, and has to carry both the way they were stored.
+fn render_body(body: &mut Value, mode: MarkdownMode, names: &UserNames, attachments: &Attachments) {
+    if mode == MarkdownMode::Readable {
+        name_mentions(body, names);
+        link_media(body, attachments);
     }
+    *body = Value::String(mode.render(body));
 }
 
-fn convert_issues_to_markdown(result: &mut Value) {
+/// A search renders each issue against the attachments it carries itself,
+/// which it does when the caller's `--fields` asked for `attachment`; the
+/// rest keep their media placeholders rather than cost a request apiece.
+fn convert_issues_to_markdown(result: &mut Value, names: &UserNames) {
     let Some(items) = result.get_mut("items").and_then(|i| i.as_array_mut()) else {
         return;
     };
 
     for issue in items {
-        convert_issue_to_markdown(issue);
+        let attachments = issue_attachments(issue, None);
+        convert_issue_to_markdown(issue, MarkdownMode::Readable, names, &attachments);
     }
 }
 
+/// The attachments in an issue's `attachment` field. Jira states `content`
+/// as an absolute URL, so nothing needs resolving against the site; with
+/// `media_dir` each points at `<dir>/<filename>` instead.
+fn issue_attachments(issue: &Value, media_dir: Option<&Path>) -> Attachments {
+    let mut attachments = Attachments::default();
+    for item in issue["fields"]["attachment"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        let (Some(filename), Some(href)) = (item["filename"].as_str(), item["content"].as_str())
+        else {
+            continue;
+        };
+        attachments.insert(Attachment {
+            id: item["id"].as_str().unwrap_or_default().to_string(),
+            filename: filename.to_string(),
+            media_type: item["mimeType"].as_str().map(str::to_string),
+            href: href.to_string(),
+        });
+    }
+    if let Some(dir) = media_dir {
+        attachments.localize(dir);
+    }
+    attachments
+}
+
+/// The unnamed mentions in the descriptions of `issues`, for one lookup
+/// ahead of rendering them all.
+fn description_mentions<'a>(issues: impl IntoIterator<Item = &'a Value>) -> Vec<String> {
+    issues
+        .into_iter()
+        .filter_map(|issue| issue.pointer("/fields/description"))
+        .flat_map(unnamed_mentions)
+        .collect()
+}
+
 const MAX_RESULTS_PER_PAGE: u32 = 100;
 
-fn apply_project_filter(jql: &str, config: &Config) -> String {
+pub(crate) fn apply_project_filter(jql: &str, config: &Config) -> String {
     if config.jira.projects_filter.is_empty() {
         return jql.to_string();
     }
@@ -64,14 +127,36 @@ fn apply_project_filter(jql: &str, config: &Config) -> String {
     )
 }
 
+/// Reads one issue. `media_dir`, for a Markdown read, points the attachments
+/// the description shows at `<dir>/<filename>` rather than at the site.
 pub async fn get_issue(
     issue_key: &str,
     api_fields: Option<Vec<String>>,
-    as_markdown: bool,
+    markdown: Option<MarkdownMode>,
+    media_dir: Option<&Path>,
+    name_fields: bool,
     client: &ApiClient,
 ) -> Result<Value> {
     let path = format!("/rest/api/3/issue/{}", encode_path_segment(issue_key));
-    let selected = fields::resolve_get_fields(api_fields, client.config()).join(",");
+    let mut field_names = FieldResolver::new(client.config());
+    let mut selected = field_names
+        .ids(
+            fields::resolve_get_fields(api_fields, client.config()),
+            client,
+        )
+        .await?;
+    // The description's media are linked from the attachment list, so a
+    // Markdown read asks for it too, and takes it back out of what it prints
+    // if the caller's fields did not name it.
+    let borrowed_attachment = markdown == Some(MarkdownMode::Readable)
+        && selected.iter().any(|f| f == "description")
+        && !selected
+            .iter()
+            .any(|f| f == "attachment" || f.starts_with('*'));
+    if borrowed_attachment {
+        selected.push("attachment".to_string());
+    }
+    let selected = selected.join(",");
 
     let request = client
         .get(Service::Jira, &path)
@@ -85,29 +170,118 @@ pub async fn get_issue(
     let response = client.execute("get issue", request).await?;
 
     let mut data: Value = response.json().await?;
+    // Read before the filter, which is about what is printed: an exclude
+    // naming `content` would otherwise leave every link without a target.
+    let attachments = issue_attachments(&data, media_dir);
+    if borrowed_attachment && let Some(fields) = data["fields"].as_object_mut() {
+        fields.remove("attachment");
+    }
 
     // Filtered before converted, as the comment and search reads are: the ADF
     // description becomes a string, and a key the caller excluded inside it
     // would no longer be there to exclude.
     filter::apply(&mut data, client.config());
-    if as_markdown {
-        convert_issue_to_markdown(&mut data);
+    if let Some(mode) = markdown {
+        // Only a readable body shows names; a lossless one writes back the
+        // ids as stored, so the lookup would be a request for nothing.
+        let mentioned = if mode == MarkdownMode::Readable {
+            description_mentions([&data])
+        } else {
+            Vec::new()
+        };
+        let mut users = UserResolver::default();
+        let names = users.resolve(mentioned, client).await?;
+        convert_issue_to_markdown(&mut data, mode, names, &attachments);
+    }
+    if name_fields {
+        field_names.name_custom_fields([&mut data], client).await?;
     }
 
     Ok(data)
 }
 
+/// The fields `get_issue_document` reads, description included.
+const DOCUMENT_FIELDS: &[&str] = &[
+    "summary",
+    "description",
+    "issuetype",
+    "status",
+    "assignee",
+    "priority",
+    "labels",
+    "parent",
+    "created",
+    "updated",
+];
+
+/// One issue as a Markdown document: its metadata as YAML front matter, its
+/// description as the body. What a static-site generator or a notes tool
+/// reads, where `get_issue` is what a `jq` pipeline reads.
+pub async fn get_issue_document(
+    issue_key: &str,
+    mode: MarkdownMode,
+    media_dir: Option<&Path>,
+    client: &ApiClient,
+) -> Result<String> {
+    let fields = DOCUMENT_FIELDS.iter().map(|f| f.to_string()).collect();
+    let issue = get_issue(
+        issue_key,
+        Some(fields),
+        Some(mode),
+        media_dir,
+        false,
+        client,
+    )
+    .await?;
+
+    let key = require_field(&issue, "/key", "get issue")?;
+    let f = &issue["fields"];
+    let name = |field: &str, attr: &str| f[field][attr].as_str().map(|s| json!(s));
+    let url = client
+        .config()
+        .domain
+        .as_deref()
+        .zip(key.as_str())
+        .map(|(domain, key)| json!(format!("https://{}/browse/{}", domain, key)));
+
+    let front_matter = [
+        ("key", key.clone()),
+        ("title", f["summary"].clone()),
+        ("type", name("issuetype", "name").unwrap_or_default()),
+        ("status", name("status", "name").unwrap_or_default()),
+        (
+            "assignee",
+            name("assignee", "displayName").unwrap_or_default(),
+        ),
+        ("priority", name("priority", "name").unwrap_or_default()),
+        ("labels", f["labels"].clone()),
+        ("parent", name("parent", "key").unwrap_or_default()),
+        ("created", f["created"].clone()),
+        ("updated", f["updated"].clone()),
+        ("url", url.unwrap_or_default()),
+    ];
+    let body = f["description"].as_str().unwrap_or_default();
+    Ok(with_front_matter(&front_matter, body))
+}
+
 pub async fn search(
     jql: &str,
     limit: u32,
     fields: Option<Vec<String>>,
     as_markdown: bool,
+    name_fields: bool,
     client: &ApiClient,
 ) -> Result<Value> {
     let final_jql = apply_project_filter(jql, client.config());
     let url = "/rest/api/3/search/jql";
 
-    let resolved_fields = fields::resolve_search_fields(fields, as_markdown, client.config());
+    let mut field_names = FieldResolver::new(client.config());
+    let resolved_fields = field_names
+        .ids(
+            fields::resolve_search_fields(fields, as_markdown, client.config()),
+            client,
+        )
+        .await?;
 
     let body = json!({
         "jql": final_jql,
@@ -136,7 +310,13 @@ pub async fn search(
     result["count"] = json!(count);
 
     if as_markdown {
-        convert_issues_to_markdown(&mut result);
+        let mentioned = description_mentions(result["items"].as_array().into_iter().flatten());
+        let mut users = UserResolver::default();
+        let names = users.resolve(mentioned, client).await?;
+        convert_issues_to_markdown(&mut result, names);
+    }
+    if name_fields && let Some(items) = result["items"].as_array_mut() {
+        field_names.name_custom_fields(items, client).await?;
     }
 
     Ok(result)
@@ -145,13 +325,37 @@ pub async fn search(
 pub async fn search_all(
     jql: &str,
     fields: Option<Vec<String>>,
-    stream: bool,
+    stream: Option<&mut Stream>,
     as_markdown: bool,
+    name_fields: bool,
+    client: &ApiClient,
+) -> Result<Value> {
+    search_up_to(jql, fields, stream, as_markdown, name_fields, None, client).await
+}
+
+/// `search_all`, stopped after the first page that takes the count past
+/// `cap` — for a caller that refuses more than `cap` matches, and need not
+/// walk a query it has already refused to its end to find out how far
+/// past it runs.
+pub(crate) async fn search_up_to(
+    jql: &str,
+    fields: Option<Vec<String>>,
+    mut stream: Option<&mut Stream>,
+    as_markdown: bool,
+    name_fields: bool,
+    cap: Option<usize>,
     client: &ApiClient,
 ) -> Result<Value> {
     let final_jql = apply_project_filter(jql, client.config());
     let url = "/rest/api/3/search/jql";
-    let resolved_fields = fields::resolve_search_fields(fields, as_markdown, client.config());
+    // One for the whole walk, for the reason `users` below is.
+    let mut field_names = FieldResolver::new(client.config());
+    let resolved_fields = field_names
+        .ids(
+            fields::resolve_search_fields(fields, as_markdown, client.config()),
+            client,
+        )
+        .await?;
 
     // The same bound `paginate` carries, for the same reason: the walk ends
     // when the server stops handing out a token, and only a server that never
@@ -162,6 +366,9 @@ pub async fn search_all(
     let mut all_issues: Vec<Value> = Vec::new();
     let mut next_page_token: Option<String> = None;
     let mut seen_tokens: std::collections::HashSet<String> = std::collections::HashSet::new();
+    // One for the whole walk, so a person mentioned on every page is looked
+    // up on the first.
+    let mut users = UserResolver::default();
 
     for page_num in 1..=MAX_PAGES {
         let mut body = json!({
@@ -194,22 +401,30 @@ pub async fn search_all(
         let next_signal = data["nextPageToken"].clone();
         let count = issues.len();
 
-        let processed_issues: Vec<Value> = issues
+        let mut processed_issues: Vec<Value> = issues
             .into_iter()
             .map(|mut issue| {
                 filter::apply(&mut issue, client.config());
-                if as_markdown {
-                    convert_issue_to_markdown(&mut issue);
-                }
                 issue
             })
             .collect();
-
-        if stream {
-            for issue in &processed_issues {
-                println!("{}", serde_json::to_string(issue)?);
+        if as_markdown {
+            let names = users
+                .resolve(description_mentions(&processed_issues), client)
+                .await?;
+            for issue in &mut processed_issues {
+                let attachments = issue_attachments(issue, None);
+                convert_issue_to_markdown(issue, MarkdownMode::Readable, names, &attachments);
             }
-            io::stdout().flush()?;
+        }
+        if name_fields {
+            field_names
+                .name_custom_fields(&mut processed_issues, client)
+                .await?;
+        }
+
+        if let Some(stream) = stream.as_deref_mut() {
+            stream.write_page(&processed_issues)?;
         }
 
         all_issues.extend(processed_issues);
@@ -227,7 +442,8 @@ pub async fn search_all(
         // that is there and is not a string is drift, the same distinction the
         // Confluence walk draws on `_links.next`. An empty page is neither —
         // with a token still live, breaking on one drops every page after it.
-        if next_signal.is_null() {
+        // Past the cap, whatever the pages after would say is not wanted.
+        if next_signal.is_null() || cap.is_some_and(|cap| all_issues.len() > cap) {
             // Cleared rather than merely broken out of: the check after the
             // loop reads this to tell "the endpoint said stop" from "the page
             // bound ran out".
@@ -271,7 +487,7 @@ pub async fn search_all(
     // Stream mode already wrote each item to stdout above. Returning Null signals
     // the caller to skip stdout output — any further JSON would corrupt the JSONL
     // stream a consumer is likely piping into `jq`/`xargs`/etc.
-    if stream {
+    if stream.is_some() {
         Ok(Value::Null)
     } else {
         Ok(json!({
@@ -281,6 +497,29 @@ pub async fn search_all(
     }
 }
 
+/// Jira's error body, `{"errorMessages": [...], "errors": {"field":
+/// "message"}}`, as one line for a person to read.
+pub(crate) fn error_summary(errors: &Value) -> String {
+    let mut parts: Vec<String> = errors["errorMessages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m.as_str().map(str::to_string))
+        .collect();
+    if let Some(by_field) = errors["errors"].as_object() {
+        parts.extend(
+            by_field.iter().map(|(field, message)| {
+                format!("{field}: {}", message.as_str().unwrap_or_default())
+            }),
+        );
+    }
+    if parts.is_empty() {
+        format!("refused: {errors}")
+    } else {
+        parts.join("; ")
+    }
+}
+
 /// Fold `--fields` into what the arguments already said.
 ///
 /// A field named in both places is refused rather than merged: which of the
@@ -315,14 +554,16 @@ fn merge_extra_fields(fields: &mut Value, extra: Value) -> Result<()> {
     Ok(())
 }
 
-pub async fn create_issue(
+/// The `fields` of a new issue, shared by `create_issue` and the bulk create
+/// so one spec means the same issue to both. `extra_fields` is already keyed
+/// by id.
+pub(crate) fn new_issue_fields(
     project_key: &str,
     summary: &str,
     issue_type: &str,
     description: Value,
     parent_key: Option<&str>,
     extra_fields: Option<Value>,
-    client: &ApiClient,
 ) -> Result<Value> {
     // Jira takes only the fields on this type's own create screen, so one
     // nobody asked for is a create refused rather than a field ignored. Each
@@ -354,6 +595,36 @@ pub async fn create_issue(
     if let Some(extra) = extra_fields {
         merge_extra_fields(&mut fields, extra)?;
     }
+    Ok(fields)
+}
+
+pub async fn create_issue(
+    project_key: &str,
+    summary: &str,
+    issue_type: &str,
+    description: Value,
+    parent_key: Option<&str>,
+    extra_fields: Option<Value>,
+    client: &ApiClient,
+) -> Result<Value> {
+    let extra_fields = match extra_fields {
+        Some(extra) => {
+            let mut extra = FieldResolver::new(client.config())
+                .keys(extra, client)
+                .await?;
+            UserLookup::default().resolve_in(&mut extra, client).await?;
+            Some(extra)
+        }
+        None => None,
+    };
+    let fields = new_issue_fields(
+        project_key,
+        summary,
+        issue_type,
+        description,
+        parent_key,
+        extra_fields,
+    )?;
     let body = json!({ "fields": fields });
 
     let request = client
@@ -372,10 +643,16 @@ pub async fn create_issue(
 
 pub async fn update_issue(
     issue_key: &str,
-    mut fields_value: Value,
+    fields_value: Value,
     client: &ApiClient,
 ) -> Result<Value> {
     let url = format!("/rest/api/3/issue/{}", encode_path_segment(issue_key));
+    let mut fields_value = FieldResolver::new(client.config())
+        .keys(fields_value, client)
+        .await?;
+    UserLookup::default()
+        .resolve_in(&mut fields_value, client)
+        .await?;
 
     if let Some(fields_obj) = fields_value.as_object_mut()
         && let Some(description_ref) = fields_obj.get_mut("description")
@@ -488,49 +765,294 @@ pub async fn delete_comment(
     Ok(json!({}))
 }
 
+/// Moves an issue through one of its transitions, named by its id, by its
+/// name, or by the status it leads to — the last being what a person
+/// actually knows ("move it to Done"), and the one a renamed transition
+/// does not break.
+///
+/// A transition whose screen asks for fields takes them in `fields` (by id
+/// or by name) and `resolution`, and `comment` is added as the transition's
+/// own comment. When a transition is named rather than numbered its screen is
+/// already fetched, so a required field left out is refused before the
+/// request, naming every field the screen demands; a numbered one that Jira
+/// refuses gets the same explanation after the fact instead of the raw 400.
 pub async fn transition_issue(
     issue_key: &str,
-    transition_id: &str,
+    transition: &str,
+    resolution: Option<&str>,
+    fields: Option<Value>,
+    comment: Value,
     client: &ApiClient,
 ) -> Result<Value> {
+    // Checked before the id-or-name choice below, which an empty string
+    // passes as an id of no digits.
+    let transition = transition.trim();
+    if transition.is_empty() {
+        anyhow::bail!("give a transition id or name, e.g. 31 or \"In Progress\"");
+    }
     let url = format!(
         "/rest/api/3/issue/{}/transitions",
         encode_path_segment(issue_key)
     );
 
-    let body = json!({
+    let mut screen_fields = match fields {
+        None => serde_json::Map::new(),
+        Some(extra) => match FieldResolver::new(client.config())
+            .keys(extra, client)
+            .await?
+        {
+            Value::Object(mut map) => {
+                // One for every field, so a person named in two is looked up
+                // once.
+                let mut users = UserLookup::default();
+                for value in map.values_mut() {
+                    users.resolve_in(value, client).await?;
+                }
+                map
+            }
+            other => anyhow::bail!(
+                "--fields takes a JSON object of the screen's fields, e.g. \
+                 '{{\"resolution\":{{\"name\":\"Done\"}}}}' — got {other}"
+            ),
+        },
+    };
+    if let Some(resolution) = resolution {
+        if screen_fields.contains_key("resolution") {
+            anyhow::bail!("resolution is given by both --resolution and --fields — give it once");
+        }
+        screen_fields.insert("resolution".into(), json!({ "name": resolution }));
+    }
+
+    // A number is taken as the id it has always been, without a lookup; a
+    // transition is never named by digits alone.
+    let chosen = if transition.chars().all(|c| c.is_ascii_digit()) {
+        None
+    } else {
+        let transitions = fetch_transitions(issue_key, true, client).await?;
+        let chosen = pick_transition(&transitions, transition, issue_key)?.clone();
+        let missing = missing_screen_fields(&chosen, &screen_fields, !comment.is_null());
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "{} — give them with --resolution or --fields '{{\"<field>\": ...}}'",
+                screen_demands(&chosen, issue_key, &missing)
+            );
+        }
+        Some(chosen)
+    };
+    let transition_id = match &chosen {
+        Some(chosen) => require_field(chosen, "/id", "get transitions")?
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        None => transition.to_string(),
+    };
+
+    let mut body = json!({
         "transition": {
             "id": transition_id
         }
     });
+    if !screen_fields.is_empty() {
+        body["fields"] = Value::Object(screen_fields.clone());
+    }
+    if !comment.is_null() {
+        body["update"] = json!({
+            "comment": [{ "add": { "body": adf::process_comment_input(comment.clone())? } }]
+        });
+    }
 
     let request = client
         .post(Service::Jira, &url)
         .await?
         .header("Content-Type", "application/json")
         .json(&body);
-    client.execute("transition issue", request).await?;
+    let Err(err) = client.execute("transition issue", request).await else {
+        return Ok(json!({}));
+    };
 
-    Ok(json!({}))
+    // Only a 400 is a screen's complaint. Explaining it is best-effort: if
+    // the screen cannot be read either, Jira's own answer is all there is.
+    let refusal = match err.downcast_ref::<ApiError>() {
+        Some(api) if api.status == reqwest::StatusCode::BAD_REQUEST => {
+            serde_json::from_str::<Value>(&api.body).ok()
+        }
+        _ => None,
+    };
+    let Some(refusal) = refusal else {
+        return Err(err);
+    };
+    let chosen = match chosen {
+        Some(chosen) => Some(chosen),
+        None => fetch_transitions(issue_key, true, client)
+            .await
+            .ok()
+            .and_then(|all| all.into_iter().find(|t| t["id"] == json!(transition_id))),
+    };
+    let Some(chosen) = chosen else {
+        return Err(err);
+    };
+    let missing = missing_screen_fields(&chosen, &screen_fields, !comment.is_null());
+    if missing.is_empty() {
+        anyhow::bail!(
+            "Jira refused transition '{}' on {issue_key}: {}",
+            chosen["name"].as_str().unwrap_or(&transition_id),
+            error_summary(&refusal)
+        );
+    }
+    anyhow::bail!(
+        "{} (Jira said: {})",
+        screen_demands(&chosen, issue_key, &missing),
+        error_summary(&refusal)
+    )
+}
+
+/// The transition `wanted` names: its id, then its name, then the name of
+/// the status it leads to, each compared without case. Two transitions to
+/// one status is an answer only the caller can choose between.
+fn pick_transition<'a>(
+    transitions: &'a [Value],
+    wanted: &str,
+    issue_key: &str,
+) -> Result<&'a Value> {
+    let named = |pointer: &str| -> Vec<&'a Value> {
+        transitions
+            .iter()
+            .filter(|t| {
+                t.pointer(pointer)
+                    .and_then(Value::as_str)
+                    .is_some_and(|name| name.eq_ignore_ascii_case(wanted))
+            })
+            .collect()
+    };
+    let describe = |t: &Value| {
+        format!(
+            "{} → {} ({})",
+            t["name"].as_str().unwrap_or("?"),
+            t.pointer("/to/name").and_then(Value::as_str).unwrap_or("?"),
+            t["id"].as_str().unwrap_or("?")
+        )
+    };
+    for pointer in ["/id", "/name", "/to/name"] {
+        match named(pointer).as_slice() {
+            [] => continue,
+            [one] => return Ok(one),
+            several => anyhow::bail!(
+                "'{wanted}' matches several transitions of {issue_key}: {} — give the id",
+                several
+                    .iter()
+                    .map(|t| describe(t))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+    anyhow::bail!(
+        "{issue_key} has no transition or target status named '{wanted}'. Available: {}",
+        if transitions.is_empty() {
+            "none".to_string()
+        } else {
+            transitions
+                .iter()
+                .map(describe)
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    )
+}
+
+/// The screen fields of `transition` that are required, have no default,
+/// and were not given. A required `comment` is given by the comment.
+fn missing_screen_fields(
+    transition: &Value,
+    given: &serde_json::Map<String, Value>,
+    has_comment: bool,
+) -> Vec<(String, Value)> {
+    transition["fields"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(id, field)| {
+            field["required"].as_bool() == Some(true)
+                && field["hasDefaultValue"].as_bool() != Some(true)
+                && !given.contains_key(*id)
+                && !(id.as_str() == "comment" && has_comment)
+        })
+        .map(|(id, field)| (id.clone(), field.clone()))
+        .collect()
+}
+
+/// "Transition 'Done' on P-1 needs ...", with each field's name, id, and the
+/// values it allows where the screen lists them.
+fn screen_demands(transition: &Value, issue_key: &str, missing: &[(String, Value)]) -> String {
+    let fields: Vec<String> = missing
+        .iter()
+        .map(|(id, field)| {
+            let name = field["name"].as_str().unwrap_or(id);
+            let allowed: Vec<&str> = field["allowedValues"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v["name"].as_str().or_else(|| v["value"].as_str()))
+                .collect();
+            if allowed.is_empty() {
+                format!("{name} ({id})")
+            } else {
+                format!("{name} ({id}): one of {}", allowed.join(", "))
+            }
+        })
+        .collect();
+    format!(
+        "Transition '{}' on {issue_key} needs fields its screen requires: {}",
+        transition["name"].as_str().unwrap_or("?"),
+        fields.join("; ")
+    )
 }
 
-pub async fn get_comments(issue_key: &str, as_markdown: bool, client: &ApiClient) -> Result<Value> {
+pub async fn get_comments(
+    issue_key: &str,
+    markdown: Option<MarkdownMode>,
+    client: &ApiClient,
+) -> Result<Value> {
     let url = format!(
         "/rest/api/3/issue/{}/comment",
         encode_path_segment(issue_key)
     );
     let items = paginate(&url, &[], "get comments", COMMENT_PAGE, client).await?;
 
-    // Filtered first, then converted: `adf_to_markdown` collapses the body
+    // Filtered first, then converted: rendering collapses the body
     // object into a string, so a key the caller excluded would no longer be
     // there to exclude. The Confluence side orders it the same way.
     let mut envelope = list_envelope(items, client);
-    if as_markdown && let Some(comments) = envelope["items"].as_array_mut() {
+    if let Some(mode) = markdown
+        && let Some(comments) = envelope["items"].as_array_mut()
+    {
+        let mentioned: Vec<String> = if mode == MarkdownMode::Readable {
+            comments
+                .iter()
+                .filter_map(|comment| comment.get("body"))
+                .flat_map(unnamed_mentions)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut users = UserResolver::default();
+        let names = users.resolve(mentioned, client).await?;
+        // A comment's media are the issue's attachments; the list is only
+        // worth a request when some comment shows one and it will be linked.
+        let shows_media = comments
+            .iter()
+            .filter_map(|comment| comment.get("body"))
+            .any(has_media);
+        let attachments = if shows_media && mode == MarkdownMode::Readable {
+            fetch_attachments(issue_key, client).await?
+        } else {
+            Attachments::default()
+        };
         for comment in comments {
             if let Some(body) = comment.get_mut("body")
                 && body.is_object()
             {
-                *body = Value::String(adf_to_markdown(body));
+                render_body(body, mode, names, &attachments);
             }
         }
     }
@@ -538,6 +1060,19 @@ pub async fn get_comments(issue_key: &str, as_markdown: bool, client: &ApiClient
     Ok(envelope)
 }
 
+/// The attachments on an issue, for bodies read apart from it.
+async fn fetch_attachments(issue_key: &str, client: &ApiClient) -> Result<Attachments> {
+    let path = format!("/rest/api/3/issue/{}", encode_path_segment(issue_key));
+    let request = client
+        .get(Service::Jira, &path)
+        .await?
+        .query(&[("fields", "attachment")])
+        .header("Accept", "application/json");
+    let response = client.execute("get attachments", request).await?;
+    let data: Value = response.json().await?;
+    Ok(issue_attachments(&data, None))
+}
+
 pub async fn get_link_types(client: &ApiClient) -> Result<Value> {
     let request = client
         .get(Service::Jira, "/rest/api/3/issueLinkType")
@@ -926,22 +1461,36 @@ pub async fn get_links(issue_key: &str, client: &ApiClient) -> Result<Value> {
 }
 
 pub async fn get_transitions(issue_key: &str, client: &ApiClient) -> Result<Value> {
+    Ok(list_envelope(
+        fetch_transitions(issue_key, false, client).await?,
+        client,
+    ))
+}
+
+/// The transitions open to an issue now. `with_fields` expands each one's
+/// screen fields — what `transition_issue` checks a request against, and
+/// too much for a plain listing.
+async fn fetch_transitions(
+    issue_key: &str,
+    with_fields: bool,
+    client: &ApiClient,
+) -> Result<Vec<Value>> {
     let url = format!(
         "/rest/api/3/issue/{}/transitions",
         encode_path_segment(issue_key)
     );
 
-    let request = client
+    let mut request = client
         .get(Service::Jira, &url)
         .await?
         .header("Accept", "application/json");
+    if with_fields {
+        request = request.query(&[("expand", "transitions.fields")]);
+    }
     let response = client.execute("get transitions", request).await?;
 
     let data: Value = response.json().await?;
-    Ok(list_envelope(
-        require_array(&data, "/transitions", "get transitions")?,
-        client,
-    ))
+    require_array(&data, "/transitions", "get transitions")
 }
 
 pub async fn add_worklog(
@@ -995,6 +1544,15 @@ pub async fn get_worklogs(issue_key: &str, client: &ApiClient) -> Result<Value>
     Ok(list_envelope(items, client))
 }
 
+/// Every changelog entry of an issue, oldest first, as the API sent them.
+pub(crate) async fn fetch_changelog(issue_key: &str, client: &ApiClient) -> Result<Vec<Value>> {
+    let url = format!(
+        "/rest/api/3/issue/{}/changelog",
+        encode_path_segment(issue_key)
+    );
+    paginate(&url, &[], "get changelog", CHANGELOG_PAGE, client).await
+}
+
 pub async fn update_worklog(
     issue_key: &str,
     worklog_id: &str,
@@ -1045,33 +1603,43 @@ pub async fn remove_worklog(
     Ok(json!({}))
 }
 
-async fn get_myself(client: &ApiClient) -> Result<Value> {
+pub(crate) async fn get_myself(client: &ApiClient) -> Result<Value> {
     let request = client.get(Service::Jira, "/rest/api/3/myself").await?;
     let response = client.execute("get current user", request).await?;
 
     response.json().await.map_err(Into::into)
 }
 
-pub async fn add_watcher(issue_key: &str, client: &ApiClient) -> Result<Value> {
+/// Adds `user` (anyone `UserLookup` can name) as a watcher, or the caller
+/// when `None`. The endpoint takes the account id as a bare JSON string, and
+/// an empty body as "me".
+pub async fn add_watcher(issue_key: &str, user: Option<&str>, client: &ApiClient) -> Result<Value> {
     let path = format!(
         "/rest/api/3/issue/{}/watchers",
         encode_path_segment(issue_key)
     );
 
-    let request = client
+    let mut request = client
         .post(Service::Jira, &path)
         .await?
         .header("Content-Type", "application/json");
+    if let Some(user) = user {
+        let account_id = UserLookup::default().account_id(user, client).await?;
+        request = request.json(&account_id);
+    }
     client.execute("add watcher", request).await?;
 
     Ok(json!({}))
 }
 
-pub async fn remove_watcher(issue_key: &str, client: &ApiClient) -> Result<Value> {
-    let me = get_myself(client).await?;
-    let account_id = me["accountId"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Could not determine current user accountId"))?;
+pub async fn remove_watcher(
+    issue_key: &str,
+    user: Option<&str>,
+    client: &ApiClient,
+) -> Result<Value> {
+    let account_id = UserLookup::default()
+        .account_id(user.unwrap_or("me"), client)
+        .await?;
 
     let url = format!(
         "/rest/api/3/issue/{}/watchers",
@@ -1081,12 +1649,34 @@ pub async fn remove_watcher(issue_key: &str, client: &ApiClient) -> Result<Value
     let request = client
         .delete(Service::Jira, &url)
         .await?
-        .query(&[("accountId", account_id)]);
+        .query(&[("accountId", account_id.as_str())]);
     client.execute("remove watcher", request).await?;
 
     Ok(json!({}))
 }
 
+/// Assigns an issue to `user`, or unassigns it for `none`.
+pub async fn assign_issue(issue_key: &str, user: &str, client: &ApiClient) -> Result<Value> {
+    let account_id = if user.eq_ignore_ascii_case("none") {
+        Value::Null
+    } else {
+        json!(UserLookup::default().account_id(user, client).await?)
+    };
+
+    let url = format!(
+        "/rest/api/3/issue/{}/assignee",
+        encode_path_segment(issue_key)
+    );
+    let request = client
+        .put(Service::Jira, &url)
+        .await?
+        .header("Content-Type", "application/json")
+        .json(&json!({ "accountId": account_id }));
+    client.execute("assign issue", request).await?;
+
+    Ok(json!({}))
+}
+
 pub async fn get_watchers(issue_key: &str, client: &ApiClient) -> Result<Value> {
     let url = format!(
         "/rest/api/3/issue/{}/watchers",
@@ -1109,7 +1699,7 @@ pub async fn get_watchers(issue_key: &str, client: &ApiClient) -> Result<Value>
 /// The wrapper is this CLI's contract rather than the API's answer, so
 /// `response_exclude_fields` has no business editing it: naming `items` would
 /// otherwise return `{}` with exit 0 where a list was promised.
-fn list_envelope(mut items: Vec<Value>, client: &ApiClient) -> Value {
+pub(crate) fn list_envelope(mut items: Vec<Value>, client: &ApiClient) -> Value {
     for item in &mut items {
         filter::apply(item, client.config());
     }
@@ -1133,14 +1723,14 @@ enum PageEnd {
 /// The shape of one paginated collection: where its items sit, and how it says
 /// it is finished.
 #[derive(Clone, Copy)]
-struct PageContract {
+pub(crate) struct PageContract {
     items: &'static str,
     end: PageEnd,
 }
 
-/// `/rest/api/3/label`, `/rest/agile/1.0/board`, sprints — everything on the
-/// Agile-style contract.
-const AGILE_PAGE: PageContract = PageContract {
+/// `/rest/api/3/label`, `/rest/agile/1.0/board`, sprints, and a project's
+/// components and versions — everything on the Agile-style contract.
+pub(crate) const AGILE_PAGE: PageContract = PageContract {
     items: "values",
     end: PageEnd::IsLast,
 };
@@ -1157,6 +1747,12 @@ const WORKLOG_PAGE: PageContract = PageContract {
     end: PageEnd::Total,
 };
 
+/// `/rest/api/3/issue/{key}/changelog`.
+const CHANGELOG_PAGE: PageContract = PageContract {
+    items: "values",
+    end: PageEnd::IsLast,
+};
+
 /// Loop through a paginated `startAt`/`maxResults` endpoint and accumulate every
 /// item, following `contract` to know where the items are and when to stop.
 ///
@@ -1164,7 +1760,7 @@ const WORKLOG_PAGE: PageContract = PageContract {
 /// about to print them — `resolve_board_id` reads an `id` off this, and a
 /// caller's `response_exclude_fields` naming a field an internal decision turns
 /// on would otherwise decide something rather than trim a display.
-async fn paginate(
+pub(crate) async fn paginate(
     path: &str,
     extra_query: &[(&str, String)],
     operation: &str,
@@ -1443,6 +2039,204 @@ pub async fn move_issues_to_backlog(issues: &[String], client: &ApiClient) -> Re
     .await
 }
 
+/// What a sprint create, start, or update sets. A part left `None` is not
+/// sent, so an update changes only what it names. Dates are passed through
+/// as given, in the ISO 8601 form the Agile API takes —
+/// `2024-06-03T09:00:00.000+09:00`.
+#[derive(Debug, Default)]
+pub struct SprintFields {
+    pub name: Option<String>,
+    pub goal: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+impl SprintFields {
+    fn body(&self) -> serde_json::Map<String, Value> {
+        [
+            ("name", &self.name),
+            ("goal", &self.goal),
+            ("startDate", &self.start_date),
+            ("endDate", &self.end_date),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), json!(value.as_ref()?))))
+        .collect()
+    }
+}
+
+/// `/rest/agile/1.0/sprint/{id}/issue`, which counts like a search.
+const SPRINT_ISSUE_PAGE: PageContract = PageContract {
+    items: "issues",
+    end: PageEnd::Total,
+};
+
+/// Create a future sprint on a board. Starting it is a separate step, as in
+/// the board's own menu.
+pub async fn create_sprint(
+    board_id: u64,
+    fields: &SprintFields,
+    client: &ApiClient,
+) -> Result<Value> {
+    let mut body = fields.body();
+    body.insert("originBoardId".into(), json!(board_id));
+
+    let request = client
+        .post(Service::Jira, "/rest/agile/1.0/sprint")
+        .await?
+        .header("Content-Type", "application/json")
+        .json(&body);
+    let response = client.execute("create sprint", request).await?;
+
+    let data: Value = response.json().await?;
+    Ok(json!({
+        "id": require_field(&data, "/id", "create sprint")?,
+        "name": require_field(&data, "/name", "create sprint")?,
+        "state": require_field(&data, "/state", "create sprint")?,
+    }))
+}
+
+pub async fn update_sprint(
+    sprint_id: u64,
+    fields: &SprintFields,
+    client: &ApiClient,
+) -> Result<Value> {
+    let body = fields.body();
+    if body.is_empty() {
+        anyhow::bail!("Nothing to update: give --name, --goal, --start-date, or --end-date");
+    }
+    post_sprint(sprint_id, body, "update sprint", client).await
+}
+
+/// Start a future sprint. Jira will not start one without both dates, so
+/// when neither the sprint nor the caller has them the start is refused here
+/// with the flags that supply them, rather than sent to fail.
+pub async fn start_sprint(
+    sprint_id: u64,
+    fields: &SprintFields,
+    client: &ApiClient,
+) -> Result<Value> {
+    let sprint = fetch_sprint(sprint_id, client).await?;
+    let state = sprint["state"].as_str().unwrap_or_default();
+    if state != "future" {
+        anyhow::bail!("Sprint {sprint_id} is {state}; only a future sprint can be started");
+    }
+    let missing: Vec<&str> = [
+        ("startDate", "--start-date", &fields.start_date),
+        ("endDate", "--end-date", &fields.end_date),
+    ]
+    .into_iter()
+    .filter(|(key, _, given)| given.is_none() && sprint[*key].as_str().is_none())
+    .map(|(_, flag, _)| flag)
+    .collect();
+    let what = match missing.as_slice() {
+        [] => "",
+        [_, _] => "dates",
+        ["--start-date"] => "start date",
+        _ => "end date",
+    };
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "Sprint {sprint_id} has no {what} to start with; give {}",
+            missing.join(" and ")
+        );
+    }
+
+    let mut body = fields.body();
+    body.insert("state".into(), json!("active"));
+    post_sprint(sprint_id, body, "start sprint", client).await
+}
+
+/// Where `complete_sprint` carries a sprint's unfinished issues.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CarryOver {
+    Sprint(u64),
+    Backlog,
+}
+
+/// Close an active sprint. With `carry_over`, its issues not yet in a done
+/// status are moved first — to another sprint or to the backlog, through the
+/// same batched moves `sprint move` and `sprint backlog` make — so the close
+/// finds nothing left open; the answer lists what moved.
+///
+/// Sub-tasks are left out of the move: they follow their parent, and the
+/// Agile move endpoints refuse one named on its own, which would fail the
+/// move and leave the sprint open.
+pub async fn complete_sprint(
+    sprint_id: u64,
+    carry_over: Option<CarryOver>,
+    client: &ApiClient,
+) -> Result<Value> {
+    let sprint = fetch_sprint(sprint_id, client).await?;
+    let state = sprint["state"].as_str().unwrap_or_default();
+    if state != "active" {
+        anyhow::bail!("Sprint {sprint_id} is {state}; only an active sprint can be completed");
+    }
+
+    let mut moved = Vec::new();
+    if let Some(carry_over) = carry_over {
+        let path = format!("/rest/agile/1.0/sprint/{}/issue", sprint_id);
+        let open = paginate(
+            &path,
+            &[
+                ("jql", "statusCategory != Done".to_string()),
+                ("fields", "issuetype".to_string()),
+            ],
+            "get sprint issues",
+            SPRINT_ISSUE_PAGE,
+            client,
+        )
+        .await?;
+        for issue in &open {
+            if issue["fields"]["issuetype"]["subtask"] == true {
+                continue;
+            }
+            let key = require_field(issue, "/key", "get sprint issues")?;
+            moved.push(key.as_str().unwrap_or_default().to_string());
+        }
+        if !moved.is_empty() {
+            match carry_over {
+                CarryOver::Sprint(target) => move_issues_to_sprint(target, &moved, client).await?,
+                CarryOver::Backlog => move_issues_to_backlog(&moved, client).await?,
+            };
+        }
+    }
+
+    post_sprint(
+        sprint_id,
+        serde_json::Map::from_iter([("state".to_string(), json!("closed"))]),
+        "complete sprint",
+        client,
+    )
+    .await?;
+    Ok(json!({ "moved": moved }))
+}
+
+async fn fetch_sprint(sprint_id: u64, client: &ApiClient) -> Result<Value> {
+    let path = format!("/rest/agile/1.0/sprint/{}", sprint_id);
+    let request = client.get(Service::Jira, &path).await?;
+    let response = client.execute("get sprint", request).await?;
+    Ok(response.json().await?)
+}
+
+/// The Agile API's partial update: `POST` changes only the fields sent,
+/// where `PUT` would clear the rest.
+async fn post_sprint(
+    sprint_id: u64,
+    body: serde_json::Map<String, Value>,
+    operation: &str,
+    client: &ApiClient,
+) -> Result<Value> {
+    let path = format!("/rest/agile/1.0/sprint/{}", sprint_id);
+    let request = client
+        .post(Service::Jira, &path)
+        .await?
+        .header("Content-Type", "application/json")
+        .json(&body);
+    client.execute(operation, request).await?;
+    Ok(json!({}))
+}
+
 pub async fn assign_issues_to_epic(
     epic_key: &str,
     issues: &[String],
@@ -1561,7 +2355,7 @@ mod tests {
 
     use crate::test_utils::mock_client;
     use wiremock::matchers::{
-        body_json, body_string, body_string_contains, method, path, query_param,
+        body_json, body_partial_json, body_string, body_string_contains, method, path, query_param,
     };
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
@@ -1579,7 +2373,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let err = search("project = X", 50, None, false, &client)
+        let err = search("project = X", 50, None, false, false, &client)
             .await
             .unwrap_err()
             .to_string();
@@ -2281,7 +3075,16 @@ mod tests {
         config.optimization.response_exclude_fields = Some(vec!["text".to_string()]);
         let client = crate::test_utils::mock_client_with_config(server.uri(), config);
 
-        let result = get_issue("ABC-1", None, true, &client).await.unwrap();
+        let result = get_issue(
+            "ABC-1",
+            None,
+            Some(MarkdownMode::Readable),
+            None,
+            false,
+            &client,
+        )
+        .await
+        .unwrap();
         let description = result["fields"]["description"].as_str().unwrap_or_default();
         assert!(
             !description.contains("sensitive"),
@@ -2306,26 +3109,321 @@ mod tests {
                         "type": "doc",
                         "content": [{
                             "type": "paragraph",
-                            "content": [{ "type": "text", "text": "sensitive" }]
+                            "content": [{ "type": "text", "text": "sensitive" }]
+                        }]
+                    }
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        // `text` lives inside the ADF body. Filtered first it is gone before
+        // conversion; converted first it is already part of the string and no
+        // exclude can reach it.
+        let mut config = crate::test_utils::create_test_config();
+        config.optimization.response_exclude_fields = Some(vec!["text".to_string()]);
+        let client = crate::test_utils::mock_client_with_config(server.uri(), config);
+
+        let result = get_comments("ABC-1", Some(MarkdownMode::Readable), &client)
+            .await
+            .unwrap();
+        let body = result["items"][0]["body"].as_str().unwrap_or_default();
+        assert!(
+            !body.contains("sensitive"),
+            "the excluded field reached the converted body: {result}"
+        );
+    }
+
+    /// A lossless read keeps a mention as a marker the write side restores;
+    /// the readable one flattens it to its display text.
+    #[tokio::test]
+    async fn integ_lossless_comments_keep_a_mention_as_a_marker() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/ABC-1/comment"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "startAt": 0,
+                "total": 1,
+                "comments": [{
+                    "id": "1",
+                    "body": {
+                        "type": "doc",
+                        "content": [{
+                            "type": "paragraph",
+                            "content": [
+                                { "type": "text", "text": "ping " },
+                                { "type": "mention", "attrs": { "id": "557058:abc", "text": "@Jane" } }
+                            ]
+                        }]
+                    }
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = get_comments("ABC-1", Some(MarkdownMode::Lossless), &client)
+            .await
+            .unwrap();
+        let body = result["items"][0]["body"].as_str().unwrap();
+        assert!(body.contains("557058:abc"), "{body}");
+
+        let restored = crate::markdown::markdown_to_adf(body);
+        assert_eq!(
+            restored["content"][0]["content"][1]["attrs"]["id"],
+            "557058:abc"
+        );
+    }
+
+    /// A lossless read writes back what was stored, so a mention stored with
+    /// only its id stays that way in its marker, and no one is looked up.
+    #[tokio::test]
+    async fn integ_lossless_comments_leave_an_unnamed_mention_as_stored() {
+        let server = MockServer::start().await;
+        let mention = json!({ "type": "mention", "attrs": { "id": "557058:abc" } });
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/ABC-1/comment"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "startAt": 0,
+                "total": 1,
+                "comments": [{
+                    "id": "1",
+                    "body": {
+                        "type": "doc",
+                        "content": [{
+                            "type": "paragraph",
+                            "content": [{ "type": "text", "text": "ping " }, mention]
+                        }]
+                    }
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/bulk"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "values": [{ "accountId": "557058:abc", "displayName": "Jane Doe" }],
+                "isLast": true
+            })))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = get_comments("ABC-1", Some(MarkdownMode::Lossless), &client)
+            .await
+            .unwrap();
+        let body = result["items"][0]["body"].as_str().unwrap();
+        assert!(!body.contains("Jane Doe"), "{body}");
+
+        let restored = crate::markdown::markdown_to_adf(body);
+        assert_eq!(restored["content"][0]["content"][1], mention);
+    }
+
+    /// A mention stored with only its id reads as the person's name once the
+    /// bulk lookup answers; without the lookup a reader sees `@557058:abc`.
+    #[tokio::test]
+    async fn integ_markdown_names_an_unnamed_mention() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/ABC-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "key": "ABC-1",
+                "fields": {
+                    "description": {
+                        "type": "doc",
+                        "content": [{
+                            "type": "paragraph",
+                            "content": [
+                                { "type": "text", "text": "ping " },
+                                { "type": "mention", "attrs": { "id": "557058:abc" } }
+                            ]
+                        }]
+                    }
+                }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/bulk"))
+            .and(query_param("accountId", "557058:abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "values": [{ "accountId": "557058:abc", "displayName": "Jane Doe" }],
+                "isLast": true
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = get_issue(
+            "ABC-1",
+            None,
+            Some(MarkdownMode::Readable),
+            None,
+            false,
+            &client,
+        )
+        .await
+        .unwrap();
+        let description = result["fields"]["description"].as_str().unwrap();
+        assert!(description.contains("@Jane Doe"), "{description}");
+    }
+
+    /// The issue read is held to the same rule as its comments.
+    #[tokio::test]
+    async fn integ_lossless_issue_looks_up_no_one() {
+        let server = MockServer::start().await;
+        let mention = json!({ "type": "mention", "attrs": { "id": "557058:abc" } });
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/ABC-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "key": "ABC-1",
+                "fields": {
+                    "description": {
+                        "type": "doc",
+                        "content": [{
+                            "type": "paragraph",
+                            "content": [{ "type": "text", "text": "ping " }, mention]
+                        }]
+                    }
+                }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/bulk"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = get_issue(
+            "ABC-1",
+            None,
+            Some(MarkdownMode::Lossless),
+            None,
+            false,
+            &client,
+        )
+        .await
+        .unwrap();
+        let description = result["fields"]["description"].as_str().unwrap();
+        let restored = crate::markdown::markdown_to_adf(description);
+        assert_eq!(restored["content"][0]["content"][1], mention);
+    }
+
+    /// A Markdown read asks for the attachment list the description's media
+    /// are linked from, and leaves it out of the issue it prints when the
+    /// caller's fields did not name it.
+    #[tokio::test]
+    async fn integ_markdown_links_media_to_the_issue_attachments() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/ABC-1"))
+            .and(query_param(
+                "fields",
+                "key,summary,description,issuetype,status,priority,assignee,reporter,created,updated,project,attachment",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "key": "ABC-1",
+                "fields": {
+                    "description": {
+                        "type": "doc",
+                        "content": [{
+                            "type": "mediaSingle",
+                            "content": [{
+                                "type": "media",
+                                "attrs": { "id": "6e7c", "type": "file", "collection": "c", "alt": "shot.png" }
+                            }]
+                        }]
+                    },
+                    "attachment": [{
+                        "id": "10001",
+                        "filename": "shot.png",
+                        "mimeType": "image/png",
+                        "content": "https://site.atlassian.net/rest/api/3/attachment/content/10001"
+                    }]
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = get_issue(
+            "ABC-1",
+            None,
+            Some(MarkdownMode::Readable),
+            None,
+            false,
+            &client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result["fields"]["description"],
+            "![shot.png](https://site.atlassian.net/rest/api/3/attachment/content/10001)"
+        );
+        assert!(result["fields"].get("attachment").is_none(), "{result}");
+
+        let local = get_issue(
+            "ABC-1",
+            None,
+            Some(MarkdownMode::Readable),
+            Some(Path::new("media")),
+            false,
+            &client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            local["fields"]["description"],
+            "![shot.png](media/shot.png)"
+        );
+    }
+
+    /// The document read asks for the fields its front matter names and puts
+    /// the description under it; an unassigned issue has no `assignee` line.
+    #[tokio::test]
+    async fn integ_issue_document_has_front_matter_and_the_description() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/ABC-1"))
+            .and(query_param(
+                "fields",
+                "summary,description,issuetype,status,assignee,priority,labels,parent,created,updated,attachment",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "key": "ABC-1",
+                "fields": {
+                    "summary": "Fix: the login",
+                    "description": {
+                        "type": "doc",
+                        "content": [{
+                            "type": "paragraph",
+                            "content": [{ "type": "text", "text": "Steps" }]
                         }]
-                    }
-                }]
+                    },
+                    "issuetype": { "name": "Bug" },
+                    "status": { "name": "In Progress" },
+                    "assignee": null,
+                    "priority": { "name": "High" },
+                    "labels": ["auth"],
+                    "parent": { "key": "ABC-0" },
+                    "created": "2026-01-02T03:04:05.000+0000",
+                    "updated": "2026-01-03T03:04:05.000+0000"
+                }
             })))
             .mount(&server)
             .await;
 
-        // `text` lives inside the ADF body. Filtered first it is gone before
-        // conversion; converted first it is already part of the string and no
-        // exclude can reach it.
-        let mut config = crate::test_utils::create_test_config();
-        config.optimization.response_exclude_fields = Some(vec!["text".to_string()]);
-        let client = crate::test_utils::mock_client_with_config(server.uri(), config);
-
-        let result = get_comments("ABC-1", true, &client).await.unwrap();
-        let body = result["items"][0]["body"].as_str().unwrap_or_default();
-        assert!(
-            !body.contains("sensitive"),
-            "the excluded field reached the converted body: {result}"
+        let client = mock_client(server.uri());
+        let document = get_issue_document("ABC-1", MarkdownMode::Readable, None, &client)
+            .await
+            .unwrap();
+        assert_eq!(
+            document,
+            "---\nkey: \"ABC-1\"\ntitle: \"Fix: the login\"\ntype: \"Bug\"\nstatus: \"In Progress\"\npriority: \"High\"\nlabels:\n  - \"auth\"\nparent: \"ABC-0\"\ncreated: \"2026-01-02T03:04:05.000+0000\"\nupdated: \"2026-01-03T03:04:05.000+0000\"\nurl: \"https://test.atlassian.net/browse/ABC-1\"\n---\n\nSteps\n"
         );
     }
 
@@ -2552,6 +3650,41 @@ mod tests {
         assert!(err.contains("JSON object of Jira field names"), "{err}");
     }
 
+    /// A body that breaks the ADF schema is refused before anything is sent,
+    /// naming the node at fault — the whole point of checking offline is that
+    /// Jira's one 400 never has to be read.
+    #[tokio::test]
+    async fn integ_a_body_outside_the_schema_is_never_sent() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(201).set_body_json(json!({ "id": "10007", "key": "PROJ-7" })),
+            )
+            .expect(0)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+        let body = json!({
+            "type": "doc",
+            "version": 1,
+            "content": [{"type": "blockquote", "content": [
+                {"type": "heading", "attrs": {"level": 2}, "content": [{"type": "text", "text": "x"}]}
+            ]}]
+        });
+
+        let err = create_issue("PROJ", "Summary", "Task", body.clone(), None, None, &client)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("/content/0/content/0"), "{err}");
+
+        let err = add_comment("PROJ-1", body, &client)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("/content/0/content/0"), "{err}");
+    }
+
     #[tokio::test]
     async fn integ_create_issue_bails_when_the_envelope_lost_its_key() {
         let server = MockServer::start().await;
@@ -2587,7 +3720,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = search("project = X", 25, None, false, &client)
+        let result = search("project = X", 25, None, false, false, &client)
             .await
             .unwrap();
         assert_eq!(result["count"], 2, "{result}");
@@ -2613,6 +3746,8 @@ mod tests {
         let result = get_issue(
             "PROJ-1",
             Some(vec!["summary&expand=changelog".to_string()]),
+            None,
+            None,
             false,
             &client,
         )
@@ -2687,7 +3822,7 @@ mod tests {
             Some(vec!["nextPageToken".to_string(), "avatarUrls".to_string()]);
         let client = crate::test_utils::mock_client_with_config(server.uri(), config);
 
-        let result = search_all("project = X", None, false, false, &client)
+        let result = search_all("project = X", None, None, false, false, &client)
             .await
             .unwrap();
 
@@ -2714,7 +3849,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let err = search_all("project = X", None, false, false, &client)
+        let err = search_all("project = X", None, None, false, false, &client)
             .await
             .unwrap_err()
             .to_string();
@@ -2740,7 +3875,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let err = search_all("project = X", None, false, false, &client)
+        let err = search_all("project = X", None, None, false, false, &client)
             .await
             .unwrap_err()
             .to_string();
@@ -2856,7 +3991,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = search_all("project = X", None, false, false, &client)
+        let result = search_all("project = X", None, None, false, false, &client)
             .await
             .unwrap();
 
@@ -2896,7 +4031,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let err = search_all("project = X", None, false, false, &client)
+        let err = search_all("project = X", None, None, false, false, &client)
             .await
             .unwrap_err()
             .to_string();
@@ -2945,9 +4080,16 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = get_issue("PROJ-1", Some(vec!["*all".to_string()]), false, &client)
-            .await
-            .unwrap();
+        let result = get_issue(
+            "PROJ-1",
+            Some(vec!["*all".to_string()]),
+            None,
+            None,
+            false,
+            &client,
+        )
+        .await
+        .unwrap();
         assert_eq!(result["key"], "PROJ-1");
     }
 
@@ -3391,6 +4533,37 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn integ_assign_resolves_the_user_and_none_unassigns() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/search"))
+            .and(query_param("query", "ann@x.io"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"accountId": "557058:aaa", "displayName": "Ann Lee"}
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/rest/api/3/issue/MDW-1/assignee"))
+            .and(body_json(json!({ "accountId": "557058:aaa" })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/rest/api/3/issue/MDW-2/assignee"))
+            .and(body_json(json!({ "accountId": null })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        assign_issue("MDW-1", "ann@x.io", &client).await.unwrap();
+        assign_issue("MDW-2", "none", &client).await.unwrap();
+    }
+
     #[tokio::test]
     async fn integ_add_watcher_posts_empty_body() {
         let server = MockServer::start().await;
@@ -3403,7 +4576,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = add_watcher("MDW-207", &client).await;
+        let result = add_watcher("MDW-207", None, &client).await;
         assert!(result.is_ok(), "{:?}", result.err());
     }
 
@@ -3426,7 +4599,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = remove_watcher("MDW-207", &client).await;
+        let result = remove_watcher("MDW-207", None, &client).await;
         assert!(result.is_ok(), "{:?}", result.err());
     }
 
@@ -3597,6 +4770,141 @@ mod tests {
         assert!(result.is_ok(), "{:?}", result.err());
     }
 
+    #[tokio::test]
+    async fn integ_complete_sprint_moves_open_issues_before_closing() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/agile/1.0/sprint/55"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({"id": 55, "state": "active"})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/agile/1.0/sprint/55/issue"))
+            .and(query_param("jql", "statusCategory != Done"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "issues": [{"key": "A-1"}, {"key": "A-3"}],
+                "total": 2
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/agile/1.0/sprint/56/issue"))
+            .and(body_json(json!({ "issues": ["A-1", "A-3"] })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/agile/1.0/sprint/55"))
+            .and(body_json(json!({ "state": "closed" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 55})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = complete_sprint(55, Some(CarryOver::Sprint(56)), &client)
+            .await
+            .unwrap();
+        assert_eq!(result, json!({ "moved": ["A-1", "A-3"] }));
+
+        // The moves land before the close, or the close would find them open.
+        let requests = server.received_requests().await.unwrap();
+        let posts: Vec<&str> = requests
+            .iter()
+            .filter(|r| r.method.as_str() == "POST")
+            .map(|r| r.url.path())
+            .collect();
+        assert_eq!(
+            posts,
+            [
+                "/rest/agile/1.0/sprint/56/issue",
+                "/rest/agile/1.0/sprint/55"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn integ_complete_sprint_leaves_open_sub_tasks_to_their_parent() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/agile/1.0/sprint/55"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({"id": 55, "state": "active"})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/agile/1.0/sprint/55/issue"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "issues": [
+                    {"key": "A-1", "fields": {"issuetype": {"subtask": false}}},
+                    {"key": "A-2", "fields": {"issuetype": {"subtask": true}}}
+                ],
+                "total": 2
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/agile/1.0/backlog/issue"))
+            .and(body_json(json!({ "issues": ["A-1"] })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/agile/1.0/sprint/55"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 55})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let result = complete_sprint(55, Some(CarryOver::Backlog), &client)
+            .await
+            .unwrap();
+        assert_eq!(result, json!({ "moved": ["A-1"] }));
+    }
+
+    #[tokio::test]
+    async fn integ_start_sprint_names_the_missing_date() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/agile/1.0/sprint/57"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": 57, "state": "future", "startDate": "2024-06-03T09:00:00.000+09:00"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/agile/1.0/sprint/57"))
+            .and(body_json(json!({
+                "endDate": "2024-06-17T18:00:00.000+09:00", "state": "active"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 57})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let err = start_sprint(57, &SprintFields::default(), &client)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("no end date") && err.contains("--end-date"),
+            "got: {err}"
+        );
+
+        let fields = SprintFields {
+            end_date: Some("2024-06-17T18:00:00.000+09:00".into()),
+            ..Default::default()
+        };
+        start_sprint(57, &fields, &client).await.unwrap();
+    }
+
     #[tokio::test]
     async fn integ_assign_issues_to_epic_encodes_path_segment() {
         let server = MockServer::start().await;
@@ -3683,7 +4991,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = get_comments("MDW 207", false, &client).await;
+        let result = get_comments("MDW 207", None, &client).await;
         assert!(result.is_ok(), "{:?}", result.err());
     }
 
@@ -3717,7 +5025,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = get_comments("ABC-1", false, &client).await.unwrap();
+        let result = get_comments("ABC-1", None, &client).await.unwrap();
         let ids: Vec<&str> = result["items"]
             .as_array()
             .unwrap()
@@ -3752,7 +5060,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let err = get_comments("ABC-1", false, &client)
+        let err = get_comments("ABC-1", None, &client)
             .await
             .unwrap_err()
             .to_string();
@@ -3786,7 +5094,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = get_comments("ABC-1", false, &client).await.unwrap();
+        let result = get_comments("ABC-1", None, &client).await.unwrap();
         assert_eq!(result["items"].as_array().unwrap().len(), 2);
     }
 
@@ -3803,7 +5111,7 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let err = get_comments("ABC-1", false, &client)
+        let err = get_comments("ABC-1", None, &client)
             .await
             .unwrap_err()
             .to_string();
@@ -4128,10 +5436,149 @@ mod tests {
             .await;
 
         let client = mock_client(server.uri());
-        let result = transition_issue("MDW-1", "31", &client).await.unwrap();
+        let result = transition_issue("MDW-1", "31", None, None, Value::Null, &client)
+            .await
+            .unwrap();
         assert_eq!(result, json!({}));
     }
 
+    fn done_transition() -> Value {
+        json!({ "transitions": [
+            {"id": "11", "name": "Start Progress", "to": {"name": "In Progress"}, "fields": {}},
+            {"id": "31", "name": "Resolve", "to": {"name": "Done"}, "fields": {
+                "resolution": {
+                    "required": true, "hasDefaultValue": false, "name": "Resolution",
+                    "allowedValues": [{"name": "Done"}, {"name": "Won't Do"}]
+                }
+            }}
+        ]})
+    }
+
+    #[tokio::test]
+    async fn integ_transition_by_status_name_sends_screen_fields_and_comment() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/MDW-1/transitions"))
+            .and(query_param("expand", "transitions.fields"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(done_transition()))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/issue/MDW-1/transitions"))
+            .and(body_partial_json(json!({
+                "transition": {"id": "31"},
+                "fields": {"resolution": {"name": "Done"}},
+                "update": {"comment": [{"add": {"body": {"type": "doc"}}}]}
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        transition_issue(
+            "MDW-1",
+            "done",
+            Some("Done"),
+            None,
+            json!("Shipped"),
+            &client,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn integ_transition_looks_up_a_person_named_in_two_fields_once() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/MDW-1/transitions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(done_transition()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/search"))
+            .and(query_param("query", "ann@example.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"accountId": "557058:ann", "emailAddress": "ann@example.com"}
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/issue/MDW-1/transitions"))
+            .and(body_partial_json(json!({"fields": {
+                "customfield_10050": {"accountId": "557058:ann"},
+                "customfield_10051": {"accountId": "557058:ann"}
+            }})))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        transition_issue(
+            "MDW-1",
+            "31",
+            Some("Done"),
+            Some(json!({
+                "customfield_10050": {"accountId": "ann@example.com"},
+                "customfield_10051": {"accountId": "ann@example.com"}
+            })),
+            Value::Null,
+            &client,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn integ_transition_missing_a_required_field_names_it_before_sending() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/MDW-1/transitions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(done_transition()))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(server.uri());
+        let err = transition_issue("MDW-1", "Resolve", None, None, Value::Null, &client)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("Resolution (resolution): one of Done, Won't Do"),
+            "{err}"
+        );
+
+        let err = transition_issue("MDW-1", "Closed", None, None, Value::Null, &client)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Resolve → Done (31)"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn integ_transition_refuses_a_blank_argument_before_any_request() {
+        let server = MockServer::start().await;
+        let client = mock_client(server.uri());
+        for blank in ["", "  "] {
+            let err = transition_issue("MDW-1", blank, None, None, Value::Null, &client)
+                .await
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("give a transition id or name"),
+                "{err}"
+            );
+        }
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn integ_update_issue_encodes_issue_key() {
         let server = MockServer::start().await;
@@ -0,0 +1,463 @@
+//! An issue as it was at an earlier moment.
+//!
+//! Jira keeps no snapshots, only the changelog: every edit with each field's
+//! value before and after. Starting from the issue as it is now and undoing
+//! every edit made after the moment asked about, newest first, leaves each
+//! field at the value it had then.
+//!
+//! The changelog records values as an id and a display string, not as the
+//! objects the issue itself carries, so a rewound field is rebuilt in the
+//! shape its current value has — `{"id", "name"}` for a status,
+//! `{"accountId", "displayName"}` for a person, a number for story points —
+//! with only what the changelog kept. Fields the changelog does not record at
+//! all (comments, votes, `updated`) keep their current value and are listed
+//! under `unreconstructed`, as is anything rewound that could not be rebuilt
+//! faithfully.
+
+use crate::client::ApiClient;
+use crate::jira::api::{fetch_changelog, get_issue};
+use crate::jira::field_names::FieldResolver;
+use anyhow::Result;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+
+/// Fields whose changes never reach the changelog, or reach it in a form
+/// that cannot be undone into the field's value.
+const UNRECORDED: &[&str] = &[
+    "updated",
+    "lastViewed",
+    "votes",
+    "watches",
+    "comment",
+    "worklog",
+    "attachment",
+    "issuelinks",
+    "subtasks",
+    "progress",
+    "aggregateprogress",
+    "aggregatetimespent",
+    "aggregatetimeestimate",
+    "aggregatetimeoriginalestimate",
+    "statuscategorychangedate",
+];
+
+/// `issue_key` as it stood at `as_of` — a day (`2024-03-01`, its first
+/// moment) or a time (`2024-03-01T17:30`, `2024-03-01T17:30:00+09:00`). A
+/// time without an offset is read in the offset Jira writes the issue's
+/// dates in, which is the caller's own time zone.
+pub async fn get_issue_as_of(
+    issue_key: &str,
+    api_fields: Option<Vec<String>>,
+    as_of: &str,
+    name_fields: bool,
+    client: &ApiClient,
+) -> Result<Value> {
+    let Some(moment) = Moment::parse(as_of) else {
+        anyhow::bail!(
+            "--as-of takes a day or a time, e.g. 2024-03-01 or 2024-03-01T17:30:00+09:00 — \
+             got '{as_of}'"
+        );
+    };
+    let issue = get_issue(issue_key, api_fields, None, None, false, client).await?;
+    let changelog = fetch_changelog(issue_key, client).await?;
+
+    // Items that name their field only by its display name ("Sprint" on
+    // some sites) are looked up once, all together.
+    let mut resolver = FieldResolver::new(client.config());
+    let mut ids_by_name = HashMap::new();
+    for name in unnamed_fields(&changelog) {
+        if let Ok(mut ids) = resolver.ids(vec![name.clone()], client).await {
+            ids_by_name.insert(name, ids.remove(0));
+        }
+    }
+
+    let mut rewound = rewind(&issue, &changelog, moment, as_of, &ids_by_name)?;
+    if name_fields {
+        resolver
+            .name_custom_fields(std::iter::once(&mut rewound), client)
+            .await?;
+    }
+    Ok(rewound)
+}
+
+/// The display names of changelog items that carry no `fieldId`.
+fn unnamed_fields(changelog: &[Value]) -> Vec<String> {
+    let mut names: Vec<String> = changelog
+        .iter()
+        .flat_map(|entry| entry["items"].as_array().into_iter().flatten())
+        .filter(|item| item["fieldId"].as_str().is_none())
+        .filter_map(|item| item["field"].as_str().map(str::to_string))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// The rewind itself, without I/O: `issue` is the issue now, `changelog`
+/// every entry it has, `ids_by_name` the field id for each item that names
+/// its field only by display name.
+fn rewind(
+    issue: &Value,
+    changelog: &[Value],
+    moment: Moment,
+    as_of: &str,
+    ids_by_name: &HashMap<String, String>,
+) -> Result<Value> {
+    // The issue's own dates say which offset a bare `--as-of` time is in.
+    let offset = issue
+        .pointer("/fields/created")
+        .and_then(Value::as_str)
+        .and_then(Moment::parse)
+        .and_then(|created| created.offset)
+        .unwrap_or(0);
+    let at = moment.seconds(offset);
+    if let Some(created) = issue
+        .pointer("/fields/created")
+        .and_then(Value::as_str)
+        .and_then(Moment::parse)
+        && created.seconds(0) > at
+    {
+        anyhow::bail!(
+            "{} did not exist at {as_of}: it was created at {}",
+            issue["key"].as_str().unwrap_or("the issue"),
+            issue["fields"]["created"].as_str().unwrap_or_default()
+        );
+    }
+
+    let current = issue["fields"].as_object().cloned().unwrap_or_default();
+    let mut fields = current.clone();
+    let mut changed: Vec<String> = Vec::new();
+    let mut unreconstructed = Map::new();
+
+    // Newest first, so the last value written for a field is the one it had
+    // before the earliest edit after the moment.
+    let mut later: Vec<&Value> = changelog
+        .iter()
+        .filter(|entry| {
+            entry["created"]
+                .as_str()
+                .and_then(Moment::parse)
+                .is_some_and(|when| when.seconds(0) > at)
+        })
+        .collect();
+    later.sort_by_key(|entry| {
+        std::cmp::Reverse(
+            entry["created"]
+                .as_str()
+                .and_then(Moment::parse)
+                .map(|when| when.seconds(0))
+                .unwrap_or_default(),
+        )
+    });
+
+    for item in later
+        .iter()
+        .flat_map(|entry| entry["items"].as_array().into_iter().flatten())
+    {
+        let name = item["field"].as_str().unwrap_or("?");
+        let id = match item["fieldId"].as_str() {
+            Some(id) => id.to_string(),
+            None => match ids_by_name.get(name) {
+                Some(id) => id.clone(),
+                None => {
+                    unreconstructed.insert(
+                        name.to_string(),
+                        json!("changed after the moment, but its field id is unknown"),
+                    );
+                    continue;
+                }
+            },
+        };
+        // A field not fetched is not shown, rewound or not.
+        if UNRECORDED.contains(&id.as_str()) || !current.contains_key(&id) {
+            continue;
+        }
+        if id == "description" || id == "environment" {
+            unreconstructed.insert(
+                id.clone(),
+                json!("rewound to the changelog's plain text; the document itself is not kept"),
+            );
+        }
+        let value = earlier_value(&id, current.get(&id).unwrap_or(&Value::Null), item);
+        fields.insert(id.clone(), value);
+        if !changed.contains(&id) {
+            changed.push(id);
+        }
+    }
+
+    for id in UNRECORDED {
+        if current.contains_key(*id) {
+            unreconstructed.insert(
+                id.to_string(),
+                json!("not recorded in the changelog; shown as it is now"),
+            );
+        }
+    }
+
+    let mut rewound = issue.clone();
+    rewound["fields"] = Value::Object(fields);
+    rewound["asOf"] = json!(as_of);
+    rewound["rewound"] = json!(changed);
+    rewound["unreconstructed"] = Value::Object(unreconstructed);
+    Ok(rewound)
+}
+
+/// The value a changelog item says the field had before it, in the shape
+/// the field's value has now. A field that is empty now has no shape to
+/// copy, so the few system fields whose shape is known are named here.
+fn earlier_value(id: &str, now: &Value, item: &Value) -> Value {
+    let raw = item["from"].as_str().filter(|s| !s.is_empty());
+    let text = item["fromString"].as_str().filter(|s| !s.is_empty());
+    if raw.is_none() && text.is_none() {
+        return Value::Null;
+    }
+    let list = |s: Option<&str>| -> Vec<String> {
+        s.map(|s| {
+            s.trim_matches(|c| c == '[' || c == ']')
+                .split(',')
+                .map(|part| part.trim().to_string())
+                .filter(|part| !part.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+    };
+    let user = || json!({ "accountId": raw, "displayName": text });
+    let named = || json!({ "id": raw, "name": text });
+
+    match (id, now) {
+        ("assignee" | "reporter", _) => user(),
+        ("status" | "priority" | "resolution" | "issuetype", _) => named(),
+        ("labels", _) => json!(
+            text.unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+        ),
+        (_, Value::Object(map)) if map.contains_key("accountId") => user(),
+        (_, Value::Object(map)) if map.contains_key("value") => {
+            json!({ "id": raw, "value": text })
+        }
+        (_, Value::Object(_)) => named(),
+        (_, Value::Number(_)) => text
+            .and_then(|t| t.parse::<f64>().ok())
+            .map(|n| json!(n))
+            .unwrap_or(Value::Null),
+        (_, Value::Array(items)) if items.iter().all(Value::is_string) && !items.is_empty() => {
+            json!(list(text))
+        }
+        ("components" | "fixVersions" | "versions", _) | (_, Value::Array(_)) => {
+            let ids = list(raw);
+            let names = list(text);
+            json!(
+                (0..ids.len().max(names.len()))
+                    .map(|i| json!({ "id": ids.get(i), "name": names.get(i) }))
+                    .collect::<Vec<_>>()
+            )
+        }
+        _ => match (raw, text) {
+            (None, Some(text)) => text
+                .parse::<f64>()
+                .ok()
+                .filter(|_| now.is_number())
+                .map(|n| json!(n))
+                .unwrap_or_else(|| json!(text)),
+            (Some(raw), Some(text)) if raw != text => json!({ "id": raw, "value": text }),
+            (raw, text) => json!(text.or(raw)),
+        },
+    }
+}
+
+/// A point in time as written, with the offset it was written in, if any.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Moment {
+    /// Seconds since the epoch, as if the time were UTC.
+    local: i64,
+    /// Seconds east of UTC.
+    offset: Option<i64>,
+}
+
+impl Moment {
+    /// `YYYY-MM-DD`, optionally followed by `T` (or a space) and
+    /// `HH:MM[:SS[.fff]]`, optionally followed by `Z`, `±HH:MM` or `±HHMM` —
+    /// which covers both what a person types and what Jira writes.
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let number = |part: &str| part.parse::<i64>().ok();
+        let date = s.get(..10)?;
+        let mut parts = date.split('-');
+        let (year, month, day) = (
+            number(parts.next()?)?,
+            number(parts.next()?)?,
+            number(parts.next()?)?,
+        );
+        if date.as_bytes()[4] != b'-' || date.as_bytes()[7] != b'-' {
+            return None;
+        }
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        let mut seconds = days_from_civil(year, month, day) * 86_400;
+
+        let rest = &s[10..];
+        let mut offset = None;
+        if let Some(time) = rest.strip_prefix(['T', ' ']) {
+            let split = time.find(['Z', '+', '-']).unwrap_or(time.len());
+            let (clock, zone) = time.split_at(split);
+            let clock = clock.split('.').next()?;
+            let mut units = clock.split(':');
+            let hours = number(units.next()?)?;
+            let minutes = number(units.next()?)?;
+            let secs = units.next().map(number).unwrap_or(Some(0))?;
+            if units.next().is_some() || hours > 23 || minutes > 59 || secs > 60 {
+                return None;
+            }
+            seconds += hours * 3600 + minutes * 60 + secs;
+            offset = match zone {
+                "" => None,
+                "Z" => Some(0),
+                _ => {
+                    let sign = if zone.starts_with('-') { -1 } else { 1 };
+                    let digits: String = zone[1..].chars().filter(|c| *c != ':').collect();
+                    // Checked before slicing by byte: a non-ASCII character
+                    // would put a slice inside it.
+                    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                        return None;
+                    }
+                    Some(sign * (number(&digits[..2])? * 3600 + number(&digits[2..])? * 60))
+                }
+            };
+        } else if !rest.is_empty() {
+            return None;
+        }
+        Some(Self {
+            local: seconds,
+            offset,
+        })
+    }
+
+    /// Seconds since the epoch in UTC, reading a moment written without an
+    /// offset as being in `default_offset`.
+    fn seconds(self, default_offset: i64) -> i64 {
+        self.local - self.offset.unwrap_or(default_offset)
+    }
+}
+
+/// Days from 1970-01-01 to a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// As `/issue/P-1` and `/issue/P-1/changelog` answered: created on the
+    /// 1st, started and assigned on the 2nd, pointed and sprinted on the 3rd,
+    /// resolved on the 5th.
+    fn recorded() -> (Value, Vec<Value>) {
+        let issue = json!({
+            "key": "P-1",
+            "fields": {
+                "created": "2024-03-01T09:00:00.000+0900",
+                "updated": "2024-03-05T18:00:00.000+0900",
+                "summary": "Checkout fails",
+                "status": {"id": "3", "name": "Done"},
+                "assignee": {"accountId": "557058:bbb", "displayName": "Bo"},
+                "labels": ["api", "urgent"],
+                "customfield_10016": 5.0,
+                "customfield_10020": [{"id": 12, "name": "Sprint 12"}],
+                "comment": {"comments": []}
+            }
+        });
+        let changelog = vec![
+            json!({"created": "2024-03-02T10:00:00.000+0900", "items": [
+                {"field": "status", "fieldId": "status",
+                 "from": "1", "fromString": "To Do", "to": "2", "toString": "In Progress"},
+                {"field": "assignee", "fieldId": "assignee",
+                 "from": null, "fromString": null, "to": "557058:aaa", "toString": "Ann"}
+            ]}),
+            json!({"created": "2024-03-03T10:00:00.000+0900", "items": [
+                {"field": "Story Points", "fieldId": "customfield_10016",
+                 "from": null, "fromString": "3", "to": null, "toString": "5"},
+                {"field": "Sprint",
+                 "from": "", "fromString": "", "to": "12", "toString": "Sprint 12"},
+                {"field": "labels", "fieldId": "labels",
+                 "fromString": "api", "toString": "api urgent"},
+                {"field": "assignee", "fieldId": "assignee",
+                 "from": "557058:aaa", "fromString": "Ann", "to": "557058:bbb", "toString": "Bo"}
+            ]}),
+            json!({"created": "2024-03-05T18:00:00.000+0900", "items": [
+                {"field": "status", "fieldId": "status",
+                 "from": "2", "fromString": "In Progress", "to": "3", "toString": "Done"},
+                {"field": "Flagged", "from": null, "fromString": null, "to": "x", "toString": "Impediment"}
+            ]}),
+        ];
+        (issue, changelog)
+    }
+
+    fn at(as_of: &str) -> Result<Value> {
+        let (issue, changelog) = recorded();
+        let ids = HashMap::from([("Sprint".to_string(), "customfield_10020".to_string())]);
+        rewind(
+            &issue,
+            &changelog,
+            Moment::parse(as_of).unwrap(),
+            as_of,
+            &ids,
+        )
+    }
+
+    #[test]
+    fn test_rewinding_undoes_every_edit_after_the_moment() {
+        let then = at("2024-03-02T12:00").unwrap();
+        assert_eq!(
+            then["fields"]["status"],
+            json!({"id": "2", "name": "In Progress"})
+        );
+        assert_eq!(
+            then["fields"]["assignee"],
+            json!({"accountId": "557058:aaa", "displayName": "Ann"})
+        );
+        assert_eq!(then["fields"]["customfield_10016"], json!(3.0));
+        assert_eq!(then["fields"]["customfield_10020"], Value::Null);
+        assert_eq!(then["fields"]["labels"], json!(["api"]));
+        assert_eq!(then["fields"]["summary"], "Checkout fails");
+        assert_eq!(
+            then["rewound"],
+            json!([
+                "status",
+                "customfield_10016",
+                "customfield_10020",
+                "labels",
+                "assignee"
+            ])
+        );
+        assert_eq!(
+            then["unreconstructed"]["Flagged"],
+            "changed after the moment, but its field id is unknown"
+        );
+        assert!(then["unreconstructed"]["comment"].is_string());
+    }
+
+    #[test]
+    fn test_offsets_are_honoured_and_the_created_moment_bounds_the_rewind() {
+        // 10:30 in Seoul is after the 10:00 edit; 10:30 UTC is 19:30 there,
+        // after the second day's edit too, but before the resolution.
+        let seoul = at("2024-03-02T10:30").unwrap();
+        assert_eq!(seoul["fields"]["status"]["name"], "In Progress");
+        let utc = at("2024-03-03T10:30Z").unwrap();
+        assert_eq!(utc["fields"]["customfield_10016"], json!(5.0));
+        assert_eq!(utc["fields"]["status"]["name"], "In Progress");
+
+        let before = at("2024-02-28").unwrap_err();
+        assert!(before.to_string().contains("did not exist at 2024-02-28"));
+        assert!(Moment::parse("March 1").is_none());
+        assert!(Moment::parse("2024-03-01T25:00").is_none());
+        assert!(Moment::parse("2024-03-01T10:00+1é1").is_none());
+        assert!(Moment::parse("2024-03-01T10:00+-100").is_none());
+    }
+}
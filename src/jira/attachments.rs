@@ -0,0 +1,286 @@
+//! The files attached to an issue: listed, uploaded, downloaded, deleted.
+//!
+//! Uploads share Confluence's multipart machinery — the same `file` part,
+//! the same extension-to-type mapping, the same XSRF-bypass header — so a
+//! file attached through either product is typed the same way. Downloads
+//! are streamed to disk, never held whole in memory: an attachment can be
+//! far larger than any response this tool otherwise reads.
+
+use crate::client::{ApiClient, Service};
+use crate::http_utils::{
+    DOWNLOAD_TIMEOUT, Destination, copy_response, encode_path_segment, file_part, local_file_name,
+    save_response,
+};
+use crate::jira::api::list_envelope;
+use crate::response::{WHOLE_BODY, require_array, require_field};
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The attachments on an issue, as Jira describes them: id, filename,
+/// size, type, author and creation time.
+pub async fn list_attachments(issue_key: &str, client: &ApiClient) -> Result<Value> {
+    Ok(list_envelope(
+        issue_attachments(issue_key, client).await?,
+        client,
+    ))
+}
+
+async fn issue_attachments(issue_key: &str, client: &ApiClient) -> Result<Vec<Value>> {
+    let url = format!("/rest/api/3/issue/{}", encode_path_segment(issue_key));
+    let request = client
+        .get(Service::Jira, &url)
+        .await?
+        .query(&[("fields", "attachment")]);
+    let response = client.execute("list attachments", request).await?;
+
+    let data: Value = response.json().await?;
+    require_array(&data, "/fields/attachment", "list attachments")
+}
+
+/// Attach `files` to an issue in one request; Jira answers with one
+/// description per file, in the order sent.
+///
+/// `content_type` overrides the extension-mapped type of every file, so it
+/// is only useful with one file, or several of the same kind.
+pub async fn upload_attachments(
+    issue_key: &str,
+    files: &[String],
+    content_type: Option<&str>,
+    client: &ApiClient,
+) -> Result<Value> {
+    let mut form = reqwest::multipart::Form::new();
+    for file in files {
+        form = form.part("file", file_part(file, content_type)?);
+    }
+
+    let url = format!(
+        "/rest/api/3/issue/{}/attachments",
+        encode_path_segment(issue_key)
+    );
+    // Jira refuses a multipart POST without this header as a possible
+    // cross-site request, exactly as Confluence does.
+    let request = client
+        .post(Service::Jira, &url)
+        .await?
+        .header("X-Atlassian-Token", "no-check")
+        .multipart(form);
+    let response = client.execute("upload attachment", request).await?;
+
+    let data: Value = response.json().await?;
+    Ok(list_envelope(
+        require_array(&data, WHOLE_BODY, "upload attachment")?,
+        client,
+    ))
+}
+
+/// Save one attachment, by id.
+pub async fn download_attachment(
+    attachment_id: &str,
+    destination: Destination<'_>,
+    client: &ApiClient,
+) -> Result<Value> {
+    let target = match destination {
+        Destination::Stdout => {
+            let response = content(attachment_id, client).await?;
+            copy_response(response, &mut std::io::stdout()).await?;
+            // As a streamed listing: stdout already holds the answer.
+            return Ok(Value::Null);
+        }
+        Destination::File(path) => path.to_path_buf(),
+        Destination::Dir(dir) => {
+            // The name is only known from the attachment's metadata.
+            let url = format!(
+                "/rest/api/3/attachment/{}",
+                encode_path_segment(attachment_id)
+            );
+            let request = client.get(Service::Jira, &url).await?;
+            let response = client.execute("get attachment", request).await?;
+            let meta: Value = response.json().await?;
+            let filename = require_field(&meta, "/filename", "get attachment")?;
+            dir.join(local_file_name(
+                filename.as_str().unwrap_or_default(),
+                attachment_id,
+            ))
+        }
+    };
+    let saved = save(attachment_id, &target, client).await?;
+    Ok(json!({ "items": [saved] }))
+}
+
+/// Save every attachment on an issue into `dir`, under the names they were
+/// uploaded with.
+///
+/// Jira lets one issue carry several files of the same name — a screenshot
+/// uploaded twice is two attachments — and saving them all under that one
+/// name would keep only the last. Any name that repeats is saved as
+/// `<id>-<name>` instead, for each of its files, so nothing is overwritten
+/// and each file can still be traced to its attachment.
+pub async fn download_all(issue_key: &str, dir: &Path, client: &ApiClient) -> Result<Value> {
+    let attachments = issue_attachments(issue_key, client).await?;
+    let named: Vec<(String, String)> = attachments
+        .iter()
+        .map(|attachment| {
+            let id = require_field(attachment, "/id", "list attachments")?;
+            let id = id.as_str().unwrap_or_default().to_string();
+            let name = local_file_name(attachment["filename"].as_str().unwrap_or_default(), &id);
+            Ok((id, name))
+        })
+        .collect::<Result<_>>()?;
+
+    let mut uses: HashMap<&str, usize> = HashMap::new();
+    for (_, name) in &named {
+        *uses.entry(name).or_default() += 1;
+    }
+
+    let mut saved = Vec::with_capacity(named.len());
+    for (id, name) in &named {
+        let file = if uses[name.as_str()] > 1 {
+            format!("{id}-{name}")
+        } else {
+            name.clone()
+        };
+        saved.push(save(id, &dir.join(file), client).await?);
+    }
+    Ok(json!({ "items": saved }))
+}
+
+async fn save(attachment_id: &str, target: &Path, client: &ApiClient) -> Result<Value> {
+    let response = content(attachment_id, client).await?;
+    let bytes = save_response(response, target)
+        .await
+        .with_context(|| format!("Failed to download attachment {attachment_id}"))?;
+    Ok(json!({
+        "id": attachment_id,
+        "path": target.display().to_string(),
+        "size": bytes,
+    }))
+}
+
+async fn content(attachment_id: &str, client: &ApiClient) -> Result<reqwest::Response> {
+    let url = format!(
+        "/rest/api/3/attachment/content/{}",
+        encode_path_segment(attachment_id)
+    );
+    // The content endpoint redirects to Atlassian's media store; the client
+    // follows it, and does not carry the Authorization header across hosts —
+    // the redirect's URL is itself the credential.
+    let request = client
+        .get(Service::Jira, &url)
+        .await?
+        .timeout(DOWNLOAD_TIMEOUT);
+    client.execute("download attachment", request).await
+}
+
+/// Delete one attachment, by id. Jira keeps no copy of it.
+pub async fn delete_attachment(attachment_id: &str, client: &ApiClient) -> Result<Value> {
+    let url = format!(
+        "/rest/api/3/attachment/{}",
+        encode_path_segment(attachment_id)
+    );
+    let request = client.delete(Service::Jira, &url).await?;
+    client.execute("delete attachment", request).await?;
+    Ok(json!({}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        create_test_config, mock_client, mock_client_with_config, slow_body_server,
+    };
+    use std::time::Duration;
+    use wiremock::matchers::{body_string_contains, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn integ_upload_attachments_posts_every_file_with_token_header() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/issue/P-1/attachments"))
+            .and(header("X-Atlassian-Token", "no-check"))
+            .and(body_string_contains("filename=\"notes.txt\""))
+            .and(body_string_contains("text/plain"))
+            .and(body_string_contains("filename=\"shot.png\""))
+            .and(body_string_contains("image/png"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"id": "10", "filename": "notes.txt"},
+                {"id": "11", "filename": "shot.png"}
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let files: Vec<String> = ["notes.txt", "shot.png"]
+            .iter()
+            .map(|name| {
+                let file = dir.path().join(name);
+                std::fs::write(&file, b"hello").unwrap();
+                file.to_str().unwrap().to_string()
+            })
+            .collect();
+
+        let client = mock_client(server.uri());
+        let result = upload_attachments("P-1", &files, None, &client)
+            .await
+            .unwrap();
+        assert_eq!(result["items"][1]["id"], "11");
+    }
+
+    #[tokio::test]
+    async fn integ_download_all_streams_each_file_and_separates_repeated_names() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/P-1"))
+            .and(query_param("fields", "attachment"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "fields": {"attachment": [
+                    {"id": "10", "filename": "shot.png"},
+                    {"id": "11", "filename": "shot.png"},
+                    {"id": "12", "filename": "../notes.txt"}
+                ]}
+            })))
+            .mount(&server)
+            .await;
+        for (id, body) in [("10", "first"), ("11", "second"), ("12", "notes")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/rest/api/3/attachment/content/{id}")))
+                .respond_with(ResponseTemplate::new(200).set_body_string(body))
+                .mount(&server)
+                .await;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = mock_client(server.uri());
+        let result = download_all("P-1", dir.path(), &client).await.unwrap();
+
+        let read = |name: &str| std::fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("10-shot.png"), "first");
+        assert_eq!(read("11-shot.png"), "second");
+        assert_eq!(read("notes.txt"), "notes");
+        assert_eq!(result["items"][1]["size"], 6);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn integ_download_outlasts_the_request_timeout_while_the_body_flows() {
+        // Ten chunks 100 ms apart: a second in all, where the request timeout
+        // is 300 ms — which no one read comes near.
+        const CHUNKS: &[&str] = &["0123456789"; 10];
+        let base_url = slow_body_server(CHUNKS, Duration::from_millis(100)).await;
+        let mut config = create_test_config();
+        config.performance.request_timeout_ms = 300;
+        let client = mock_client_with_config(base_url, config);
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("big.bin");
+        let result = download_attachment("10", Destination::File(&target), &client)
+            .await
+            .unwrap();
+
+        assert_eq!(result["items"][0]["size"], 100);
+        assert_eq!(std::fs::read(&target).unwrap().len(), 100);
+    }
+}
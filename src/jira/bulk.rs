@@ -0,0 +1,1013 @@
+//! Acting on many issues at once: creating them from a file, and editing
+//! every issue a query matches.
+//!
+//! `jira create --from` reads issue specs from JSONL, CSV or TOML and sends
+//! them through `/rest/api/3/issue/bulk`, fifty to a request. Every format
+//! says the same thing in the same keys — `project`, `summary`, `type`,
+//! `description`, `parent`, `ref` — and any other key is a field, by id or by
+//! name. A row can name another row as its parent with `parent = "#<ref>"`,
+//! where `<ref>` is that row's `ref`: the parent is created in an earlier
+//! request, and its key is filled in before the child is sent.
+//!
+//! The whole file is read and checked before anything is created, so a typo
+//! on the last row does not leave the first forty-nine half-seeded. Once
+//! creation starts, each row answers for itself: the result maps every row to
+//! the key it was created as or the error it was refused with.
+//!
+//! `jira bulk edit` is the other direction: the keys come from a JQL walk and
+//! one edit is applied to each, a few at a time. It answers the same way, one
+//! item per issue, and names the failures as a query that matches only them.
+
+use crate::client::{ApiClient, ApiError, Service};
+use crate::http_utils::encode_path_segment;
+use crate::jira::adf;
+use crate::jira::api::{apply_project_filter, error_summary, new_issue_fields, search_up_to};
+use crate::jira::field_names::FieldResolver;
+use crate::jira::users::UserLookup;
+use crate::response::{require_array, require_field};
+use anyhow::{Context, Result};
+use futures_util::stream::{self, StreamExt};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Issues per bulk request — the most `/rest/api/3/issue/bulk` accepts.
+const BULK_CREATE_LIMIT: usize = 50;
+
+/// What a row's parent is: an issue that already exists, or a row above it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Parent {
+    Key(String),
+    Row(String),
+}
+
+/// One issue to create, as read from the file.
+#[derive(Debug, Clone)]
+pub struct IssueSpec {
+    /// 1-based, counting records rather than lines: a CSV header is not a
+    /// row, and neither is a blank JSONL line.
+    pub row: usize,
+    pub reference: Option<String>,
+    pub project: String,
+    pub summary: String,
+    pub issue_type: String,
+    /// `null` when the row has none. A string is kept as written for the
+    /// caller to read in its `--input-format`.
+    pub description: Value,
+    pub parent: Option<Parent>,
+    pub fields: Map<String, Value>,
+}
+
+/// Reads the issue specs in `path`, chosen by extension: `.jsonl`/`.ndjson`
+/// (one object per line), `.csv` (a header row naming the keys), or `.toml`
+/// (an `[[issues]]` array of tables). `rename` maps a key as the file writes
+/// it to the key it stands for, so a CSV exported from elsewhere can be used
+/// without editing its header (`Title` → `summary`).
+pub fn read_issue_specs(path: &Path, rename: &HashMap<String, String>) -> Result<Vec<IssueSpec>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    let records = match extension.as_deref() {
+        Some("jsonl" | "ndjson") => jsonl_records(&content)?,
+        Some("csv") => csv_records(&content)?,
+        Some("toml") => toml_records(&content)?,
+        _ => anyhow::bail!(
+            "{path:?}: --from reads .jsonl, .csv or .toml files, chosen by the extension"
+        ),
+    };
+    if records.is_empty() {
+        anyhow::bail!("{path:?} has no issues in it");
+    }
+
+    let specs = records
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let record = record
+                .into_iter()
+                .map(|(key, value)| (rename.get(&key).cloned().unwrap_or(key), value))
+                .collect();
+            spec(i + 1, record).with_context(|| format!("{path:?}, row {}", i + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    check_references(&specs).with_context(|| format!("{path:?}"))?;
+    Ok(specs)
+}
+
+fn jsonl_records(content: &str) -> Result<Vec<Map<String, Value>>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| match serde_json::from_str(line) {
+            Ok(Value::Object(record)) => Ok(record),
+            Ok(other) => anyhow::bail!("line {}: expected a JSON object, got {other}", n + 1),
+            Err(e) => anyhow::bail!("line {}: {e}", n + 1),
+        })
+        .collect()
+}
+
+/// A cell becomes a string, except under a field column, where one that
+/// parses as a JSON number, boolean, array or object is taken as that — a
+/// story-point estimate is a number to Jira, and `["api","ui"]` is the only
+/// way a cell can say a list. An empty cell is an absent value.
+fn csv_records(content: &str) -> Result<Vec<Map<String, Value>>> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .context("Failed to read the CSV header row")?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+
+    let mut records = Vec::new();
+    for (i, row) in reader.records().enumerate() {
+        let row = row.with_context(|| format!("CSV row {}", i + 1))?;
+        let mut record = Map::new();
+        for (header, cell) in headers.iter().zip(row.iter()) {
+            if cell.is_empty() {
+                continue;
+            }
+            let value = match serde_json::from_str::<Value>(cell) {
+                Ok(
+                    parsed @ (Value::Number(_)
+                    | Value::Bool(_)
+                    | Value::Array(_)
+                    | Value::Object(_)),
+                ) if !SPEC_KEYS.contains(&header.as_str()) => parsed,
+                _ => Value::String(cell.to_string()),
+            };
+            record.insert(header.clone(), value);
+        }
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn toml_records(content: &str) -> Result<Vec<Map<String, Value>>> {
+    let document: toml::Table = toml::from_str(content).context("Failed to parse the TOML")?;
+    let Some(issues) = document.get("issues") else {
+        anyhow::bail!("expected an [[issues]] array of tables");
+    };
+    match serde_json::to_value(issues)? {
+        Value::Array(issues) => issues
+            .into_iter()
+            .map(|issue| match issue {
+                Value::Object(record) => Ok(record),
+                other => anyhow::bail!("each [[issues]] entry must be a table, got {other}"),
+            })
+            .collect(),
+        _ => anyhow::bail!("`issues` must be an [[issues]] array of tables"),
+    }
+}
+
+/// The keys a spec reads itself; every other key is a field.
+const SPEC_KEYS: &[&str] = &["ref", "project", "summary", "type", "description", "parent"];
+
+fn spec(row: usize, mut record: Map<String, Value>) -> Result<IssueSpec> {
+    let mut text = |key: &str| -> Result<Option<String>> {
+        match record.remove(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(s)) if s.trim().is_empty() => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.trim().to_string())),
+            Some(other) => anyhow::bail!("`{key}` must be a string, got {other}"),
+        }
+    };
+    let required = |value: Option<String>, key: &str| {
+        value.ok_or_else(|| anyhow::anyhow!("`{key}` is required"))
+    };
+
+    let reference = text("ref")?;
+    let project = required(text("project")?, "project")?;
+    let summary = required(text("summary")?, "summary")?;
+    let issue_type = required(text("type")?, "type")?;
+    let parent = text("parent")?.map(|p| match p.strip_prefix('#') {
+        Some(reference) => Parent::Row(reference.to_string()),
+        None => Parent::Key(p),
+    });
+    let description = record.remove("description").unwrap_or(Value::Null);
+
+    // A nested `fields` table is the same as its keys written at the top,
+    // which is how TOML and JSON spell a group of them most naturally.
+    let mut fields = match record.remove("fields") {
+        None => Map::new(),
+        Some(Value::Object(nested)) => nested,
+        Some(other) => anyhow::bail!("`fields` must be an object of fields, got {other}"),
+    };
+    for (key, value) in record {
+        if fields.insert(key.clone(), value).is_some() {
+            anyhow::bail!("`{key}` is given both at the top and under `fields`");
+        }
+    }
+
+    Ok(IssueSpec {
+        row,
+        reference,
+        project,
+        summary,
+        issue_type,
+        description,
+        parent,
+        fields,
+    })
+}
+
+/// Every `#ref` names exactly one row above the one using it. Requiring the
+/// parent to come first rules out cycles without looking for them, and is the
+/// order a person writes a hierarchy in anyway.
+fn check_references(specs: &[IssueSpec]) -> Result<()> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for spec in specs {
+        if let Some(Parent::Row(reference)) = &spec.parent
+            && !seen.contains_key(reference.as_str())
+        {
+            anyhow::bail!(
+                "row {}: parent `#{reference}` names no row above it — a parent row must \
+                 come before its children",
+                spec.row
+            );
+        }
+        if let Some(reference) = &spec.reference
+            && let Some(first) = seen.insert(reference, spec.row)
+        {
+            anyhow::bail!(
+                "rows {first} and {} both have ref `{reference}` — a ref names one row",
+                spec.row
+            );
+        }
+    }
+    Ok(())
+}
+
+/// What became of one row.
+enum Outcome {
+    Created { key: String, id: String },
+    Failed(String),
+}
+
+/// Creates every spec, parents before the rows that name them, and answers
+/// with one item per row in file order: `{"row", "ref", "key", "id"}` or
+/// `{"row", "ref", "error"}`.
+///
+/// A request Jira refuses as a whole — not per row, as it does a bad field —
+/// ends the run: what failed one batch would fail the next, and the rows
+/// after it are reported as not attempted rather than sent into the same
+/// refusal fifty at a time.
+pub async fn create_issues(specs: Vec<IssueSpec>, client: &ApiClient) -> Result<Value> {
+    let prepared = prepare_fields(&specs, client).await?;
+    let mut outcomes: Vec<Option<Outcome>> = (0..specs.len()).map(|_| None).collect();
+    let mut keys_by_ref: HashMap<String, String> = HashMap::new();
+    let mut stopped: Option<String> = None;
+
+    // Each wave is every row whose parent is known by now: the rows with an
+    // existing parent or none in the first, their children in the next.
+    loop {
+        let mut wave: Vec<(usize, Value)> = Vec::new();
+        for (i, spec) in specs.iter().enumerate() {
+            if outcomes[i].is_some() {
+                continue;
+            }
+            let mut fields = prepared[i].clone();
+            match &spec.parent {
+                None => {}
+                Some(Parent::Key(key)) => fields["parent"] = json!({ "key": key }),
+                Some(Parent::Row(reference)) => match keys_by_ref.get(reference) {
+                    Some(key) => fields["parent"] = json!({ "key": key }),
+                    None => {
+                        let parent_failed = specs.iter().zip(&outcomes).any(|(s, o)| {
+                            s.reference.as_ref() == Some(reference)
+                                && matches!(o, Some(Outcome::Failed(_)))
+                        });
+                        if parent_failed {
+                            outcomes[i] = Some(Outcome::Failed(format!(
+                                "not created: its parent `#{reference}` was not created"
+                            )));
+                        }
+                        continue;
+                    }
+                },
+            }
+            wave.push((i, json!({ "fields": fields })));
+        }
+        if wave.is_empty() {
+            break;
+        }
+
+        for batch in wave.chunks(BULK_CREATE_LIMIT) {
+            if let Some(reason) = &stopped {
+                for (i, _) in batch {
+                    outcomes[*i] = Some(Outcome::Failed(reason.clone()));
+                }
+                continue;
+            }
+            match post_batch(batch, client).await {
+                Ok(results) => {
+                    for ((i, _), outcome) in batch.iter().zip(results) {
+                        if let (Outcome::Created { key, .. }, Some(reference)) =
+                            (&outcome, &specs[*i].reference)
+                        {
+                            keys_by_ref.insert(reference.clone(), key.clone());
+                        }
+                        outcomes[*i] = Some(outcome);
+                    }
+                }
+                Err(err) => {
+                    let message = format!("{err:#}");
+                    for (i, _) in batch {
+                        outcomes[*i] = Some(Outcome::Failed(message.clone()));
+                    }
+                    stopped = Some(format!(
+                        "not attempted: an earlier request was refused ({message})"
+                    ));
+                }
+            }
+        }
+    }
+
+    let mut created = 0;
+    let items: Vec<Value> = specs
+        .iter()
+        .zip(outcomes)
+        .map(|(spec, outcome)| {
+            let mut item = json!({ "row": spec.row });
+            if let Some(reference) = &spec.reference {
+                item["ref"] = json!(reference);
+            }
+            match outcome.expect("every row is decided once no wave is left") {
+                Outcome::Created { key, id } => {
+                    created += 1;
+                    item["key"] = json!(key);
+                    item["id"] = json!(id);
+                }
+                Outcome::Failed(error) => item["error"] = json!(error),
+            }
+            item
+        })
+        .collect();
+    let failed = items.len() - created;
+    Ok(json!({ "items": items, "created": created, "failed": failed }))
+}
+
+/// Every spec's `fields`, with names resolved to ids and people to account
+/// ids, or the file refused with every row that cannot be sent.
+///
+/// This is the check the module promises before anything is created: a field
+/// name that matches nothing or a person nobody is on the last row stops the
+/// run before the first request. A row naming another row as its parent is
+/// checked with a stand-in key, which the wave that sends it replaces.
+async fn prepare_fields(specs: &[IssueSpec], client: &ApiClient) -> Result<Vec<Value>> {
+    let mut resolver = FieldResolver::new(client.config());
+    let mut users = UserLookup::default();
+    let mut prepared = Vec::with_capacity(specs.len());
+    let mut problems = Vec::new();
+    for spec in specs {
+        let parent_key = match &spec.parent {
+            None => None,
+            Some(Parent::Key(key)) => Some(key.as_str()),
+            Some(Parent::Row(reference)) => Some(reference.as_str()),
+        };
+        let fields = async {
+            let mut extra = resolver
+                .keys(Value::Object(spec.fields.clone()), client)
+                .await?;
+            users.resolve_in(&mut extra, client).await?;
+            new_issue_fields(
+                &spec.project,
+                &spec.summary,
+                &spec.issue_type,
+                spec.description.clone(),
+                parent_key,
+                Some(extra),
+            )
+        }
+        .await;
+        match fields {
+            Ok(fields) => prepared.push(fields),
+            Err(err) => problems.push(format!("row {}: {err:#}", spec.row)),
+        }
+    }
+    if !problems.is_empty() {
+        anyhow::bail!(
+            "nothing was created — fix these rows and run it again:\n{}",
+            problems.join("\n")
+        );
+    }
+    Ok(prepared)
+}
+
+/// One bulk request, answered as one outcome per element in order.
+///
+/// Jira lists the issues it created in the order sent, skipping the ones it
+/// refused, and names each refusal by its index in the request. When it
+/// refuses every element the answer is a 400 carrying the same per-element
+/// errors, which is still a per-row answer, not a failed request.
+async fn post_batch(batch: &[(usize, Value)], client: &ApiClient) -> Result<Vec<Outcome>> {
+    let body = json!({ "issueUpdates": batch.iter().map(|(_, u)| u).collect::<Vec<_>>() });
+    let request = client
+        .post(Service::Jira, "/rest/api/3/issue/bulk")
+        .await?
+        .header("Content-Type", "application/json")
+        .json(&body);
+    let data: Value = match client.execute("create issues", request).await {
+        Ok(response) => response.json().await?,
+        Err(err) => match element_errors_of(&err) {
+            Some(data) => data,
+            None => return Err(err),
+        },
+    };
+
+    let mut errors: HashMap<usize, String> = HashMap::new();
+    if let Some(list) = data["errors"].as_array() {
+        for error in list {
+            if let Some(index) = error["failedElementNumber"].as_u64() {
+                errors.insert(index as usize, error_summary(&error["elementErrors"]));
+            }
+        }
+    }
+    let mut issues = require_array(&data, "/issues", "create issues")?.into_iter();
+
+    (0..batch.len())
+        .map(|index| {
+            if let Some(message) = errors.remove(&index) {
+                return Ok(Outcome::Failed(message));
+            }
+            let Some(issue) = issues.next() else {
+                anyhow::bail!(
+                    "create issues succeeded but listed fewer issues than it did not refuse: {data}"
+                );
+            };
+            Ok(Outcome::Created {
+                key: require_field(&issue, "/key", "create issues")?
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                id: require_field(&issue, "/id", "create issues")?
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The body of a 400 that refused every element one by one.
+fn element_errors_of(err: &anyhow::Error) -> Option<Value> {
+    let api = err.downcast_ref::<ApiError>()?;
+    if api.status != reqwest::StatusCode::BAD_REQUEST {
+        return None;
+    }
+    let data: Value = serde_json::from_str(&api.body).ok()?;
+    data["errors"]
+        .as_array()
+        .is_some_and(|errors| errors.iter().any(|e| e["failedElementNumber"].is_u64()))
+        .then_some(data)
+}
+
+/// Edits in flight at once. Enough to finish a few hundred issues in
+/// seconds; few enough that the rate limit is met as the odd 429 the client
+/// retries rather than as a wall every request hits.
+const BULK_EDIT_CONCURRENCY: usize = 4;
+
+/// One edit for every issue: `--set` replaces a field, `--add`/`--remove`
+/// change one value of a list field (`labels`, `components`, `fixVersions`)
+/// without touching the rest of it.
+#[derive(Debug, Default)]
+pub struct Edit {
+    set: Vec<(String, Value)>,
+    add: Vec<(String, Value)>,
+    remove: Vec<(String, Value)>,
+}
+
+impl Edit {
+    /// Each argument is `FIELD=VALUE`, the field by id or by name. A value
+    /// written as JSON structure — `{"name":"High"}`, `["a","b"]`, a quoted
+    /// `"…"`, or `null` — is taken as that, as a CSV cell is; anything else
+    /// is a string, so `labels=api` needs no quoting and `summary=123` stays
+    /// the text it was typed as. `set_json` is `--set-json`, whose value is
+    /// always JSON, for the numbers and booleans a plain `--set` keeps as text.
+    pub fn new(
+        set: &[String],
+        set_json: &[String],
+        add: &[String],
+        remove: &[String],
+    ) -> Result<Self> {
+        let split = |flag: &str, arg: &str| -> Result<(String, String)> {
+            match arg.split_once('=') {
+                Some((field, value)) if !field.trim().is_empty() => {
+                    Ok((field.trim().to_string(), value.to_string()))
+                }
+                _ => {
+                    anyhow::bail!("{flag} takes FIELD=VALUE, e.g. {flag} labels=api — got '{arg}'")
+                }
+            }
+        };
+        let parse = |flag: &str, args: &[String]| -> Result<Vec<(String, Value)>> {
+            args.iter()
+                .map(|arg| {
+                    let (field, value) = split(flag, arg)?;
+                    let reads_as_json = value.starts_with(['{', '[', '"']) || value == "null";
+                    let value = reads_as_json
+                        .then(|| serde_json::from_str(&value).ok())
+                        .flatten()
+                        .unwrap_or(Value::String(value));
+                    Ok((field, value))
+                })
+                .collect()
+        };
+        let mut set = parse("--set", set)?;
+        for arg in set_json {
+            let (field, value) = split("--set-json", arg)?;
+            let value = serde_json::from_str(&value)
+                .with_context(|| format!("--set-json {field}: '{value}' is not JSON"))?;
+            set.push((field, value));
+        }
+        let edit = Self {
+            set,
+            add: parse("--add", add)?,
+            remove: parse("--remove", remove)?,
+        };
+        if edit.set.is_empty() && edit.add.is_empty() && edit.remove.is_empty() {
+            anyhow::bail!("bulk edit needs at least one --set, --add or --remove");
+        }
+        Ok(edit)
+    }
+
+    /// The body of the `PUT` every issue gets: `fields` for what is set,
+    /// `update` for what is added or removed. A field both set and changed is
+    /// refused, as Jira refuses it, but here before the first issue is sent.
+    async fn body(&self, client: &ApiClient) -> Result<Value> {
+        let mut resolver = FieldResolver::new(client.config());
+        let mut users = UserLookup::default();
+        let mut fields = Map::new();
+        for (field, value) in &self.set {
+            let id = resolver.ids(vec![field.clone()], client).await?.remove(0);
+            let value = if id == "description" {
+                adf::process_description_input(value.clone())?
+            } else {
+                value.clone()
+            };
+            if fields.insert(id, value).is_some() {
+                anyhow::bail!("--set names `{field}` more than once");
+            }
+        }
+        let mut update: Map<String, Value> = Map::new();
+        for (verb, changes) in [("add", &self.add), ("remove", &self.remove)] {
+            for (field, value) in changes {
+                let id = resolver.ids(vec![field.clone()], client).await?.remove(0);
+                if fields.contains_key(&id) {
+                    anyhow::bail!(
+                        "`{field}` is given to both --set and --{verb} — a field is \
+                         replaced or changed, not both"
+                    );
+                }
+                let operations = update.entry(id).or_insert_with(|| json!([]));
+                operations
+                    .as_array_mut()
+                    .expect("built as an array")
+                    .push(json!({ verb: value }));
+            }
+        }
+
+        let mut body = Map::new();
+        if !fields.is_empty() {
+            body.insert("fields".into(), Value::Object(fields));
+        }
+        if !update.is_empty() {
+            body.insert("update".into(), Value::Object(update));
+        }
+        let mut body = Value::Object(body);
+        users.resolve_in(&mut body, client).await?;
+        Ok(body)
+    }
+}
+
+/// Applies `edit` to every issue `jql` matches, or with `dry_run` only says
+/// which issues those are and what each would be sent.
+///
+/// The query is walked to the end before anything is edited, so the set is
+/// fixed: an edit that takes an issue out of the query does not shift the
+/// pages under the walk. More than `max` matches is refused outright, dry run
+/// or not — a query broader than meant is the mistake this guards against,
+/// and editing the first `max` of it would only make that mistake smaller.
+/// The walk stops as soon as it passes `max`, so a query matching thousands
+/// is refused after a page, not after all of them.
+///
+/// The answer has one item per issue, `{"key", "status"}` with `"error"` on
+/// a failure, and `retry_jql`: a query matching only the failed issues, for
+/// `--jql` on the next run.
+pub async fn edit_issues(
+    jql: &str,
+    edit: &Edit,
+    max: usize,
+    dry_run: bool,
+    client: &ApiClient,
+) -> Result<Value> {
+    let body = edit.body(client).await?;
+    let final_jql = apply_project_filter(jql, client.config());
+    let matched = search_up_to(
+        jql,
+        Some(vec!["summary".into()]),
+        None,
+        false,
+        false,
+        Some(max),
+        client,
+    )
+    .await
+    .context("Failed to collect the issues to edit")?;
+    let issues = require_array(&matched, "/items", "search")?;
+    if issues.len() > max {
+        anyhow::bail!(
+            "More issues match `{final_jql}` than --max {max}. Narrow the query, or raise --max \
+             if all of them are meant."
+        );
+    }
+
+    if dry_run {
+        let items: Vec<Value> = issues
+            .iter()
+            .map(|issue| {
+                json!({
+                    "key": issue["key"],
+                    "summary": issue.pointer("/fields/summary").cloned().unwrap_or(Value::Null),
+                    "status": "would edit",
+                })
+            })
+            .collect();
+        return Ok(json!({
+            "items": items,
+            "jql": final_jql,
+            "matched": issues.len(),
+            "edit": body,
+            "dry_run": true,
+        }));
+    }
+
+    let keys: Vec<String> = issues
+        .iter()
+        .map(|issue| {
+            require_field(issue, "/key", "search")
+                .map(|key| key.as_str().unwrap_or_default().to_string())
+        })
+        .collect::<Result<_>>()?;
+    // `buffered`, not `buffer_unordered`: the report keeps the query's order,
+    // which is the order a person reading it expects.
+    let outcomes: Vec<(String, Result<()>)> = stream::iter(keys)
+        .map(|key| {
+            let body = &body;
+            async move {
+                let result = edit_issue(&key, body, client).await;
+                (key, result)
+            }
+        })
+        .buffered(BULK_EDIT_CONCURRENCY)
+        .collect()
+        .await;
+
+    let failed: Vec<&str> = outcomes
+        .iter()
+        .filter(|(_, result)| result.is_err())
+        .map(|(key, _)| key.as_str())
+        .collect();
+    let retry_jql = (!failed.is_empty()).then(|| format!("key in ({})", failed.join(", ")));
+    let failed = failed.len();
+    let items: Vec<Value> = outcomes
+        .iter()
+        .map(|(key, result)| match result {
+            Ok(()) => json!({ "key": key, "status": "edited" }),
+            Err(err) => json!({ "key": key, "status": "failed", "error": format!("{err:#}") }),
+        })
+        .collect();
+    Ok(json!({
+        "items": items,
+        "jql": final_jql,
+        "matched": items.len(),
+        "edited": items.len() - failed,
+        "failed": failed,
+        "retry_jql": retry_jql,
+    }))
+}
+
+async fn edit_issue(key: &str, body: &Value, client: &ApiClient) -> Result<()> {
+    let url = format!("/rest/api/3/issue/{}", encode_path_segment(key));
+    let request = client
+        .put(Service::Jira, &url)
+        .await?
+        .header("Content-Type", "application/json")
+        .json(body);
+    client.execute("edit issue", request).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_client;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn write(name: &str, content: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(name);
+        std::fs::write(&file, content).unwrap();
+        (dir, file)
+    }
+
+    #[test]
+    fn test_the_three_formats_read_as_the_same_specs() {
+        let (_d, jsonl) = write(
+            "a.jsonl",
+            "{\"ref\":\"e\",\"project\":\"P\",\"summary\":\"Epic\",\"type\":\"Epic\"}\n\n\
+             {\"project\":\"P\",\"summary\":\"Child\",\"type\":\"Task\",\"parent\":\"#e\",\"Story Points\":3}\n",
+        );
+        let (_d2, csv) = write(
+            "a.csv",
+            "ref,project,Title,type,parent,Story Points\n\
+             e,P,Epic,Epic,,\n\
+             ,P,Child,Task,#e,3\n",
+        );
+        let (_d3, toml) = write(
+            "a.toml",
+            "[[issues]]\nref = \"e\"\nproject = \"P\"\nsummary = \"Epic\"\ntype = \"Epic\"\n\n\
+             [[issues]]\nproject = \"P\"\nsummary = \"Child\"\ntype = \"Task\"\nparent = \"#e\"\n\
+             fields = { \"Story Points\" = 3 }\n",
+        );
+        let rename = HashMap::from([("Title".to_string(), "summary".to_string())]);
+        for file in [jsonl, csv, toml] {
+            let specs = read_issue_specs(&file, &rename).unwrap();
+            assert_eq!(specs.len(), 2, "{file:?}");
+            assert_eq!(specs[0].reference.as_deref(), Some("e"));
+            assert_eq!(specs[1].summary, "Child");
+            assert_eq!(specs[1].parent, Some(Parent::Row("e".into())));
+            assert_eq!(
+                specs[1].fields,
+                Map::from_iter([("Story Points".into(), json!(3))])
+            );
+        }
+    }
+
+    #[test]
+    fn test_a_parent_ref_must_name_a_row_above() {
+        let (_d, file) = write(
+            "a.jsonl",
+            "{\"project\":\"P\",\"summary\":\"Child\",\"type\":\"Task\",\"parent\":\"#e\"}\n\
+             {\"ref\":\"e\",\"project\":\"P\",\"summary\":\"Epic\",\"type\":\"Epic\"}\n",
+        );
+        let err = read_issue_specs(&file, &HashMap::new()).unwrap_err();
+        assert!(format!("{err:#}").contains("names no row above it"));
+
+        let (_d, file) = write("a.jsonl", "{\"project\":\"P\",\"type\":\"Task\"}\n");
+        let err = read_issue_specs(&file, &HashMap::new()).unwrap_err();
+        assert!(format!("{err:#}").contains("row 1: `summary` is required"));
+    }
+
+    #[tokio::test]
+    async fn integ_children_wait_for_their_parent_and_errors_stay_per_row() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/issue/bulk"))
+            .and(body_partial_json(
+                json!({"issueUpdates": [{"fields": {"summary": "Epic"}}]}),
+            ))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "issues": [{"id": "10", "key": "P-1"}],
+                "errors": [{
+                    "failedElementNumber": 1,
+                    "elementErrors": {"errorMessages": [], "errors": {"issuetype": "invalid"}}
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/issue/bulk"))
+            .and(body_partial_json(json!({"issueUpdates": [
+                {"fields": {"summary": "Child", "parent": {"key": "P-1"}}}
+            ]})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "issues": [{"id": "11", "key": "P-2"}],
+                "errors": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let spec =
+            |row, reference: Option<&str>, summary: &str, parent: Option<Parent>| IssueSpec {
+                row,
+                reference: reference.map(str::to_string),
+                project: "P".into(),
+                summary: summary.into(),
+                issue_type: "Task".into(),
+                description: Value::Null,
+                parent,
+                fields: Map::new(),
+            };
+        let result = create_issues(
+            vec![
+                spec(1, Some("e"), "Epic", None),
+                spec(2, Some("bad"), "Bad", None),
+                spec(3, None, "Child", Some(Parent::Row("e".into()))),
+                spec(4, None, "Orphan", Some(Parent::Row("bad".into()))),
+            ],
+            &client,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            result,
+            json!({
+                "items": [
+                    {"row": 1, "ref": "e", "key": "P-1", "id": "10"},
+                    {"row": 2, "ref": "bad", "error": "issuetype: invalid"},
+                    {"row": 3, "key": "P-2", "id": "11"},
+                    {"row": 4, "error": "not created: its parent `#bad` was not created"}
+                ],
+                "created": 2,
+                "failed": 2
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn integ_a_bad_field_on_the_last_row_creates_nothing() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/field"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"id": "customfield_10016", "name": "Story Points", "custom": true}
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/issue/bulk"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let spec = |row, name: &str| IssueSpec {
+            row,
+            reference: None,
+            project: "P".into(),
+            summary: format!("Row {row}"),
+            issue_type: "Task".into(),
+            description: Value::Null,
+            parent: None,
+            fields: Map::from_iter([(name.to_string(), json!(3))]),
+        };
+        let err = create_issues(
+            vec![spec(1, "Story Points"), spec(2, "Story Pionts")],
+            &client,
+        )
+        .await
+        .unwrap_err();
+
+        let message = format!("{err:#}");
+        assert!(message.starts_with("nothing was created"), "{message}");
+        assert!(message.contains("row 2: "), "{message}");
+        assert!(!message.contains("row 1: "), "{message}");
+    }
+
+    #[tokio::test]
+    async fn integ_bulk_edit_previews_then_reports_each_issue() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/search/jql"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "issues": [
+                    {"key": "P-1", "fields": {"summary": "One"}},
+                    {"key": "P-2", "fields": {"summary": "Two"}}
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/rest/api/3/issue/P-1"))
+            .and(body_partial_json(json!({
+                "fields": {"priority": {"name": "High"}},
+                "update": {"labels": [{"add": "triage"}, {"remove": "stale"}]}
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/rest/api/3/issue/P-2"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "errorMessages": [], "errors": {"priority": "not on the edit screen"}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+        let edit = Edit::new(
+            &[r#"priority={"name":"High"}"#.into()],
+            &[],
+            &["labels=triage".into()],
+            &["labels=stale".into()],
+        )
+        .unwrap();
+
+        let preview = edit_issues("project = P", &edit, 10, true, &client)
+            .await
+            .unwrap();
+        assert_eq!(preview["matched"], 2);
+        assert_eq!(
+            preview["items"][1],
+            json!({"key": "P-2", "summary": "Two", "status": "would edit"})
+        );
+
+        let err = edit_issues("project = P", &edit, 1, false, &client)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("than --max 1"));
+
+        let report = edit_issues("project = P", &edit, 10, false, &client)
+            .await
+            .unwrap();
+        assert_eq!(
+            report["items"][0],
+            json!({"key": "P-1", "status": "edited"})
+        );
+        assert_eq!(report["items"][1]["status"], "failed");
+        assert!(
+            report["items"][1]["error"]
+                .as_str()
+                .unwrap()
+                .contains("not on the edit screen")
+        );
+        assert_eq!(report["retry_jql"], "key in (P-2)");
+        assert_eq!(
+            (report["edited"].clone(), report["failed"].clone()),
+            (json!(1), json!(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn integ_bulk_edit_stops_the_walk_once_past_max() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/search/jql"))
+            .and(body_partial_json(json!({"nextPageToken": "p2"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"issues": []})))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/search/jql"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "issues": [
+                    {"key": "P-1", "fields": {"summary": "One"}},
+                    {"key": "P-2", "fields": {"summary": "Two"}}
+                ],
+                "nextPageToken": "p2"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+        let edit = Edit::new(&["labels=[]".into()], &[], &[], &[]).unwrap();
+
+        let err = edit_issues("project = P", &edit, 1, true, &client)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("than --max 1"), "{err}");
+    }
+
+    #[test]
+    fn test_set_keeps_a_number_typed_as_text_and_set_json_does_not() {
+        let edit = Edit::new(
+            &[
+                "summary=123".into(),
+                "labels=[\"a\"]".into(),
+                "assignee=null".into(),
+                "environment=\"42\"".into(),
+            ],
+            &["Story Points=5".into()],
+            &["labels=true".into()],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            edit.set,
+            [
+                ("summary".to_string(), json!("123")),
+                ("labels".to_string(), json!(["a"])),
+                ("assignee".to_string(), Value::Null),
+                ("environment".to_string(), json!("42")),
+                ("Story Points".to_string(), json!(5)),
+            ]
+        );
+        assert_eq!(edit.add, [("labels".to_string(), json!("true"))]);
+
+        let err = Edit::new(&[], &["Story Points=five".into()], &[], &[]).unwrap_err();
+        assert!(format!("{err:#}").contains("is not JSON"), "{err:#}");
+    }
+}
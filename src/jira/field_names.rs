@@ -0,0 +1,368 @@
+//! Jira fields by the names people know them by.
+//!
+//! A custom field is `customfield_10015` to the API and "Story Points" to
+//! everyone else. `/rest/api/3/field` maps one to the other, but it lists
+//! every field on the site — hundreds on a busy one — so the answer is kept
+//! on disk per site and read from there until a name turns up that it does
+//! not have. A field added since is then found by fetching once more; there
+//! is no expiry to tune.
+//!
+//! Nothing is fetched for a selector that already reads as an id
+//! (`summary`, `customfield_10015`, `*all`, `-comment`), so every command
+//! that worked before this module existed makes exactly the requests it
+//! made then. A name is matched without regard to case, but it is only
+//! looked up when it is written like a name — see `reads_as_id`.
+
+use crate::client::{ApiClient, Service};
+use crate::config::Config;
+use crate::response::{WHOLE_BODY, require_array};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Field {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Default)]
+struct Catalog {
+    fields: Vec<Field>,
+    /// Lower-cased name to every id carrying it. Jira does not keep custom
+    /// field names unique, so one name can stand for several fields.
+    by_name: HashMap<String, Vec<String>>,
+}
+
+impl Catalog {
+    fn new(fields: Vec<Field>) -> Self {
+        let mut by_name: HashMap<String, Vec<String>> = HashMap::new();
+        for field in &fields {
+            by_name
+                .entry(field.name.to_lowercase())
+                .or_default()
+                .push(field.id.clone());
+        }
+        Self { fields, by_name }
+    }
+
+    fn has_id(&self, id: &str) -> bool {
+        self.fields.iter().any(|f| f.id == id)
+    }
+}
+
+/// One per invocation: the catalog is read at most once from disk and
+/// fetched at most once from the site, however many names it is asked about.
+pub struct FieldResolver {
+    cache: Option<PathBuf>,
+    catalog: Option<Catalog>,
+    fetched: bool,
+}
+
+impl FieldResolver {
+    pub fn new(config: &Config) -> Self {
+        // Keyed by the site, not the profile: two profiles on one site share
+        // a catalog, and one profile repointed at another site does not read
+        // the old site's.
+        let site = config.domain.clone().or_else(|| match &config.auth {
+            Some(crate::AuthConfig::ScopedToken { cloud_id, .. })
+            | Some(crate::AuthConfig::ServiceAccount { cloud_id, .. })
+            | Some(crate::AuthConfig::OAuth { cloud_id, .. }) => cloud_id.clone(),
+            _ => None,
+        });
+        let cache = config.cache_dir.as_ref().zip(site).map(|(dir, site)| {
+            let file: String = site
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            dir.join("jira-fields").join(format!("{file}.json"))
+        });
+        Self {
+            cache,
+            catalog: None,
+            fetched: false,
+        }
+    }
+
+    /// Field selectors with every name replaced by its id.
+    pub async fn ids(&mut self, selectors: Vec<String>, client: &ApiClient) -> Result<Vec<String>> {
+        let mut out = Vec::with_capacity(selectors.len());
+        for selector in selectors {
+            out.push(self.id(selector, client).await?);
+        }
+        Ok(out)
+    }
+
+    /// A JSON object of field values (`jira create --fields`, `jira update`)
+    /// with every key that is a name replaced by its id. Anything other than
+    /// an object is returned as it is, for the caller to refuse in its own
+    /// words.
+    pub async fn keys(&mut self, value: Value, client: &ApiClient) -> Result<Value> {
+        let Value::Object(map) = value else {
+            return Ok(value);
+        };
+        let mut out = Map::with_capacity(map.len());
+        for (key, value) in map {
+            let id = self.id(key.clone(), client).await?;
+            if out.contains_key(&id) {
+                anyhow::bail!("fields name `{id}` twice (once as `{key}`) — give each field once");
+            }
+            out.insert(id, value);
+        }
+        Ok(Value::Object(out))
+    }
+
+    /// Renames each `customfield_*` key under an issue's `fields` to the
+    /// field's name, for output a person reads. A name two fields share, or
+    /// one a system field already has, is left as the id: renaming it would
+    /// merge two fields, or overwrite one, under a single key.
+    pub async fn name_custom_fields<'a>(
+        &mut self,
+        issues: impl IntoIterator<Item = &'a mut Value>,
+        client: &ApiClient,
+    ) -> Result<()> {
+        let mut issues: Vec<&mut Value> = issues
+            .into_iter()
+            .filter(|issue| {
+                issue["fields"]
+                    .as_object()
+                    .is_some_and(|f| f.keys().any(|k| k.starts_with("customfield_")))
+            })
+            .collect();
+        if issues.is_empty() {
+            return Ok(());
+        }
+        let catalog = self.catalog(client).await?;
+        let unique_name = |id: &str| {
+            let field = catalog.fields.iter().find(|f| f.id == id)?;
+            let ids = catalog.by_name.get(&field.name.to_lowercase())?;
+            (ids.len() == 1).then(|| field.name.clone())
+        };
+
+        for issue in &mut issues {
+            let Some(fields) = issue["fields"].as_object_mut() else {
+                continue;
+            };
+            let renamed: Map<String, Value> = std::mem::take(fields)
+                .into_iter()
+                .map(|(key, value)| match unique_name(&key) {
+                    Some(name) if key.starts_with("customfield_") => (name, value),
+                    _ => (key, value),
+                })
+                .collect();
+            *fields = renamed;
+        }
+        Ok(())
+    }
+
+    async fn id(&mut self, selector: String, client: &ApiClient) -> Result<String> {
+        if reads_as_id(&selector) {
+            return Ok(selector);
+        }
+        if let Some(id) = self.lookup(&selector, client).await? {
+            return Ok(id);
+        }
+        // A field created since the catalog was cached.
+        if !self.fetched {
+            self.refresh(client).await?;
+            if let Some(id) = self.lookup(&selector, client).await? {
+                return Ok(id);
+            }
+        }
+        anyhow::bail!("no Jira field is named `{selector}` on this site")
+    }
+
+    async fn lookup(&mut self, selector: &str, client: &ApiClient) -> Result<Option<String>> {
+        let catalog = self.catalog(client).await?;
+        if catalog.has_id(selector) {
+            return Ok(Some(selector.to_string()));
+        }
+        match catalog
+            .by_name
+            .get(&selector.to_lowercase())
+            .map(Vec::as_slice)
+        {
+            None => Ok(None),
+            Some([id]) => Ok(Some(id.clone())),
+            Some(ids) => anyhow::bail!(
+                "more than one Jira field is named `{selector}` ({}) — use the id",
+                ids.join(", ")
+            ),
+        }
+    }
+
+    async fn catalog(&mut self, client: &ApiClient) -> Result<&Catalog> {
+        if self.catalog.is_none() {
+            match self.read_cache() {
+                Some(fields) => self.catalog = Some(Catalog::new(fields)),
+                None => self.refresh(client).await?,
+            }
+        }
+        Ok(self.catalog.as_ref().expect("loaded above"))
+    }
+
+    async fn refresh(&mut self, client: &ApiClient) -> Result<()> {
+        let request = client
+            .get(Service::Jira, "/rest/api/3/field")
+            .await?
+            .header("Accept", "application/json");
+        let response = client.execute("list fields", request).await?;
+        let data: Value = response.json().await?;
+
+        let fields: Vec<Field> = require_array(&data, WHOLE_BODY, "list fields")?
+            .into_iter()
+            .filter_map(|field| serde_json::from_value(field).ok())
+            .collect();
+        self.write_cache(&fields);
+        self.catalog = Some(Catalog::new(fields));
+        self.fetched = true;
+        Ok(())
+    }
+
+    /// A cache that is missing or unreadable is a cache miss, not an error:
+    /// the site has the answer, and a command should not fail over a copy.
+    fn read_cache(&self) -> Option<Vec<Field>> {
+        let path = self.cache.as_ref()?;
+        let bytes = std::fs::read(path).ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(fields) => Some(fields),
+            Err(err) => {
+                tracing::debug!(?path, error = %err, "ignoring an unreadable field cache");
+                None
+            }
+        }
+    }
+
+    fn write_cache(&self, fields: &[Field]) {
+        let Some(path) = &self.cache else {
+            return;
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(path, serde_json::to_vec(fields)?));
+        if let Err(err) = written {
+            tracing::debug!(?path, error = %err, "could not cache the field catalog");
+        }
+    }
+}
+
+/// Whether a selector is taken as an id without a lookup. Jira's ids are
+/// lower-case ASCII (`duedate`, `customfield_10015`, `*all`, `-comment`);
+/// the names people give fields have capitals, spaces, or letters outside
+/// ASCII. So only a selector with one of those is looked up, and anything
+/// else reaches the server as it was written, as it always did. An id that
+/// does not read this way (`lastViewed`) still resolves, through the catalog.
+fn reads_as_id(selector: &str) -> bool {
+    selector
+        .chars()
+        .all(|c| c.is_ascii() && !c.is_ascii_uppercase() && !c.is_whitespace())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{create_test_config, mock_client_with_config};
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn catalog() -> Value {
+        json!([
+            {"id": "summary", "name": "Summary", "custom": false},
+            {"id": "customfield_10016", "name": "Story Points", "custom": true},
+            {"id": "customfield_10020", "name": "Team", "custom": true},
+            {"id": "customfield_10021", "name": "Team", "custom": true}
+        ])
+    }
+
+    #[tokio::test]
+    async fn integ_names_resolve_through_a_catalog_cached_on_disk() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/field"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(catalog()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = create_test_config();
+        config.cache_dir = Some(dir.path().to_path_buf());
+        let client = mock_client_with_config(server.uri(), config);
+
+        let ids = FieldResolver::new(client.config())
+            .ids(vec!["key".into(), "story points".into()], &client)
+            .await
+            .unwrap();
+        assert_eq!(ids, ["key", "customfield_10016"]);
+
+        // A second invocation reads the file, which `expect(1)` holds it to.
+        let fields = FieldResolver::new(client.config())
+            .keys(json!({"Story Points": 5, "summary": "x"}), &client)
+            .await
+            .unwrap();
+        assert_eq!(fields, json!({"customfield_10016": 5, "summary": "x"}));
+        assert!(
+            dir.path()
+                .join("jira-fields/test.atlassian.net.json")
+                .is_file()
+        );
+    }
+
+    #[tokio::test]
+    async fn integ_a_shared_name_is_refused_and_left_as_the_id_in_output() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/field"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(catalog()))
+            .mount(&server)
+            .await;
+        let client = mock_client_with_config(server.uri(), create_test_config());
+        let mut resolver = FieldResolver::new(client.config());
+
+        let err = resolver
+            .ids(vec!["Team".into()], &client)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("customfield_10020, customfield_10021")
+        );
+        assert!(resolver.ids(vec!["Nope".into()], &client).await.is_err());
+
+        let mut issue = json!({"key": "A-1", "fields": {
+            "customfield_10016": 3, "customfield_10020": "x", "summary": "S"
+        }});
+        resolver
+            .name_custom_fields([&mut issue], &client)
+            .await
+            .unwrap();
+        assert_eq!(
+            issue["fields"],
+            json!({"Story Points": 3, "customfield_10020": "x", "summary": "S"})
+        );
+    }
+
+    #[tokio::test]
+    async fn integ_ids_are_used_without_asking_the_site() {
+        // No mock mounted: any request would fail the call.
+        let server = MockServer::start().await;
+        let client = mock_client_with_config(server.uri(), create_test_config());
+        let ids = FieldResolver::new(client.config())
+            .ids(
+                vec!["summary".into(), "customfield_10015".into(), "*all".into()],
+                &client,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ids, ["summary", "customfield_10015", "*all"]);
+    }
+}
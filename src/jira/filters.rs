@@ -0,0 +1,285 @@
+//! Saved filters: a name, a query, and who may see it.
+//!
+//! Much reporting lives in filters, so a filter can stand in for a query —
+//! `jira search --filter` reads a filter's JQL and then runs it exactly as a
+//! typed query runs, project filter and all. A filter is named by id or by
+//! its name; a name must pick out one filter, as a user's name must pick out
+//! one person.
+
+use crate::client::{ApiClient, Service};
+use crate::filter;
+use crate::http_utils::encode_path_segment;
+use crate::jira::api::{AGILE_PAGE, list_envelope, paginate};
+use crate::response::require_field;
+use anyhow::Result;
+use serde_json::{Map, Value, json};
+
+/// What `filter/search` leaves out unless asked: without `jql` a listing
+/// could not be told apart from a list of names.
+const LIST_EXPAND: &str = "description,favourite,jql,owner,sharePermissions";
+
+/// Every filter the caller can see, or those whose name contains `name`.
+pub async fn list_filters(name: Option<&str>, client: &ApiClient) -> Result<Value> {
+    let items = search_filters(name, client).await?;
+    Ok(list_envelope(items, client))
+}
+
+async fn search_filters(name: Option<&str>, client: &ApiClient) -> Result<Vec<Value>> {
+    let mut query = vec![("expand", LIST_EXPAND.to_string())];
+    if let Some(name) = name {
+        query.push(("filterName", name.to_string()));
+    }
+    paginate(
+        "/rest/api/3/filter/search",
+        &query,
+        "list filters",
+        AGILE_PAGE,
+        client,
+    )
+    .await
+}
+
+/// One filter with its JQL, owner, and `sharePermissions` — who besides the
+/// owner can see it: a project, a role, a group, a user, or everyone
+/// logged in.
+pub async fn get_filter(filter_ref: &str, client: &ApiClient) -> Result<Value> {
+    let mut found = resolve_filter(filter_ref, client).await?;
+    filter::apply(&mut found, client.config());
+    Ok(found)
+}
+
+/// The JQL of the filter `filter_ref` names, for a search to run.
+pub async fn filter_jql(filter_ref: &str, client: &ApiClient) -> Result<String> {
+    let found = resolve_filter(filter_ref, client).await?;
+    Ok(require_field(&found, "/jql", "get filter")?
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// The filter an id or a name names, as the API gives it. A name is matched
+/// whole and case-insensitively; `filter/search` matches any part of it, so
+/// `Sprint` alone would otherwise pick whichever of `Sprint bugs` and
+/// `Sprint risks` came first.
+async fn resolve_filter(filter_ref: &str, client: &ApiClient) -> Result<Value> {
+    let filter_ref = filter_ref.trim();
+    if filter_ref.chars().all(|c| c.is_ascii_digit()) && !filter_ref.is_empty() {
+        let url = format!("/rest/api/3/filter/{}", encode_path_segment(filter_ref));
+        let request = client.get(Service::Jira, &url).await?;
+        let response = client.execute("get filter", request).await?;
+        return Ok(response.json().await?);
+    }
+
+    let found = search_filters(Some(filter_ref), client).await?;
+    let exact: Vec<Value> = found
+        .into_iter()
+        .filter(|f| {
+            f["name"]
+                .as_str()
+                .is_some_and(|name| name.eq_ignore_ascii_case(filter_ref))
+        })
+        .collect();
+    match exact.as_slice() {
+        [one] => Ok(one.clone()),
+        [] => anyhow::bail!("No filter is named '{filter_ref}'"),
+        several => anyhow::bail!(
+            "'{filter_ref}' names several filters: {} — give the filter's id",
+            several
+                .iter()
+                .map(|f| format!(
+                    "{} (id {}, owner {})",
+                    f["name"].as_str().unwrap_or_default(),
+                    f["id"].as_str().unwrap_or_default(),
+                    f["owner"]["displayName"].as_str().unwrap_or("unknown")
+                ))
+                .collect::<Vec<_>>()
+                .join("; ")
+        ),
+    }
+}
+
+/// Save `jql` as a new filter, private to the caller until shared.
+pub async fn create_filter(
+    name: &str,
+    jql: &str,
+    description: Option<&str>,
+    favourite: bool,
+    client: &ApiClient,
+) -> Result<Value> {
+    let mut body = json!({ "name": name, "jql": jql, "favourite": favourite });
+    if let Some(description) = description {
+        body["description"] = json!(description);
+    }
+    let request = client
+        .post(Service::Jira, "/rest/api/3/filter")
+        .await?
+        .header("Content-Type", "application/json")
+        .json(&body);
+    let response = client.execute("create filter", request).await?;
+
+    let data: Value = response.json().await?;
+    Ok(json!({
+        "id": require_field(&data, "/id", "create filter")?,
+        "name": require_field(&data, "/name", "create filter")?,
+    }))
+}
+
+/// Change a filter's name, query, or description. Its sharing is left as it
+/// is.
+///
+/// Jira's update replaces the filter and refuses one without a name, so the
+/// current name is read first when the caller is not changing it.
+pub async fn update_filter(
+    filter_ref: &str,
+    name: Option<&str>,
+    jql: Option<&str>,
+    description: Option<&str>,
+    client: &ApiClient,
+) -> Result<Value> {
+    if name.is_none() && jql.is_none() && description.is_none() {
+        anyhow::bail!("Nothing to update: give --name, --jql, or --description");
+    }
+    let current = resolve_filter(filter_ref, client).await?;
+    let id = require_field(&current, "/id", "get filter")?;
+    let id = id.as_str().unwrap_or_default();
+
+    let mut body = Map::new();
+    body.insert(
+        "name".into(),
+        match name {
+            Some(name) => json!(name),
+            None => require_field(&current, "/name", "get filter")?,
+        },
+    );
+    if let Some(jql) = jql {
+        body.insert("jql".into(), json!(jql));
+    }
+    if let Some(description) = description {
+        body.insert("description".into(), json!(description));
+    }
+
+    let url = format!("/rest/api/3/filter/{}", encode_path_segment(id));
+    let request = client
+        .put(Service::Jira, &url)
+        .await?
+        .header("Content-Type", "application/json")
+        .json(&body);
+    client.execute("update filter", request).await?;
+    Ok(json!({}))
+}
+
+/// Delete a filter. Jira keeps no copy, and anything subscribed to it stops,
+/// so the CLI layer requires an explicit `--yes`.
+pub async fn delete_filter(filter_ref: &str, client: &ApiClient) -> Result<Value> {
+    let current = resolve_filter(filter_ref, client).await?;
+    let id = require_field(&current, "/id", "get filter")?;
+    let url = format!(
+        "/rest/api/3/filter/{}",
+        encode_path_segment(id.as_str().unwrap_or_default())
+    );
+    let request = client.delete(Service::Jira, &url).await?;
+    client.execute("delete filter", request).await?;
+    Ok(json!({}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_client;
+    use wiremock::matchers::{body_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mount_search(server: &MockServer, name: &str, values: Value) {
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/filter/search"))
+            .and(query_param("filterName", name))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "values": values, "isLast": true })),
+            )
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn integ_filter_jql_picks_the_whole_name_and_refuses_a_tie() {
+        let server = MockServer::start().await;
+        mount_search(
+            &server,
+            "sprint bugs",
+            json!([
+                {"id": "1", "name": "Sprint bugs", "jql": "type = Bug AND sprint in openSprints()"},
+                {"id": "2", "name": "Sprint bugs (old)", "jql": "type = Bug"}
+            ]),
+        )
+        .await;
+        mount_search(
+            &server,
+            "Mine",
+            json!([
+                {"id": "3", "name": "Mine", "owner": {"displayName": "Ann"}},
+                {"id": "4", "name": "mine", "owner": {"displayName": "Bo"}}
+            ]),
+        )
+        .await;
+        let client = mock_client(server.uri());
+
+        let jql = filter_jql("sprint bugs", &client).await.unwrap();
+        assert_eq!(jql, "type = Bug AND sprint in openSprints()");
+
+        let err = filter_jql("Mine", &client).await.unwrap_err().to_string();
+        assert!(err.contains("Mine (id 3, owner Ann)"), "got: {err}");
+        assert!(err.contains("mine (id 4, owner Bo)"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn integ_get_filter_by_id_keeps_share_permissions() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/filter/10042"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "10042", "name": "Open bugs", "jql": "type = Bug",
+                "sharePermissions": [{"id": 1, "type": "project", "project": {"key": "PROJ"}}]
+            })))
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let result = get_filter("10042", &client).await.unwrap();
+        assert_eq!(result["sharePermissions"][0]["project"]["key"], "PROJ");
+    }
+
+    #[tokio::test]
+    async fn integ_update_filter_keeps_the_current_name() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/filter/7"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"id": "7", "name": "Open bugs", "jql": "type = Bug"})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/rest/api/3/filter/7"))
+            .and(body_json(
+                json!({"name": "Open bugs", "jql": "type = Bug AND resolution IS EMPTY"}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "7"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let result = update_filter(
+            "7",
+            None,
+            Some("type = Bug AND resolution IS EMPTY"),
+            None,
+            &client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, json!({}));
+    }
+}
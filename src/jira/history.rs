@@ -0,0 +1,351 @@
+//! Who changed what on an issue, and when.
+//!
+//! The changelog is one entry per edit — an author, a time, and the fields
+//! that edit touched, each with its value before and after. `jira history`
+//! narrows it to the fields and the days asked about, and either reads one
+//! issue or walks every issue a query matches, one changelog after another,
+//! for an export no single request can give.
+//!
+//! A description's before and after are whole documents, and two of them
+//! side by side do not say what changed. In Markdown mode they are replaced
+//! by a line diff, which does.
+
+use crate::client::ApiClient;
+use crate::date_utils::is_day;
+use crate::filter;
+use crate::jira::api::{fetch_changelog, list_envelope, search_all};
+use crate::output::Stream;
+use crate::response::{require_array, require_field};
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+/// Which entries of a changelog to keep. Every part is optional; an empty
+/// filter keeps everything.
+#[derive(Debug, Default)]
+pub struct HistoryFilter {
+    /// Lower-cased; matched against a change's field name and its id, so
+    /// `status`, `Story Points` and `customfield_10016` all work.
+    fields: Vec<String>,
+    since: Option<String>,
+    until: Option<String>,
+}
+
+impl HistoryFilter {
+    /// `since` and `until` are days, `YYYY-MM-DD`, both inclusive. They are
+    /// compared with the date an entry carries, which Jira writes in the
+    /// caller's own time zone — the day a person would say it happened.
+    pub fn new(fields: Vec<String>, since: Option<String>, until: Option<String>) -> Result<Self> {
+        for (flag, day) in [("--since", &since), ("--until", &until)] {
+            if let Some(day) = day
+                && !is_day(day)
+            {
+                anyhow::bail!("{flag} takes a day as YYYY-MM-DD, got '{day}'");
+            }
+        }
+        Ok(Self {
+            fields: fields.iter().map(|f| f.trim().to_lowercase()).collect(),
+            since,
+            until,
+        })
+    }
+
+    /// `entry` with only the matching changes, or `None` when it falls
+    /// outside the dates or none of its changes match.
+    fn keep(&self, mut entry: Value) -> Option<Value> {
+        let day = entry["created"].as_str().and_then(|c| c.get(..10))?;
+        if self.since.as_deref().is_some_and(|since| day < since)
+            || self.until.as_deref().is_some_and(|until| day > until)
+        {
+            return None;
+        }
+        if !self.fields.is_empty() {
+            let items = entry["items"].as_array_mut()?;
+            items.retain(|item| {
+                ["field", "fieldId"].iter().any(|key| {
+                    item[*key]
+                        .as_str()
+                        .is_some_and(|name| self.fields.contains(&name.to_lowercase()))
+                })
+            });
+            if items.is_empty() {
+                return None;
+            }
+        }
+        Some(entry)
+    }
+}
+
+/// One issue's history, oldest first.
+pub async fn issue_history(
+    issue_key: &str,
+    history: &HistoryFilter,
+    as_markdown: bool,
+    client: &ApiClient,
+) -> Result<Value> {
+    let entries = entries_of(issue_key, history, as_markdown, client).await?;
+    Ok(list_envelope(entries, client))
+}
+
+/// The history of every issue `jql` matches, in the query's order. Each
+/// entry names its issue, so the result reads as one list. With a stream,
+/// each issue's entries are written as they arrive.
+pub async fn history_walk(
+    jql: &str,
+    history: &HistoryFilter,
+    as_markdown: bool,
+    mut stream: Option<&mut Stream>,
+    client: &ApiClient,
+) -> Result<Value> {
+    let matched = search_all(
+        jql,
+        Some(vec!["summary".into()]),
+        None,
+        false,
+        false,
+        client,
+    )
+    .await
+    .context("Failed to collect the issues to read the history of")?;
+    let issues = require_array(&matched, "/items", "search")?;
+
+    let mut all = Vec::new();
+    for issue in &issues {
+        let key = require_field(issue, "/key", "search")?;
+        let key = key.as_str().unwrap_or_default();
+        let mut entries = entries_of(key, history, as_markdown, client).await?;
+        for entry in &mut entries {
+            filter::apply(entry, client.config());
+        }
+        if let Some(stream) = stream.as_deref_mut() {
+            stream.write_page(&entries)?;
+        } else {
+            all.extend(entries);
+        }
+    }
+
+    // As `search_all`: the stream already holds the answer.
+    if stream.is_some() {
+        return Ok(Value::Null);
+    }
+    Ok(json!({ "items": all, "issues": issues.len() }))
+}
+
+async fn entries_of(
+    issue_key: &str,
+    history: &HistoryFilter,
+    as_markdown: bool,
+    client: &ApiClient,
+) -> Result<Vec<Value>> {
+    Ok(fetch_changelog(issue_key, client)
+        .await?
+        .into_iter()
+        .filter_map(|entry| history.keep(entry))
+        .map(|mut entry| {
+            entry["issue"] = json!(issue_key);
+            if as_markdown {
+                diff_descriptions(&mut entry);
+            }
+            entry
+        })
+        .collect())
+}
+
+/// Replaces a description change's `fromString`/`toString` with `diff`.
+fn diff_descriptions(entry: &mut Value) {
+    let Some(items) = entry["items"].as_array_mut() else {
+        return;
+    };
+    for item in items {
+        let is_description = item["fieldId"] == "description" || item["field"] == "description";
+        if !is_description {
+            continue;
+        }
+        let before = item["fromString"].as_str().unwrap_or_default().to_string();
+        let after = item["toString"].as_str().unwrap_or_default().to_string();
+        if let Some(item) = item.as_object_mut() {
+            item.remove("fromString");
+            item.remove("toString");
+            item.insert("diff".into(), json!(line_diff(&before, &after)));
+        }
+    }
+}
+
+/// Unchanged lines kept either side of a change.
+const DIFF_CONTEXT: usize = 2;
+
+/// Past this many line pairs the longest-common-subsequence table is not
+/// built: the before is shown removed and the after added, which is still a
+/// correct diff, only not a minimal one.
+const DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// `before` to `after`, line by line: `- ` removed, `+ ` added, `  `
+/// unchanged, and runs of unchanged lines far from any change cut to `…`.
+fn line_diff(before: &str, after: &str) -> String {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut lines: Vec<Line> = old[..prefix].iter().map(|l| Line::Same(l)).collect();
+    if a.len().saturating_mul(b.len()) > DIFF_CELLS {
+        lines.extend(a.iter().map(|l| Line::Removed(l)));
+        lines.extend(b.iter().map(|l| Line::Added(l)));
+    } else {
+        lines.extend(lcs_lines(a, b));
+    }
+    lines.extend(old[old.len() - suffix..].iter().map(|l| Line::Same(l)));
+
+    let near_change = |i: usize| {
+        let from = i.saturating_sub(DIFF_CONTEXT);
+        let to = (i + DIFF_CONTEXT + 1).min(lines.len());
+        lines[from..to].iter().any(|l| !matches!(l, Line::Same(_)))
+    };
+    let mut out = Vec::new();
+    let mut skipping = false;
+    for (i, line) in lines.iter().enumerate() {
+        match line {
+            Line::Same(text) if near_change(i) => {
+                skipping = false;
+                out.push(format!("  {text}"));
+            }
+            Line::Same(_) => {
+                if !skipping {
+                    out.push("  …".to_string());
+                }
+                skipping = true;
+            }
+            Line::Removed(text) => {
+                skipping = false;
+                out.push(format!("- {text}"));
+            }
+            Line::Added(text) => {
+                skipping = false;
+                out.push(format!("+ {text}"));
+            }
+        }
+    }
+    out.join("\n")
+}
+
+fn lcs_lines<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<Line<'a>> {
+    // lengths[i][j]: the longest common subsequence of a[i..] and b[j..].
+    let mut lengths = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::with_capacity(a.len() + b.len());
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            lines.push(Line::Same(a[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            lines.push(Line::Removed(a[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(b[j]));
+            j += 1;
+        }
+    }
+    lines.extend(a[i..].iter().map(|l| Line::Removed(l)));
+    lines.extend(b[j..].iter().map(|l| Line::Added(l)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_client;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_line_diff_marks_changes_and_cuts_distant_context() {
+        let before = "a\nb\nc\nd\ne\nf\ng";
+        let after = "a\nb\nc\nd\ne\nF\ng\nh";
+        assert_eq!(
+            line_diff(before, after),
+            "  …\n  d\n  e\n- f\n+ F\n  g\n+ h"
+        );
+        assert_eq!(line_diff("", "new"), "+ new");
+    }
+
+    #[tokio::test]
+    async fn integ_history_pages_and_keeps_only_the_asked_fields_and_days() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/P-1/changelog"))
+            .and(query_param("startAt", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "values": [
+                    {"id": "1", "created": "2024-03-01T09:00:00.000+0900",
+                     "items": [{"field": "status", "fromString": "To Do", "toString": "Done"}]},
+                    {"id": "2", "created": "2024-03-05T09:00:00.000+0900",
+                     "items": [{"field": "labels", "fromString": "", "toString": "api"}]}
+                ],
+                "isLast": false
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/P-1/changelog"))
+            .and(query_param("startAt", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "values": [
+                    {"id": "3", "created": "2024-03-09T09:00:00.000+0900",
+                     "items": [
+                        {"field": "description", "fieldId": "description",
+                         "fromString": "one\ntwo", "toString": "one\n2"},
+                        {"field": "status", "fromString": "Done", "toString": "To Do"}
+                     ]}
+                ],
+                "isLast": true
+            })))
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let only = HistoryFilter::new(
+            vec!["Status".into(), "description".into()],
+            Some("2024-03-02".into()),
+            None,
+        )
+        .unwrap();
+        let result = issue_history("P-1", &only, true, &client).await.unwrap();
+        assert_eq!(
+            result,
+            json!({"items": [{
+                "id": "3", "issue": "P-1", "created": "2024-03-09T09:00:00.000+0900",
+                "items": [
+                    {"field": "description", "fieldId": "description", "diff": "  one\n- two\n+ 2"},
+                    {"field": "status", "fromString": "Done", "toString": "To Do"}
+                ]
+            }]})
+        );
+        assert!(HistoryFilter::new(vec![], Some("March".into()), None).is_err());
+    }
+}
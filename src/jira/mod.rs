@@ -1,5 +1,13 @@
 pub mod adf;
 pub mod api;
+pub mod as_of;
+pub mod attachments;
+pub mod bulk;
+pub mod field_names;
 pub mod fields;
+pub mod filters;
+pub mod history;
+pub mod projects;
+pub mod users;
 
 pub use api::*;
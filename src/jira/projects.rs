@@ -0,0 +1,436 @@
+//! Projects, and the components and versions that belong to them.
+//!
+//! Every listing here is one of the Agile-style `values`/`isLast` collections,
+//! so each goes through `paginate` and answers with the same `{"items": [...]}`
+//! envelope as the other list commands. Writes answer the way issue writes
+//! do: a create names what it made, an update answers `{}`.
+//!
+//! A release cut is the reason most of this exists: create the next version,
+//! release the current one and move its unresolved issues on, archive what is
+//! long shipped, fold a version that should never have been split back into
+//! its twin.
+
+use crate::client::{ApiClient, Service};
+use crate::date_utils::is_day;
+use crate::filter;
+use crate::http_utils::encode_path_segment;
+use crate::jira::api::{AGILE_PAGE, list_envelope, paginate};
+use crate::jira::users::UserLookup;
+use crate::response::require_field;
+use anyhow::Result;
+use serde_json::{Map, Value, json};
+
+/// Every project the caller can browse, or those whose key or name contains
+/// `query`.
+pub async fn list_projects(query: Option<&str>, client: &ApiClient) -> Result<Value> {
+    let query: Vec<(&str, String)> = query
+        .map(|q| ("query", q.to_string()))
+        .into_iter()
+        .collect();
+    let items = paginate(
+        "/rest/api/3/project/search",
+        &query,
+        "list projects",
+        AGILE_PAGE,
+        client,
+    )
+    .await?;
+    Ok(list_envelope(items, client))
+}
+
+pub async fn get_project(project: &str, client: &ApiClient) -> Result<Value> {
+    let mut data = fetch_project(project, client).await?;
+    filter::apply(&mut data, client.config());
+    Ok(data)
+}
+
+async fn fetch_project(project: &str, client: &ApiClient) -> Result<Value> {
+    let url = format!("/rest/api/3/project/{}", encode_path_segment(project));
+    let request = client.get(Service::Jira, &url).await?;
+    let response = client.execute("get project", request).await?;
+    Ok(response.json().await?)
+}
+
+// -- Components --
+
+pub async fn list_components(project: &str, client: &ApiClient) -> Result<Value> {
+    let path = format!(
+        "/rest/api/3/project/{}/component",
+        encode_path_segment(project)
+    );
+    let items = paginate(&path, &[], "list components", AGILE_PAGE, client).await?;
+    Ok(list_envelope(items, client))
+}
+
+/// What a component create or update sets. A part left `None` is not sent,
+/// so an update changes only what it names.
+#[derive(Debug, Default)]
+pub struct ComponentFields {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// An account id, email, display name, or `me`; `none` clears the lead.
+    pub lead: Option<String>,
+}
+
+impl ComponentFields {
+    async fn body(&self, client: &ApiClient) -> Result<Map<String, Value>> {
+        let mut body = Map::new();
+        if let Some(name) = &self.name {
+            body.insert("name".into(), json!(name));
+        }
+        if let Some(description) = &self.description {
+            body.insert("description".into(), json!(description));
+        }
+        if let Some(lead) = &self.lead {
+            let lead = if lead.eq_ignore_ascii_case("none") {
+                Value::Null
+            } else {
+                json!(UserLookup::default().account_id(lead, client).await?)
+            };
+            body.insert("leadAccountId".into(), lead);
+        }
+        Ok(body)
+    }
+}
+
+pub async fn create_component(
+    project: &str,
+    fields: &ComponentFields,
+    client: &ApiClient,
+) -> Result<Value> {
+    let mut body = fields.body(client).await?;
+    body.insert("project".into(), json!(project));
+
+    let request = client
+        .post(Service::Jira, "/rest/api/3/component")
+        .await?
+        .header("Content-Type", "application/json")
+        .json(&body);
+    let response = client.execute("create component", request).await?;
+
+    let data: Value = response.json().await?;
+    Ok(json!({
+        "id": require_field(&data, "/id", "create component")?,
+        "name": require_field(&data, "/name", "create component")?,
+    }))
+}
+
+pub async fn update_component(
+    component_id: &str,
+    fields: &ComponentFields,
+    client: &ApiClient,
+) -> Result<Value> {
+    let body = fields.body(client).await?;
+    if body.is_empty() {
+        anyhow::bail!("Nothing to update: give --name, --description, or --lead");
+    }
+    let url = format!(
+        "/rest/api/3/component/{}",
+        encode_path_segment(component_id)
+    );
+    let request = client
+        .put(Service::Jira, &url)
+        .await?
+        .header("Content-Type", "application/json")
+        .json(&body);
+    client.execute("update component", request).await?;
+    Ok(json!({}))
+}
+
+/// Delete a component. Its issues lose it, or move to `move_issues_to` when
+/// given. Irreversible, so the CLI layer requires an explicit `--yes`.
+pub async fn delete_component(
+    component_id: &str,
+    move_issues_to: Option<&str>,
+    client: &ApiClient,
+) -> Result<Value> {
+    let url = format!(
+        "/rest/api/3/component/{}",
+        encode_path_segment(component_id)
+    );
+    let mut request = client.delete(Service::Jira, &url).await?;
+    if let Some(target) = move_issues_to {
+        request = request.query(&[("moveIssuesTo", target)]);
+    }
+    client.execute("delete component", request).await?;
+    Ok(json!({}))
+}
+
+// -- Versions --
+
+/// A project's versions. `status` narrows them as Jira does: a comma list of
+/// `released`, `unreleased` and `archived`.
+pub async fn list_versions(
+    project: &str,
+    status: Option<&str>,
+    client: &ApiClient,
+) -> Result<Value> {
+    let path = format!(
+        "/rest/api/3/project/{}/version",
+        encode_path_segment(project)
+    );
+    let query: Vec<(&str, String)> = status
+        .map(|s| ("status", s.to_string()))
+        .into_iter()
+        .collect();
+    let items = paginate(&path, &query, "list versions", AGILE_PAGE, client).await?;
+    Ok(list_envelope(items, client))
+}
+
+/// What a version create or update sets. A part left `None` is not sent,
+/// so an update changes only what it names.
+#[derive(Debug, Default)]
+pub struct VersionFields {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// `YYYY-MM-DD`.
+    pub start_date: Option<String>,
+    /// `YYYY-MM-DD`.
+    pub release_date: Option<String>,
+}
+
+impl VersionFields {
+    /// The body, with the dates checked first: Jira answers a malformed one
+    /// with a parse error that does not say which of the two it was.
+    fn body(&self) -> Result<Map<String, Value>> {
+        let mut body = Map::new();
+        for (key, flag, value) in [
+            ("name", "", &self.name),
+            ("description", "", &self.description),
+            ("startDate", "--start-date", &self.start_date),
+            ("releaseDate", "--release-date", &self.release_date),
+        ] {
+            let Some(value) = value else { continue };
+            if !flag.is_empty() && !is_day(value) {
+                anyhow::bail!("{flag} takes a day as YYYY-MM-DD, got '{value}'");
+            }
+            body.insert(key.into(), json!(value));
+        }
+        Ok(body)
+    }
+}
+
+pub async fn create_version(
+    project: &str,
+    fields: &VersionFields,
+    client: &ApiClient,
+) -> Result<Value> {
+    let mut body = fields.body()?;
+    // The version body names its project by numeric id only.
+    let project_id = match project.parse::<u64>() {
+        Ok(id) => id,
+        Err(_) => {
+            let data = fetch_project(project, client).await?;
+            let id = require_field(&data, "/id", "get project")?;
+            id.as_str()
+                .and_then(|id| id.parse().ok())
+                .ok_or_else(|| anyhow::anyhow!("Project {project} has a non-numeric id: {id}"))?
+        }
+    };
+    body.insert("projectId".into(), json!(project_id));
+
+    let request = client
+        .post(Service::Jira, "/rest/api/3/version")
+        .await?
+        .header("Content-Type", "application/json")
+        .json(&body);
+    let response = client.execute("create version", request).await?;
+
+    let data: Value = response.json().await?;
+    Ok(json!({
+        "id": require_field(&data, "/id", "create version")?,
+        "name": require_field(&data, "/name", "create version")?,
+    }))
+}
+
+pub async fn update_version(
+    version_id: &str,
+    fields: &VersionFields,
+    client: &ApiClient,
+) -> Result<Value> {
+    let body = fields.body()?;
+    if body.is_empty() {
+        anyhow::bail!(
+            "Nothing to update: give --name, --description, --start-date, or --release-date"
+        );
+    }
+    put_version(version_id, Value::Object(body), "update version", client).await
+}
+
+/// Mark a version released, on `date` when given. With `move_unresolved_to`
+/// its unresolved issues are moved to that version in the same request, as
+/// the release dialog offers; otherwise they stay where they are.
+pub async fn release_version(
+    version_id: &str,
+    date: Option<&str>,
+    move_unresolved_to: Option<&str>,
+    client: &ApiClient,
+) -> Result<Value> {
+    let mut body = VersionFields {
+        release_date: date.map(str::to_string),
+        ..Default::default()
+    }
+    .body()?;
+    body.insert("released".into(), json!(true));
+    if let Some(target) = move_unresolved_to {
+        // Jira takes the target as the version's URL, not its id.
+        let url = format!("/rest/api/3/version/{}", encode_path_segment(target));
+        let request = client.get(Service::Jira, &url).await?;
+        let response = client.execute("get version", request).await?;
+        let data: Value = response.json().await?;
+        body.insert(
+            "moveUnfixedIssuesTo".into(),
+            require_field(&data, "/self", "get version")?,
+        );
+    }
+    put_version(version_id, Value::Object(body), "release version", client).await
+}
+
+pub async fn archive_version(version_id: &str, client: &ApiClient) -> Result<Value> {
+    put_version(
+        version_id,
+        json!({ "archived": true }),
+        "archive version",
+        client,
+    )
+    .await
+}
+
+async fn put_version(
+    version_id: &str,
+    body: Value,
+    operation: &str,
+    client: &ApiClient,
+) -> Result<Value> {
+    let url = format!("/rest/api/3/version/{}", encode_path_segment(version_id));
+    let request = client
+        .put(Service::Jira, &url)
+        .await?
+        .header("Content-Type", "application/json")
+        .json(&body);
+    client.execute(operation, request).await?;
+    Ok(json!({}))
+}
+
+/// Move every issue of `version_id` to `into` and delete `version_id`.
+/// Irreversible, so the CLI layer requires an explicit `--yes`.
+pub async fn merge_version(version_id: &str, into: &str, client: &ApiClient) -> Result<Value> {
+    let url = format!(
+        "/rest/api/3/version/{}/mergeto/{}",
+        encode_path_segment(version_id),
+        encode_path_segment(into)
+    );
+    let request = client.put(Service::Jira, &url).await?;
+    client.execute("merge version", request).await?;
+    Ok(json!({}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_client;
+    use wiremock::matchers::{body_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn integ_list_versions_pages_with_the_status_filter() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/project/PROJ/version"))
+            .and(query_param("status", "unreleased"))
+            .and(query_param("startAt", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "values": [{"id": "1", "name": "1.0"}],
+                "isLast": false
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/project/PROJ/version"))
+            .and(query_param("status", "unreleased"))
+            .and(query_param("startAt", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "values": [{"id": "2", "name": "1.1"}],
+                "isLast": true
+            })))
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let result = list_versions("PROJ", Some("unreleased"), &client)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            json!({"items": [{"id": "1", "name": "1.0"}, {"id": "2", "name": "1.1"}]})
+        );
+    }
+
+    #[tokio::test]
+    async fn integ_create_version_names_the_project_by_id() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/project/PROJ"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({"id": "10000", "key": "PROJ"})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/version"))
+            .and(body_json(json!({
+                "name": "2.0", "releaseDate": "2024-06-01", "projectId": 10000
+            })))
+            .respond_with(
+                ResponseTemplate::new(201).set_body_json(json!({"id": "42", "name": "2.0"})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let fields = VersionFields {
+            name: Some("2.0".into()),
+            release_date: Some("2024-06-01".into()),
+            ..Default::default()
+        };
+        let result = create_version("PROJ", &fields, &client).await.unwrap();
+        assert_eq!(result, json!({"id": "42", "name": "2.0"}));
+
+        let bad = VersionFields {
+            start_date: Some("June".into()),
+            ..Default::default()
+        };
+        let err = create_version("PROJ", &bad, &client).await.unwrap_err();
+        assert!(err.to_string().contains("--start-date"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn integ_release_version_moves_unresolved_issues_by_url() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/version/43"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "43", "self": "https://example.atlassian.net/rest/api/3/version/43"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/rest/api/3/version/42"))
+            .and(body_json(json!({
+                "releaseDate": "2024-06-01",
+                "released": true,
+                "moveUnfixedIssuesTo": "https://example.atlassian.net/rest/api/3/version/43"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "42"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let result = release_version("42", Some("2024-06-01"), Some("43"), &client)
+            .await
+            .unwrap();
+        assert_eq!(result, json!({}));
+    }
+}
@@ -0,0 +1,423 @@
+//! Users both ways round: display names for account ids, looked up for the
+//! Markdown renderers, and account ids for the names people type.
+//!
+//! Jira and Confluence bodies name people by account id. The bulk user
+//! endpoint turns a batch of ids into names in one request, and one
+//! `UserResolver` per invocation remembers every answer, so a search that
+//! renders a hundred issues mentioning the same ten people asks about each
+//! of them once.
+//!
+//! The other direction is `UserLookup`: `assign`, `watcher --user`, and any
+//! `{"accountId": ...}` written into `--fields` take an email, a display
+//! name, or `me` where the API wants an id. Nobody knows their colleagues'
+//! account ids, and looking one up by hand was the only way to set a user
+//! field before.
+
+use crate::client::{ApiClient, ApiError, Service};
+use crate::jira::api::{get_myself, list_envelope};
+use crate::markdown::UserNames;
+use crate::response::{WHOLE_BODY, require_array, require_field};
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+
+/// Ids per request. The endpoint pages its answer, and a batch no larger than
+/// the page it is asked for comes back in one.
+const BULK_BATCH: usize = 50;
+
+#[derive(Default)]
+pub struct UserResolver {
+    names: UserNames,
+    asked: HashSet<String>,
+    /// Set once the site refuses the lookup: asking again for the next batch
+    /// would only be refused again.
+    unavailable: bool,
+}
+
+impl UserResolver {
+    /// Looks up the ids not asked about before and returns every name known
+    /// so far. An id the site does not return (a deleted account) stays
+    /// unnamed, and renders as itself.
+    ///
+    /// A refused lookup is not an error: reading a page does not need the
+    /// Browse-users permission, and a caller without it still gets the body,
+    /// with ids where the names would be. A 404 is treated the same way —
+    /// a Confluence-only site has no Jira user endpoint to ask.
+    pub async fn resolve(
+        &mut self,
+        account_ids: impl IntoIterator<Item = String>,
+        client: &ApiClient,
+    ) -> Result<&UserNames> {
+        let wanted: Vec<String> = account_ids
+            .into_iter()
+            .filter(|id| !id.is_empty() && self.asked.insert(id.clone()))
+            .collect();
+        if self.unavailable {
+            return Ok(&self.names);
+        }
+
+        let page_size = BULK_BATCH.to_string();
+        for batch in wanted.chunks(BULK_BATCH) {
+            let mut query: Vec<(&str, &str)> =
+                batch.iter().map(|id| ("accountId", id.as_str())).collect();
+            query.push(("maxResults", page_size.as_str()));
+
+            let request = client
+                .get(Service::Jira, "/rest/api/3/user/bulk")
+                .await?
+                .query(&query)
+                .header("Accept", "application/json");
+            let response = match client.execute("look up users", request).await {
+                Ok(response) => response,
+                Err(err) if is_refusal(&err) => {
+                    tracing::debug!(error = %err, "user lookup refused; showing account ids");
+                    self.unavailable = true;
+                    break;
+                }
+                Err(err) => return Err(err),
+            };
+
+            let data: serde_json::Value = response.json().await?;
+            for user in require_array(&data, "/values", "look up users")? {
+                if let (Some(id), Some(name)) =
+                    (user["accountId"].as_str(), user["displayName"].as_str())
+                {
+                    self.names.insert(id, name);
+                }
+            }
+        }
+
+        Ok(&self.names)
+    }
+}
+
+fn is_refusal(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ApiError>().is_some_and(|api| {
+        matches!(
+            api.status,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::NOT_FOUND
+        )
+    })
+}
+
+/// Users matching `query` — a prefix of a display name or an email address.
+pub async fn search_users(query: &str, limit: u32, client: &ApiClient) -> Result<Value> {
+    Ok(list_envelope(
+        find_users(query, limit, client).await?,
+        client,
+    ))
+}
+
+/// One user, named however `UserLookup` accepts.
+pub async fn get_user(who: &str, client: &ApiClient) -> Result<Value> {
+    let account_id = UserLookup::default().account_id(who, client).await?;
+    let request = client
+        .get(Service::Jira, "/rest/api/3/user")
+        .await?
+        .query(&[("accountId", account_id.as_str())])
+        .header("Accept", "application/json");
+    let response = client.execute("get user", request).await?;
+    let mut user: Value = response.json().await?;
+    crate::filter::apply(&mut user, client.config());
+    Ok(user)
+}
+
+async fn find_users(query: &str, limit: u32, client: &ApiClient) -> Result<Vec<Value>> {
+    let request = client
+        .get(Service::Jira, "/rest/api/3/user/search")
+        .await?
+        .query(&[("query", query), ("maxResults", &limit.to_string())])
+        .header("Accept", "application/json");
+    let response = client.execute("search users", request).await?;
+    let data: Value = response.json().await?;
+    require_array(&data, WHOLE_BODY, "search users")
+}
+
+/// Candidates fetched per name. A name that needs more than this to tell
+/// apart is refused as ambiguous either way.
+const LOOKUP_CANDIDATES: u32 = 20;
+
+/// Account ids for what a person types: an account id as it is, `me`, an
+/// email address, or a display name. One per invocation, remembering every
+/// answer.
+#[derive(Default)]
+pub struct UserLookup {
+    known: HashMap<String, String>,
+}
+
+impl UserLookup {
+    /// The one account `who` names. A search that finds several is not
+    /// guessed between — unless exactly one of them has `who` as its whole
+    /// email or display name — and the error lists them so the caller can
+    /// pick by id or by email.
+    pub async fn account_id(&mut self, who: &str, client: &ApiClient) -> Result<String> {
+        let who = who.trim();
+        if reads_as_account_id(who) {
+            return Ok(who.to_string());
+        }
+        if let Some(id) = self.known.get(who) {
+            return Ok(id.clone());
+        }
+
+        let id = if who.eq_ignore_ascii_case("me") {
+            let me = get_myself(client).await?;
+            require_field(&me, "/accountId", "get current user")?
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        } else {
+            let found = find_users(who, LOOKUP_CANDIDATES, client).await?;
+            let candidates: Vec<&Value> = found
+                .iter()
+                .filter(|user| user["active"].as_bool() != Some(false))
+                .collect();
+            let exact: Vec<&Value> = candidates
+                .iter()
+                .copied()
+                .filter(|user| {
+                    ["displayName", "emailAddress"].iter().any(|key| {
+                        user[*key]
+                            .as_str()
+                            .is_some_and(|v| v.eq_ignore_ascii_case(who))
+                    })
+                })
+                .collect();
+            let user = match (exact.as_slice(), candidates.as_slice()) {
+                ([one], _) | ([], [one]) => *one,
+                ([], []) => anyhow::bail!("No active user matches '{who}'"),
+                ([], several) | (several @ [_, _, ..], _) => anyhow::bail!(
+                    "'{who}' matches several users: {} — give an email or an account id",
+                    several
+                        .iter()
+                        .map(|user| describe(user))
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                ),
+            };
+            require_field(user, "/accountId", "search users")?
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        };
+        self.known.insert(who.to_string(), id.clone());
+        Ok(id)
+    }
+
+    /// Rewrites every `{"accountId": "<who>"}` in `value` whose `<who>` names
+    /// a person rather than an account, at any depth — a multi-user field is
+    /// an array of them. This is the shape every user field takes, so a field
+    /// needs no schema lookup to be recognised as one.
+    ///
+    /// Only an email, a name with a space in it, or `me` is looked up. Ids
+    /// come in more forms than can be listed — a service-desk customer's
+    /// `qm:<uuid>:<uuid>`, the `-1` that picks the default assignee — and a
+    /// value sent here as an id has to reach Jira as it was written.
+    pub async fn resolve_in(&mut self, value: &mut Value, client: &ApiClient) -> Result<()> {
+        let mut found = Vec::new();
+        user_references(value, String::new(), &mut found);
+        for (pointer, who) in found {
+            let id = self.account_id(&who, client).await?;
+            if let Some(slot) = value.pointer_mut(&pointer) {
+                *slot = json!(id);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pointers to the `accountId` strings under `value` that still need
+/// looking up.
+fn user_references(value: &Value, pointer: String, found: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, inner) in map {
+                let pointer = format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"));
+                match (key.as_str(), inner) {
+                    ("accountId", Value::String(who)) if names_a_person(who) => {
+                        found.push((pointer, who.clone()));
+                    }
+                    _ => user_references(inner, pointer, found),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, inner) in items.iter().enumerate() {
+                user_references(inner, format!("{pointer}/{i}"), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether `s` reads as an account id rather than a name: 24 hex digits, a
+/// scoped `<prefix>:<id>` such as `557058:<uuid>` or a service-desk
+/// customer's `qm:<uuid>:<uuid>`, or `-1`. Not every id Jira may hand out
+/// is covered, which is why a `--fields` value is looked up only when it
+/// plainly names a person — see `names_a_person`.
+fn reads_as_account_id(s: &str) -> bool {
+    let hex = s.len() == 24 && s.chars().all(|c| c.is_ascii_hexdigit());
+    let scoped = s.split_once(':').is_some_and(|(prefix, rest)| {
+        !prefix.is_empty()
+            && prefix.chars().all(|c| c.is_ascii_alphanumeric())
+            && !rest.is_empty()
+            && rest
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == ':')
+    });
+    hex || scoped || s == "-1"
+}
+
+/// Whether an `accountId` value is a person's email, full name, or `me` —
+/// what no account id looks like.
+fn names_a_person(s: &str) -> bool {
+    let s = s.trim();
+    s.contains('@') || s.contains(char::is_whitespace) || s.eq_ignore_ascii_case("me")
+}
+
+fn describe(user: &Value) -> String {
+    let name = user["displayName"].as_str().unwrap_or("?");
+    let id = user["accountId"].as_str().unwrap_or("?");
+    match user["emailAddress"].as_str() {
+        Some(email) => format!("{name} <{email}> ({id})"),
+        None => format!("{name} ({id})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_client;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn integ_each_id_is_asked_about_once_per_invocation() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/bulk"))
+            .and(query_param("accountId", "a"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "values": [{"accountId": "a", "displayName": "Ann"}],
+                "isLast": true
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let mut resolver = UserResolver::default();
+        let names = resolver.resolve(vec!["a".into()], &client).await.unwrap();
+        assert_eq!(names.get("a"), Some("Ann"));
+        let names = resolver.resolve(vec!["a".into()], &client).await.unwrap();
+        assert_eq!(names.get("a"), Some("Ann"));
+    }
+
+    #[tokio::test]
+    async fn integ_a_refused_lookup_leaves_ids_unnamed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/bulk"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("no browse users"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let mut resolver = UserResolver::default();
+        let names = resolver.resolve(vec!["a".into()], &client).await.unwrap();
+        assert_eq!(names.get("a"), None);
+        // Refused once is refused for the invocation: no second request.
+        resolver.resolve(vec!["b".into()], &client).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn integ_other_failures_are_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/bulk"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+
+        let err = UserResolver::default()
+            .resolve(vec!["a".into()], &client)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("look up users"));
+    }
+
+    #[tokio::test]
+    async fn integ_a_name_resolves_only_when_it_names_one_user() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/search"))
+            .and(query_param("query", "ann"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"accountId": "557058:aaa", "displayName": "Ann Lee", "emailAddress": "ann@x.io"},
+                {"accountId": "557058:bbb", "displayName": "Anna Park"}
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/user/search"))
+            .and(query_param("query", "ann@x.io"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"accountId": "557058:aaa", "displayName": "Ann Lee", "emailAddress": "ann@x.io"}
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(server.uri());
+        let mut lookup = UserLookup::default();
+
+        let err = lookup.account_id("ann", &client).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Ann Lee <ann@x.io> (557058:aaa); Anna Park (557058:bbb)"),
+            "{err}"
+        );
+
+        let mut fields = json!({
+            "assignee": {"accountId": "ann@x.io"},
+            "customfield_1": [{"accountId": "ann@x.io"}, {"accountId": "5b10ac8d82e05b22cc7d4ef5"}]
+        });
+        lookup.resolve_in(&mut fields, &client).await.unwrap();
+        assert_eq!(
+            fields,
+            json!({
+                "assignee": {"accountId": "557058:aaa"},
+                "customfield_1": [{"accountId": "557058:aaa"}, {"accountId": "5b10ac8d82e05b22cc7d4ef5"}]
+            })
+        );
+    }
+
+    /// Ids of other shapes reach Jira as written; only the search above is
+    /// mounted, so a lookup of either would fail the test.
+    #[tokio::test]
+    async fn integ_ids_of_other_shapes_pass_through() {
+        let server = MockServer::start().await;
+        let client = mock_client(server.uri());
+        let customer =
+            "qm:1d3c5b2a-0000-4000-8000-000000000001:5e6f7a8b-0000-4000-8000-000000000002";
+        let mut fields = json!({
+            "reporter": {"accountId": customer},
+            "assignee": {"accountId": "-1"}
+        });
+        let before = fields.clone();
+        UserLookup::default()
+            .resolve_in(&mut fields, &client)
+            .await
+            .unwrap();
+        assert_eq!(fields, before);
+        assert!(
+            UserLookup::default()
+                .account_id(customer, &client)
+                .await
+                .is_ok()
+        );
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+}
@@ -2,13 +2,16 @@ pub mod auth;
 pub mod client;
 pub mod config;
 pub mod confluence;
+pub(crate) mod date_utils;
 pub mod dist;
 pub mod filter;
 pub(crate) mod http_utils;
 pub mod jira;
 pub mod markdown;
+pub mod output;
 pub(crate) mod query_utils;
 pub(crate) mod response;
+pub mod select;
 
 #[cfg(test)]
 mod test_support;
@@ -44,3 +47,4 @@ pub use client::ApiError;
 pub use client::Service;
 pub use config::CliOverrides;
 pub use config::Config;
+pub use http_utils::Destination;
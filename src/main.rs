@@ -9,6 +9,63 @@ enum OutputFormat {
     Markdown,
 }
 
+/// How a result is printed. `json` is what every command answers. `md` is
+/// `get`'s item as a Markdown document with its metadata as YAML front
+/// matter. `csv` and `tsv` write an `items` list as rows of `--columns`;
+/// `table` aligns it for a terminal, in default columns unless named.
+///
+/// This is `get`'s own `--output json|md`, widened to every printing command
+/// rather than joined by a second flag: it is still written after `get`,
+/// still defaults to `json`, and `md` still means what it meant there.
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum Output {
+    #[default]
+    Json,
+    Md,
+    Csv,
+    Tsv,
+    Table,
+}
+
+/// How a Jira body argument is read before it becomes ADF. `text` keeps the
+/// historical behaviour — one paragraph, or an ADF JSON string used as-is.
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum InputFormat {
+    #[default]
+    Text,
+    Markdown,
+}
+
+/// How a Confluence body argument is read. `storage` is sent verbatim, as it
+/// always was; `markdown` is converted to storage first.
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum BodyFormat {
+    #[default]
+    Storage,
+    Markdown,
+}
+
+/// How `confluence get` returns the page body: storage HTML as stored,
+/// Markdown rendered from it, or the page's ADF document.
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum PageFormat {
+    #[default]
+    Html,
+    Markdown,
+    Adf,
+}
+
+/// How a page body argument is read: as `BodyFormat`, or as an ADF JSON
+/// document written in that representation, so a body from `jira get` or
+/// `confluence get --format adf` can be sent as it is.
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum PageBodyFormat {
+    #[default]
+    Storage,
+    Markdown,
+    Adf,
+}
+
 #[derive(Parser)]
 #[command(name = "atlassian-cli", version, about = "CLI for Atlassian Jira and Confluence", long_about = None)]
 struct Cli {
@@ -42,6 +99,31 @@ struct Cli {
     #[arg(long, help = "Pretty-print JSON output")]
     pretty: bool,
 
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value = "json",
+        help = "Print JSON; md: a get's Markdown document with YAML front matter; csv/tsv: rows of --columns; table: aligned columns for reading"
+    )]
+    output: Output,
+
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        help = "Keep only these paths of the result: JSON pointers or items[].fields.status.name"
+    )]
+    select: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        help = "Columns for --output csv/tsv/table: top-level keys or JSON pointers (e.g. key,/fields/status/name)"
+    )]
+    columns: Option<Vec<String>>,
+
     #[arg(short, long, action = clap::ArgAction::Count, help = "Verbose logging")]
     verbose: u8,
 }
@@ -174,39 +256,104 @@ enum JiraSubcommand {
         fields: Option<Vec<String>>,
         #[arg(long, value_enum, default_value = "html", help = "ADF content format")]
         format: OutputFormat,
+        #[arg(
+            long,
+            help = "Keep nodes Markdown cannot express as markers, so the text can be edited and written back (requires --format markdown)"
+        )]
+        lossless: bool,
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Link attached images and files as DIR/<filename> instead of their URLs (requires --format markdown)"
+        )]
+        media_dir: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Show custom fields under their names instead of customfield_* ids"
+        )]
+        field_names: bool,
+        #[arg(
+            long,
+            value_name = "TIME",
+            help = "Show the issue as it was at a day or time (2024-03-01, 2024-03-01T17:30+09:00), rewound through its changelog"
+        )]
+        as_of: Option<String>,
     },
     /// Search issues with JQL; the configured project filter is auto-injected
     Search {
-        jql: String,
+        #[arg(required_unless_present = "filter")]
+        jql: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "jql",
+            help = "Run a saved filter's JQL instead, by filter id or name"
+        )]
+        filter: Option<String>,
         #[arg(long, default_value = "100", help = "Results per page")]
         limit: u32,
         #[arg(long, help = "Fetch all results via token pagination")]
         all: bool,
-        #[arg(long, help = "Stream as JSONL (requires --all)")]
+        #[arg(
+            long,
+            help = "Write each page as it arrives: JSONL, or rows with --output csv/tsv (requires --all)"
+        )]
         stream: bool,
         #[arg(long, value_delimiter = ',', help = "Fields to return")]
         fields: Option<Vec<String>>,
         #[arg(long, value_enum, default_value = "html", help = "ADF content format")]
         format: OutputFormat,
+        #[arg(
+            long,
+            help = "Show custom fields under their names instead of customfield_* ids"
+        )]
+        field_names: bool,
     },
-    /// Create an issue (plain-text description auto-converts to ADF)
+    /// Create an issue (plain-text description auto-converts to ADF), or many
+    /// from a file with --from
     Create {
-        project: String,
-        summary: String,
-        issue_type: String,
-        #[arg(long)]
+        #[arg(required_unless_present = "from")]
+        project: Option<String>,
+        #[arg(required_unless_present = "from")]
+        summary: Option<String>,
+        #[arg(required_unless_present = "from")]
+        issue_type: Option<String>,
+        #[arg(long, conflicts_with = "from")]
         description: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "How --description (or a file's descriptions) is read"
+        )]
+        input_format: InputFormat,
         /// Parent issue key — required by every sub-task type, and how a
         /// team-managed project puts an issue under its epic
-        #[arg(long)]
+        #[arg(long, conflicts_with = "from")]
         parent: Option<String>,
         /// Any other field the create screen requires, as a JSON object —
         /// '{"components":[{"name":"api"}],"customfield_10010":"X"}'
-        #[arg(long)]
+        #[arg(long, conflicts_with = "from")]
         fields: Option<String>,
+        /// Create every issue in a .jsonl, .csv or .toml file, in batches;
+        /// a row's parent can be "#<ref>" of a row above it
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["project", "summary", "issue_type"])]
+        from: Option<PathBuf>,
+        /// Rename the file's columns or keys, e.g. "Title=summary,Points=Story Points"
+        #[arg(long, value_delimiter = ',', requires = "from")]
+        map: Vec<String>,
     },
     /// Update an issue's fields from a JSON object (e.g. '{"summary":"..."}')
-    Update { issue_key: String, fields: String },
+    Update {
+        issue_key: String,
+        fields: String,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "How a string \"description\" in the fields is read"
+        )]
+        input_format: InputFormat,
+    },
     /// Permanently delete an issue (irreversible — requires --yes)
     Delete {
         issue_key: String,
@@ -237,6 +384,48 @@ enum JiraSubcommand {
         #[command(subcommand)]
         action: WorklogAction,
     },
+    /// Show who changed what on an issue, or on every issue a query matches
+    History {
+        #[arg(required_unless_present = "jql", conflicts_with = "jql")]
+        issue_key: Option<String>,
+        /// Read the history of every issue this query matches instead
+        #[arg(long)]
+        jql: Option<String>,
+        /// Keep only changes to these fields (names or ids)
+        #[arg(long = "field", value_delimiter = ',')]
+        fields: Vec<String>,
+        /// Keep only changes made on or after this day (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// Keep only changes made on or before this day (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "html",
+            help = "markdown shows description changes as line diffs"
+        )]
+        format: OutputFormat,
+        #[arg(
+            long,
+            requires = "jql",
+            help = "Write each issue's entries as they arrive"
+        )]
+        stream: bool,
+    },
+    /// Assign an issue to a user (account id, email, display name, "me"), or "none"
+    Assign { issue_key: String, user: String },
+    /// Search for users, or look one up to find their account id
+    User {
+        #[command(subcommand)]
+        action: UserAction,
+    },
+    /// List, upload, download, or delete the files attached to an issue
+    Attachment {
+        #[command(subcommand)]
+        action: JiraAttachmentAction,
+    },
     /// Start watching, stop watching, or list watchers on an issue
     Watcher {
         #[command(subcommand)]
@@ -247,12 +436,33 @@ enum JiraSubcommand {
         #[command(subcommand)]
         action: ListAction,
     },
+    /// List, get, create, update, or delete saved filters
+    Filter {
+        #[command(subcommand)]
+        action: FilterAction,
+    },
+    /// List projects, or get one by key
+    Project {
+        #[command(subcommand)]
+        action: ProjectAction,
+    },
+    /// List, create, update, or delete a project's components
+    Component {
+        #[command(subcommand)]
+        action: ComponentAction,
+    },
+    /// List, create, update, release, archive, or merge a project's versions
+    Version {
+        #[command(subcommand)]
+        action: VersionAction,
+    },
     /// List agile boards for a project
     Board {
         #[command(subcommand)]
         action: BoardAction,
     },
-    /// List sprints, or move issues between a sprint and the backlog
+    /// List, create, start, update, or complete sprints, or move issues between
+    /// a sprint and the backlog
     Sprint {
         #[command(subcommand)]
         action: SprintAction,
@@ -262,23 +472,88 @@ enum JiraSubcommand {
         #[command(subcommand)]
         action: EpicAction,
     },
+    /// Edit every issue a JQL query matches
+    Bulk {
+        #[command(subcommand)]
+        action: BulkAction,
+    },
+    /// Check ADF documents offline, without a profile or credentials
+    Adf {
+        #[command(subcommand)]
+        action: AdfAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum BulkAction {
+    /// Set, add, or remove field values on every matching issue
+    #[command(group(clap::ArgGroup::new("confirm").required(true).args(["dry_run", "yes"])))]
+    Edit {
+        /// The issues to edit (the profile's projects_filter still applies)
+        #[arg(long)]
+        jql: String,
+        /// Replace a field: FIELD=VALUE, a value starting with { [ " or null read as
+        /// JSON and anything else as a plain string
+        #[arg(long, value_name = "FIELD=VALUE")]
+        set: Vec<String>,
+        /// Replace a field with a JSON value, e.g. 'Story Points=5'
+        #[arg(long, value_name = "FIELD=JSON")]
+        set_json: Vec<String>,
+        /// Add one value to a list field, e.g. labels=triage
+        #[arg(long, value_name = "FIELD=VALUE")]
+        add: Vec<String>,
+        /// Remove one value from a list field, e.g. labels=stale
+        #[arg(long, value_name = "FIELD=VALUE")]
+        remove: Vec<String>,
+        /// List the matching issues and the edit without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Apply the edit
+        #[arg(long)]
+        yes: bool,
+        /// Refuse when more issues than this match
+        #[arg(long, default_value_t = 100)]
+        max: usize,
+    },
 }
 
 #[derive(Subcommand)]
 enum CommentAction {
     /// Add a comment to an issue
-    Add { issue_key: String, text: String },
+    Add {
+        issue_key: String,
+        text: String,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "How the text is read"
+        )]
+        input_format: InputFormat,
+    },
     /// Update an existing comment
     Update {
         issue_key: String,
         comment_id: String,
         text: String,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "How the text is read"
+        )]
+        input_format: InputFormat,
     },
     /// List comments on an issue
     List {
         issue_key: String,
         #[arg(long, value_enum, default_value = "html", help = "ADF content format")]
         format: OutputFormat,
+        #[arg(
+            long,
+            help = "Keep nodes Markdown cannot express as markers (requires --format markdown)"
+        )]
+        lossless: bool,
     },
     /// Delete a comment by id
     Delete {
@@ -292,7 +567,24 @@ enum TransitionAction {
     /// Apply a transition to an issue
     Apply {
         issue_key: String,
-        transition_id: String,
+        /// Transition id, transition name, or the name of the status it leads to
+        transition: String,
+        /// Resolution to set, for a transition whose screen requires one
+        #[arg(long)]
+        resolution: Option<String>,
+        /// Comment to add with the transition
+        #[arg(long)]
+        comment: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "How --comment is read"
+        )]
+        input_format: InputFormat,
+        /// Other fields the transition screen requires, as a JSON object
+        #[arg(long)]
+        fields: Option<String>,
     },
     /// List available transitions for an issue
     List { issue_key: String },
@@ -380,17 +672,72 @@ enum WorklogAction {
     },
 }
 
+#[derive(Subcommand)]
+enum UserAction {
+    /// Find users by a prefix of their name or email
+    Search {
+        query: String,
+        #[arg(long, default_value = "50", help = "Most users to return")]
+        limit: u32,
+    },
+    /// Show one user: account id, email, display name, or "me"
+    Get { user: String },
+}
+
+#[derive(Subcommand)]
+enum JiraAttachmentAction {
+    /// List the attachments on an issue
+    List { issue_key: String },
+    /// Attach one or more local files to an issue
+    Upload {
+        issue_key: String,
+        /// Paths to the local files to upload
+        #[arg(required = true)]
+        files: Vec<String>,
+        /// Override the Content-Type (default: mapped from each file's extension)
+        #[arg(long = "content-type")]
+        content_type: Option<String>,
+    },
+    /// Save an attachment by id, or with --all every attachment on an issue
+    Download {
+        /// Attachment id; with --all, the issue key
+        target: String,
+        /// Save every attachment on the issue named by TARGET
+        #[arg(long)]
+        all: bool,
+        /// Directory to save into, under the uploaded names (default: current directory)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// File to save a single attachment to, or - for stdout
+        #[arg(long, conflicts_with_all = ["all", "dir"])]
+        to: Option<PathBuf>,
+    },
+    /// Delete an attachment by id (irreversible — requires --yes)
+    Delete {
+        attachment_id: String,
+        /// Confirm the irreversible deletion
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum WatcherAction {
-    /// Start watching an issue (adds current user)
+    /// Start watching an issue (adds current user, or --user)
     Add {
         /// Issue key
         issue_key: String,
+        /// Someone else to add: account id, email, or display name
+        #[arg(long)]
+        user: Option<String>,
     },
-    /// Stop watching an issue (removes current user)
+    /// Stop watching an issue (removes current user, or --user)
     Remove {
         /// Issue key
         issue_key: String,
+        /// Someone else to remove: account id, email, or display name
+        #[arg(long)]
+        user: Option<String>,
     },
     /// List watchers on an issue
     List {
@@ -411,6 +758,157 @@ enum ListAction {
     Labels,
 }
 
+#[derive(Subcommand)]
+enum FilterAction {
+    /// List the saved filters you can see
+    List {
+        /// Only filters whose name contains this
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Get a filter, with its JQL and share permissions, by id or name
+    Get { filter: String },
+    /// Save a JQL query as a filter
+    Create {
+        name: String,
+        jql: String,
+        #[arg(long)]
+        description: Option<String>,
+        /// Also add it to your favourite filters
+        #[arg(long)]
+        favourite: bool,
+    },
+    /// Change a filter's name, JQL, or description
+    Update {
+        /// Filter id or name
+        filter: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        jql: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+    },
+    /// Delete a filter (irreversible — requires --yes)
+    Delete {
+        /// Filter id or name
+        filter: String,
+        /// Confirm the irreversible deletion
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProjectAction {
+    /// List the projects you can browse
+    List {
+        /// Only projects whose key or name contains this
+        #[arg(long)]
+        query: Option<String>,
+    },
+    /// Get a single project by key or id
+    Get { project: String },
+}
+
+#[derive(Subcommand)]
+enum ComponentAction {
+    /// List a project's components
+    List { project: String },
+    /// Create a component in a project
+    Create {
+        /// Project key
+        project: String,
+        name: String,
+        #[arg(long)]
+        description: Option<String>,
+        /// Component lead: account id, email, display name, or me
+        #[arg(long)]
+        lead: Option<String>,
+    },
+    /// Update a component's name, description, or lead
+    Update {
+        component_id: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        /// New lead (account id, email, display name, or me), or none to clear it
+        #[arg(long)]
+        lead: Option<String>,
+    },
+    /// Delete a component (irreversible — requires --yes)
+    Delete {
+        component_id: String,
+        /// Component to move the deleted one's issues to
+        #[arg(long = "move-issues-to")]
+        move_issues_to: Option<String>,
+        /// Confirm the irreversible deletion
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum VersionAction {
+    /// List a project's versions
+    List {
+        project: String,
+        /// Comma list of released, unreleased, archived
+        #[arg(long)]
+        status: Option<String>,
+    },
+    /// Create a version in a project
+    Create {
+        /// Project key or id
+        project: String,
+        name: String,
+        #[arg(long)]
+        description: Option<String>,
+        /// YYYY-MM-DD
+        #[arg(long = "start-date")]
+        start_date: Option<String>,
+        /// YYYY-MM-DD
+        #[arg(long = "release-date")]
+        release_date: Option<String>,
+    },
+    /// Update a version's name, description, or dates
+    Update {
+        version_id: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        /// YYYY-MM-DD
+        #[arg(long = "start-date")]
+        start_date: Option<String>,
+        /// YYYY-MM-DD
+        #[arg(long = "release-date")]
+        release_date: Option<String>,
+    },
+    /// Mark a version released
+    Release {
+        version_id: String,
+        /// Release date, YYYY-MM-DD (default: the version's own release date)
+        #[arg(long = "release-date")]
+        release_date: Option<String>,
+        /// Version to move the unresolved issues to
+        #[arg(long = "move-unresolved-to")]
+        move_unresolved_to: Option<String>,
+    },
+    /// Archive a version
+    Archive { version_id: String },
+    /// Move a version's issues to another and delete it (requires --yes)
+    Merge {
+        version_id: String,
+        /// Version that receives the issues
+        into: String,
+        /// Confirm the deletion of VERSION_ID
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum BoardAction {
     /// List boards for a project
@@ -449,6 +947,70 @@ enum SprintAction {
         #[arg(required = true)]
         issues: Vec<String>,
     },
+    /// Create a future sprint on a board
+    Create {
+        name: String,
+        /// Board ID
+        #[arg(long, group = "board_source")]
+        board: Option<u64>,
+        /// Project key (auto-resolves board)
+        #[arg(long, group = "board_source")]
+        project: Option<String>,
+        /// Sprint goal
+        #[arg(long)]
+        goal: Option<String>,
+        /// ISO 8601, e.g. 2024-06-03T09:00:00.000+09:00
+        #[arg(long = "start-date")]
+        start_date: Option<String>,
+        /// ISO 8601, e.g. 2024-06-17T18:00:00.000+09:00
+        #[arg(long = "end-date")]
+        end_date: Option<String>,
+    },
+    /// Start a future sprint (needs both dates, given here or at create)
+    Start {
+        sprint_id: u64,
+        #[arg(long)]
+        goal: Option<String>,
+        /// ISO 8601, e.g. 2024-06-03T09:00:00.000+09:00
+        #[arg(long = "start-date")]
+        start_date: Option<String>,
+        /// ISO 8601, e.g. 2024-06-17T18:00:00.000+09:00
+        #[arg(long = "end-date")]
+        end_date: Option<String>,
+    },
+    /// Change a sprint's name, goal, or dates
+    Update {
+        sprint_id: u64,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        goal: Option<String>,
+        /// ISO 8601, e.g. 2024-06-03T09:00:00.000+09:00
+        #[arg(long = "start-date")]
+        start_date: Option<String>,
+        /// ISO 8601, e.g. 2024-06-17T18:00:00.000+09:00
+        #[arg(long = "end-date")]
+        end_date: Option<String>,
+    },
+    /// Complete an active sprint
+    Complete {
+        sprint_id: u64,
+        /// Move the unfinished issues first: to a sprint id, or to backlog
+        #[arg(long = "move-open-to", value_name = "SPRINT_ID|backlog", value_parser = parse_carry_over)]
+        move_open_to: Option<atlassian_cli::jira::CarryOver>,
+    },
+}
+
+/// `--move-open-to`: a sprint id, or `backlog`.
+fn parse_carry_over(value: &str) -> Result<atlassian_cli::jira::CarryOver, String> {
+    use atlassian_cli::jira::CarryOver;
+    if value.eq_ignore_ascii_case("backlog") {
+        return Ok(CarryOver::Backlog);
+    }
+    value
+        .parse()
+        .map(CarryOver::Sprint)
+        .map_err(|_| format!("expected a sprint id or 'backlog', got '{value}'"))
 }
 
 #[derive(Subcommand)]
@@ -469,6 +1031,15 @@ enum EpicAction {
     },
 }
 
+#[derive(Subcommand)]
+enum AdfAction {
+    /// Check a JSON file against the ADF schema, listing every violation by JSON pointer
+    Validate {
+        /// Path to the ADF document
+        file: PathBuf,
+    },
+}
+
 #[derive(Parser)]
 struct ConfluenceCommand {
     #[command(subcommand)]
@@ -488,7 +1059,10 @@ enum ConfluenceSubcommand {
         limit: u32,
         #[arg(long, help = "Fetch all results via cursor pagination")]
         all: bool,
-        #[arg(long, help = "Stream as JSONL (requires --all)")]
+        #[arg(
+            long,
+            help = "Write each page as it arrives: JSONL, or rows with --output csv/tsv (requires --all)"
+        )]
         stream: bool,
         #[arg(
             long,
@@ -503,9 +1077,15 @@ enum ConfluenceSubcommand {
     Get {
         page_id: String,
         #[arg(long, value_enum, default_value = "html", help = "Body content format")]
-        format: OutputFormat,
+        format: PageFormat,
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Link attached images and files as DIR/<filename> instead of their URLs (requires --format markdown)"
+        )]
+        media_dir: Option<PathBuf>,
     },
-    /// Create a page from storage-format HTML content
+    /// Create a page from storage-format HTML (or Markdown, or ADF) content
     Create {
         space: String,
         title: String,
@@ -513,8 +1093,15 @@ enum ConfluenceSubcommand {
         /// Parent page id to nest under (omit to create at the space root)
         #[arg(long)]
         parent: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "storage",
+            help = "How the content is read"
+        )]
+        body_format: PageBodyFormat,
     },
-    /// Update a page's title and storage-format HTML content
+    /// Update a page's title and storage-format HTML (or Markdown, or ADF) content
     Update {
         page_id: String,
         title: String,
@@ -522,6 +1109,13 @@ enum ConfluenceSubcommand {
         /// Parent page id to re-parent under (omit to keep the current parent)
         #[arg(long)]
         parent: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "storage",
+            help = "How the content is read"
+        )]
+        body_format: PageBodyFormat,
     },
     /// List the direct child pages of a page (metadata only)
     Children { page_id: String },
@@ -545,7 +1139,7 @@ enum ConfluenceSubcommand {
         #[command(subcommand)]
         action: ConfluenceSpaceAction,
     },
-    /// List attachments on a page, or upload a file
+    /// List, upload, download, or delete the files attached to a page
     Attachment {
         #[command(subcommand)]
         action: ConfluenceAttachmentAction,
@@ -627,12 +1221,26 @@ enum ConfluenceCommentAction {
         /// Reply to an existing comment instead of posting a top-level one
         #[arg(long = "reply-to")]
         reply_to: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "storage",
+            help = "How the body is read"
+        )]
+        body_format: BodyFormat,
     },
     /// Update a footer comment's body
     Update {
         comment_id: String,
         /// New comment body (storage-format HTML)
         body: String,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "storage",
+            help = "How the body is read"
+        )]
+        body_format: BodyFormat,
     },
     /// Delete a footer comment by id
     Delete { comment_id: String },
@@ -690,6 +1298,27 @@ enum ConfluenceAttachmentAction {
         #[arg(long = "content-type")]
         content_type: Option<String>,
     },
+    /// Save an attachment by id, or with --all every attachment on a page
+    Download {
+        /// Attachment id; with --all, the page id
+        target: String,
+        /// Save every attachment on the page named by TARGET
+        #[arg(long)]
+        all: bool,
+        /// Directory to save into, under the attachment titles (default: current directory)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// File to save a single attachment to, or - for stdout
+        #[arg(long, conflicts_with_all = ["all", "dir"])]
+        to: Option<PathBuf>,
+    },
+    /// Move an attachment to the trash (recoverable — requires --yes)
+    Delete {
+        attachment_id: String,
+        /// Confirm the deletion
+        #[arg(long)]
+        yes: bool,
+    },
 }
 
 #[derive(Parser)]
@@ -764,45 +1393,198 @@ async fn main() {
         .with_writer(std::io::stderr)
         .init();
 
-    if let Err(err) = run(cli).await {
-        let (code, payload) = render_error(&err);
-        eprintln!("{payload}");
-        std::process::exit(code);
+    if let Err(err) = run(cli).await {
+        let (code, payload) = render_error(&err);
+        eprintln!("{payload}");
+        std::process::exit(code);
+    }
+}
+
+/// What `--output` and `--columns` asked for, settled once for the whole run.
+struct Printing {
+    output: Output,
+    columns: Option<atlassian_cli::output::Columns>,
+    select: Option<atlassian_cli::select::Selection>,
+}
+
+impl Printing {
+    fn new(cli: &Cli) -> Result<Self> {
+        use atlassian_cli::output::Columns;
+
+        let columns = match (cli.output, &cli.columns) {
+            (Output::Csv | Output::Tsv | Output::Table, Some(columns)) => {
+                Some(Columns::new(columns)?)
+            }
+            (Output::Csv | Output::Tsv, None) => anyhow::bail!(
+                "--output csv/tsv needs --columns, e.g. --columns key,/fields/status/name"
+            ),
+            (Output::Json | Output::Md, Some(_)) => {
+                anyhow::bail!("--columns applies to --output csv, tsv or table")
+            }
+            (_, None) => None,
+        };
+
+        // Only the commands that print through here can honour a format
+        // other than JSON; the rest would take the flag and ignore it.
+        let document = matches!(
+            cli.command,
+            Command::Jira(JiraCommand {
+                subcommand: JiraSubcommand::Get { .. }
+            }) | Command::Confluence(ConfluenceCommand {
+                subcommand: ConfluenceSubcommand::Get { .. }
+            })
+        );
+        let printed = matches!(cli.command, Command::Selfcmd(_))
+            || matches!(&cli.command, Command::Jira(cmd) if !matches!(cmd.subcommand, JiraSubcommand::Adf { .. }))
+            || matches!(cli.command, Command::Confluence(_));
+        match cli.output {
+            Output::Md if !document => {
+                anyhow::bail!("--output md applies to `jira get` and `confluence get`")
+            }
+            Output::Csv | Output::Tsv | Output::Table if !printed => anyhow::bail!(
+                "--output csv/tsv/table applies to jira, confluence and self commands"
+            ),
+            _ => {}
+        }
+
+        let select = match &cli.select {
+            Some(_) if cli.output == Output::Md => {
+                anyhow::bail!("--select applies to JSON, not to a --output md document")
+            }
+            Some(_) if !printed => {
+                anyhow::bail!("--select applies to jira, confluence and self commands")
+            }
+            Some(paths) => Some(atlassian_cli::select::Selection::new(paths)?),
+            None => None,
+        };
+
+        Ok(Self {
+            output: cli.output,
+            columns,
+            select,
+        })
+    }
+
+    /// Where an `--all --stream` walk writes its pages. A table is refused:
+    /// its widths come from every row, so none can be written before the last
+    /// page is in. A stream's lines are items, so `--select` reaches them
+    /// through its `items[]` paths, and one with none would write nothing.
+    fn stream(&self) -> Result<atlassian_cli::output::Stream> {
+        use atlassian_cli::output::{Delimiter, Rows, Stream};
+
+        let delimiter = match self.output {
+            Output::Csv => Some(Delimiter::Csv),
+            Output::Tsv => Some(Delimiter::Tsv),
+            Output::Table => anyhow::bail!(
+                "--output table aligns columns over the whole result; drop --stream, or stream csv/tsv"
+            ),
+            Output::Json | Output::Md => None,
+        };
+        let stream = match delimiter {
+            Some(delimiter) => {
+                let columns = self.columns.clone().expect("checked in Printing::new");
+                Stream::rows(Rows::new(delimiter, columns))
+            }
+            None => Stream::jsonl(),
+        };
+        match &self.select {
+            None => Ok(stream),
+            Some(selection) => match selection.within_items() {
+                Some(within) => Ok(stream.select(within)),
+                None => anyhow::bail!(
+                    "--stream writes one item per line; give --select paths under items[], e.g. items[].key"
+                ),
+            },
+        }
+    }
+
+    /// Rows are one per item of an `items` envelope (or a bare list); any
+    /// other answer is a single object and becomes a single row, so a `get`
+    /// or a write prints in the same columns as the list it came from.
+    fn print(&self, value: &serde_json::Value, pretty: bool) -> Result<()> {
+        use atlassian_cli::output::{Columns, Delimiter, Rows, Table};
+
+        let selected;
+        let value = match &self.select {
+            Some(selection) if !value.is_null() => {
+                selected = selection.apply(value);
+                &selected
+            }
+            _ => value,
+        };
+        if value.is_null() || matches!(self.output, Output::Json | Output::Md) {
+            output_json(value, pretty);
+            return Ok(());
+        }
+        let items = match value.get("items").unwrap_or(value) {
+            serde_json::Value::Array(items) => items.as_slice(),
+            _ => std::slice::from_ref(value),
+        };
+        let columns = self
+            .columns
+            .clone()
+            .unwrap_or_else(|| Columns::defaults_for(items));
+        let text = match self.output {
+            Output::Csv => Rows::new(Delimiter::Csv, columns).document(items),
+            Output::Tsv => Rows::new(Delimiter::Tsv, columns).document(items),
+            _ => Table::new(columns, terminal_width()).render(items),
+        };
+        print!("{text}");
+        Ok(())
+    }
+}
+
+/// The width a table is fitted to: `COLUMNS` when set, else the terminal's.
+/// Output that goes to a pipe or a file is not cut at all — nothing there
+/// wraps it, and a truncated cell is a value lost.
+fn terminal_width() -> Option<usize> {
+    if let Some(columns) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse::<usize>().ok())
+        .filter(|&c| c > 0)
+    {
+        return Some(columns);
     }
+    terminal_size::terminal_size().map(|(terminal_size::Width(w), _)| usize::from(w))
 }
 
 async fn run(cli: Cli) -> Result<()> {
     let overrides = cli.to_overrides();
     let config_path = cli.config.clone();
     let profile = cli.profile.clone();
+    // Checked before anything runs: a write whose answer cannot be printed
+    // the way it was asked for should not be made.
+    let printing = Printing::new(&cli)?;
 
     match cli.command {
         Command::Config(cmd) => {
             handle_config(cmd, config_path.as_ref(), profile.as_ref(), overrides).await
         }
         Command::Auth(cmd) => handle_auth(cmd, config_path, profile, overrides).await,
+        // Before the client: checking a local file needs no profile, and a
+        // machine without credentials should still be able to run it.
+        Command::Jira(JiraCommand {
+            subcommand: JiraSubcommand::Adf { action },
+        }) => handle_adf(action, cli.pretty),
         Command::Jira(cmd) => {
             let config =
                 atlassian_cli::Config::load(config_path.as_ref(), profile.as_ref(), overrides)?;
 
             let client = atlassian_cli::ApiClient::new(config).await?;
-            let result = handle_jira(cmd, &client).await?;
-            output_json(&result, cli.pretty);
-            Ok(())
+            let result = handle_jira(cmd, &printing, &client).await?;
+            printing.print(&result, cli.pretty)
         }
         Command::Confluence(cmd) => {
             let config =
                 atlassian_cli::Config::load(config_path.as_ref(), profile.as_ref(), overrides)?;
 
             let client = atlassian_cli::ApiClient::new(config).await?;
-            let result = handle_confluence(cmd, &client).await?;
-            output_json(&result, cli.pretty);
-            Ok(())
+            let result = handle_confluence(cmd, &printing, &client).await?;
+            printing.print(&result, cli.pretty)
         }
         Command::Selfcmd(cmd) => {
             let result = handle_self(cmd).await?;
-            output_json(&result, cli.pretty);
-            Ok(())
+            printing.print(&result, cli.pretty)
         }
         Command::Completions { shell } => {
             use clap::CommandFactory;
@@ -1576,6 +2358,7 @@ async fn handle_config(
 
 async fn handle_jira(
     cmd: JiraCommand,
+    printing: &Printing,
     client: &atlassian_cli::ApiClient,
 ) -> Result<serde_json::Value> {
     use atlassian_cli::jira;
@@ -1585,38 +2368,148 @@ async fn handle_jira(
             issue_key,
             fields,
             format,
+            lossless,
+            media_dir,
+            field_names,
+            as_of,
         } => {
-            let as_markdown = matches!(format, OutputFormat::Markdown);
-            jira::get_issue(&issue_key, fields, as_markdown, client).await
+            let document = printing.output == Output::Md;
+            if let Some(as_of) = as_of {
+                // The changelog keeps text, not documents, so a rewound
+                // description is not something Markdown could render.
+                if document
+                    || matches!(format, OutputFormat::Markdown)
+                    || lossless
+                    || media_dir.is_some()
+                {
+                    anyhow::bail!(
+                        "--as-of shows fields as JSON; it does not combine with --output md, \
+                         --format markdown, --lossless or --media-dir"
+                    );
+                }
+                return jira::as_of::get_issue_as_of(
+                    &issue_key,
+                    fields,
+                    &as_of,
+                    field_names,
+                    client,
+                )
+                .await;
+            }
+            // A document's body is Markdown whatever `--format` says.
+            let format = if document {
+                OutputFormat::Markdown
+            } else {
+                format
+            };
+            let markdown = markdown_mode(format, lossless)?;
+            if media_dir.is_some()
+                && markdown != Some(atlassian_cli::markdown::MarkdownMode::Readable)
+            {
+                anyhow::bail!("--media-dir requires --format markdown without --lossless");
+            }
+            match (document, markdown) {
+                (true, Some(mode)) => {
+                    if fields.is_some() || field_names {
+                        anyhow::bail!("--fields and --field-names do not apply to --output md");
+                    }
+                    let document =
+                        jira::get_issue_document(&issue_key, mode, media_dir.as_deref(), client)
+                            .await?;
+                    print_document(&document)
+                }
+                _ => {
+                    jira::get_issue(
+                        &issue_key,
+                        fields,
+                        markdown,
+                        media_dir.as_deref(),
+                        field_names,
+                        client,
+                    )
+                    .await
+                }
+            }
         }
         JiraSubcommand::Search {
             jql,
+            filter,
             limit,
             all,
             stream,
             fields,
             format,
+            field_names,
         } => {
             if stream && !all {
                 anyhow::bail!("--stream requires --all flag");
             }
+            let jql = match (jql, filter) {
+                (Some(jql), _) => jql,
+                (None, Some(filter)) => jira::filters::filter_jql(&filter, client).await?,
+                (None, None) => anyhow::bail!("jira search needs a JQL query, or --filter"),
+            };
             let as_markdown = matches!(format, OutputFormat::Markdown);
             if all {
-                jira::search_all(&jql, fields, stream, as_markdown, client).await
+                let mut sink = if stream {
+                    Some(printing.stream()?)
+                } else {
+                    None
+                };
+                jira::search_all(
+                    &jql,
+                    fields,
+                    sink.as_mut(),
+                    as_markdown,
+                    field_names,
+                    client,
+                )
+                .await
             } else {
-                jira::search(&jql, limit, fields, as_markdown, client).await
+                jira::search(&jql, limit, fields, as_markdown, field_names, client).await
+            }
+        }
+        JiraSubcommand::Create {
+            from: Some(file),
+            map,
+            input_format,
+            ..
+        } => {
+            let rename = map
+                .iter()
+                .map(|pair| match pair.split_once('=') {
+                    Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => {
+                        Ok((from.trim().to_string(), to.trim().to_string()))
+                    }
+                    _ => Err(anyhow::anyhow!(
+                        "--map takes FROM=TO pairs, e.g. Title=summary — got '{pair}'"
+                    )),
+                })
+                .collect::<Result<_>>()?;
+            let mut specs = jira::bulk::read_issue_specs(&file, &rename)?;
+            for spec in &mut specs {
+                if let serde_json::Value::String(text) = &spec.description {
+                    spec.description = jira_body(text.clone(), input_format);
+                }
             }
+            jira::bulk::create_issues(specs, client).await
         }
         JiraSubcommand::Create {
             project,
             summary,
             issue_type,
             description,
+            input_format,
             parent,
             fields,
+            ..
         } => {
+            let (Some(project), Some(summary), Some(issue_type)) = (project, summary, issue_type)
+            else {
+                anyhow::bail!("jira create needs PROJECT SUMMARY ISSUE_TYPE, or --from <FILE>");
+            };
             let desc = description
-                .map(serde_json::Value::String)
+                .map(|text| jira_body(text, input_format))
                 .unwrap_or(serde_json::Value::Null);
             let extra = fields
                 .map(|raw| {
@@ -1639,13 +2532,23 @@ async fn handle_jira(
             )
             .await
         }
-        JiraSubcommand::Update { issue_key, fields } => {
-            let fields_value: serde_json::Value = serde_json::from_str(&fields).map_err(|e| {
-                anyhow::anyhow!(
-                    "Invalid JSON for update fields: {}. Example: {{\"summary\":\"New title\"}}",
-                    e
-                )
-            })?;
+        JiraSubcommand::Update {
+            issue_key,
+            fields,
+            input_format,
+        } => {
+            let mut fields_value: serde_json::Value =
+                serde_json::from_str(&fields).map_err(|e| {
+                    anyhow::anyhow!(
+                        "Invalid JSON for update fields: {}. Example: {{\"summary\":\"New title\"}}",
+                        e
+                    )
+                })?;
+            if let Some(description) = fields_value.get_mut("description")
+                && let serde_json::Value::String(text) = description
+            {
+                *description = jira_body(std::mem::take(text), input_format);
+            }
             jira::update_issue(&issue_key, fields_value, client).await
         }
         JiraSubcommand::Delete {
@@ -1661,26 +2564,48 @@ async fn handle_jira(
             }
             jira::delete_issue(&issue_key, delete_subtasks, client).await
         }
-        JiraSubcommand::Comment { action } => match action {
-            CommentAction::Add { issue_key, text } => {
-                jira::add_comment(&issue_key, serde_json::Value::String(text), client).await
+        JiraSubcommand::Bulk { action } => match action {
+            BulkAction::Edit {
+                jql,
+                set,
+                set_json,
+                add,
+                remove,
+                dry_run,
+                max,
+                ..
+            } => {
+                let edit = jira::bulk::Edit::new(&set, &set_json, &add, &remove)?;
+                jira::bulk::edit_issues(&jql, &edit, max, dry_run, client).await
             }
+        },
+        JiraSubcommand::Comment { action } => match action {
+            CommentAction::Add {
+                issue_key,
+                text,
+                input_format,
+            } => jira::add_comment(&issue_key, jira_body(text, input_format), client).await,
             CommentAction::Update {
                 issue_key,
                 comment_id,
                 text,
+                input_format,
             } => {
                 jira::update_comment(
                     &issue_key,
                     &comment_id,
-                    serde_json::Value::String(text),
+                    jira_body(text, input_format),
                     client,
                 )
                 .await
             }
-            CommentAction::List { issue_key, format } => {
-                let as_markdown = matches!(format, OutputFormat::Markdown);
-                jira::get_comments(&issue_key, as_markdown, client).await
+            CommentAction::List {
+                issue_key,
+                format,
+                lossless,
+            } => {
+                let markdown = markdown_mode(format, lossless)?;
+                jira::get_comments(&issue_key, markdown, client).await
             }
             CommentAction::Delete {
                 issue_key,
@@ -1690,8 +2615,35 @@ async fn handle_jira(
         JiraSubcommand::Transition { action } => match action {
             TransitionAction::Apply {
                 issue_key,
-                transition_id,
-            } => jira::transition_issue(&issue_key, &transition_id, client).await,
+                transition,
+                resolution,
+                comment,
+                input_format,
+                fields,
+            } => {
+                let fields = fields
+                    .map(|raw| {
+                        serde_json::from_str::<serde_json::Value>(&raw).map_err(|e| {
+                            anyhow::anyhow!(
+                                "Invalid JSON for --fields: {}. Example: {{\"resolution\":{{\"name\":\"Done\"}}}}",
+                                e
+                            )
+                        })
+                    })
+                    .transpose()?;
+                let comment = comment
+                    .map(|text| jira_body(text, input_format))
+                    .unwrap_or(serde_json::Value::Null);
+                jira::transition_issue(
+                    &issue_key,
+                    &transition,
+                    resolution.as_deref(),
+                    fields,
+                    comment,
+                    client,
+                )
+                .await
+            }
             TransitionAction::List { issue_key } => jira::get_transitions(&issue_key, client).await,
         },
         JiraSubcommand::Link { action } => match action {
@@ -1765,9 +2717,90 @@ async fn handle_jira(
                 worklog_id,
             } => jira::remove_worklog(&issue_key, &worklog_id, client).await,
         },
+        JiraSubcommand::History {
+            issue_key,
+            jql,
+            fields,
+            since,
+            until,
+            format,
+            stream,
+        } => {
+            let history = jira::history::HistoryFilter::new(fields, since, until)?;
+            let as_markdown = matches!(format, OutputFormat::Markdown);
+            match (issue_key, jql) {
+                (_, Some(jql)) => {
+                    let mut sink = if stream {
+                        Some(printing.stream()?)
+                    } else {
+                        None
+                    };
+                    jira::history::history_walk(&jql, &history, as_markdown, sink.as_mut(), client)
+                        .await
+                }
+                (Some(issue_key), None) => {
+                    jira::history::issue_history(&issue_key, &history, as_markdown, client).await
+                }
+                (None, None) => anyhow::bail!("jira history needs an issue key or --jql"),
+            }
+        }
+        JiraSubcommand::Assign { issue_key, user } => {
+            jira::assign_issue(&issue_key, &user, client).await
+        }
+        JiraSubcommand::User { action } => match action {
+            UserAction::Search { query, limit } => {
+                jira::users::search_users(&query, limit, client).await
+            }
+            UserAction::Get { user } => jira::users::get_user(&user, client).await,
+        },
+        JiraSubcommand::Attachment { action } => match action {
+            JiraAttachmentAction::List { issue_key } => {
+                jira::attachments::list_attachments(&issue_key, client).await
+            }
+            JiraAttachmentAction::Upload {
+                issue_key,
+                files,
+                content_type,
+            } => {
+                jira::attachments::upload_attachments(
+                    &issue_key,
+                    &files,
+                    content_type.as_deref(),
+                    client,
+                )
+                .await
+            }
+            JiraAttachmentAction::Download {
+                target,
+                all,
+                dir,
+                to,
+            } => {
+                let dir = dir.unwrap_or_else(|| PathBuf::from("."));
+                if all {
+                    jira::attachments::download_all(&target, &dir, client).await
+                } else {
+                    let destination = download_destination(to.as_deref(), &dir);
+                    jira::attachments::download_attachment(&target, destination, client).await
+                }
+            }
+            JiraAttachmentAction::Delete { attachment_id, yes } => {
+                if !yes {
+                    anyhow::bail!(
+                        "Deleting attachment {} is irreversible. Re-run with --yes to confirm.",
+                        attachment_id
+                    );
+                }
+                jira::attachments::delete_attachment(&attachment_id, client).await
+            }
+        },
         JiraSubcommand::Watcher { action } => match action {
-            WatcherAction::Add { issue_key } => jira::add_watcher(&issue_key, client).await,
-            WatcherAction::Remove { issue_key } => jira::remove_watcher(&issue_key, client).await,
+            WatcherAction::Add { issue_key, user } => {
+                jira::add_watcher(&issue_key, user.as_deref(), client).await
+            }
+            WatcherAction::Remove { issue_key, user } => {
+                jira::remove_watcher(&issue_key, user.as_deref(), client).await
+            }
             WatcherAction::List { issue_key } => jira::get_watchers(&issue_key, client).await,
         },
         JiraSubcommand::List { action } => match action {
@@ -1776,6 +2809,162 @@ async fn handle_jira(
             ListAction::Statuses => jira::get_statuses(client).await,
             ListAction::Labels => jira::get_labels(client).await,
         },
+        JiraSubcommand::Filter { action } => match action {
+            FilterAction::List { name } => {
+                jira::filters::list_filters(name.as_deref(), client).await
+            }
+            FilterAction::Get { filter } => jira::filters::get_filter(&filter, client).await,
+            FilterAction::Create {
+                name,
+                jql,
+                description,
+                favourite,
+            } => {
+                jira::filters::create_filter(&name, &jql, description.as_deref(), favourite, client)
+                    .await
+            }
+            FilterAction::Update {
+                filter,
+                name,
+                jql,
+                description,
+            } => {
+                jira::filters::update_filter(
+                    &filter,
+                    name.as_deref(),
+                    jql.as_deref(),
+                    description.as_deref(),
+                    client,
+                )
+                .await
+            }
+            FilterAction::Delete { filter, yes } => {
+                if !yes {
+                    anyhow::bail!(
+                        "Deleting filter {} is irreversible. Re-run with --yes to confirm.",
+                        filter
+                    );
+                }
+                jira::filters::delete_filter(&filter, client).await
+            }
+        },
+        JiraSubcommand::Project { action } => match action {
+            ProjectAction::List { query } => {
+                jira::projects::list_projects(query.as_deref(), client).await
+            }
+            ProjectAction::Get { project } => jira::projects::get_project(&project, client).await,
+        },
+        JiraSubcommand::Component { action } => match action {
+            ComponentAction::List { project } => {
+                jira::projects::list_components(&project, client).await
+            }
+            ComponentAction::Create {
+                project,
+                name,
+                description,
+                lead,
+            } => {
+                let fields = jira::projects::ComponentFields {
+                    name: Some(name),
+                    description,
+                    lead,
+                };
+                jira::projects::create_component(&project, &fields, client).await
+            }
+            ComponentAction::Update {
+                component_id,
+                name,
+                description,
+                lead,
+            } => {
+                let fields = jira::projects::ComponentFields {
+                    name,
+                    description,
+                    lead,
+                };
+                jira::projects::update_component(&component_id, &fields, client).await
+            }
+            ComponentAction::Delete {
+                component_id,
+                move_issues_to,
+                yes,
+            } => {
+                if !yes {
+                    anyhow::bail!(
+                        "Deleting component {} is irreversible. Re-run with --yes to confirm.",
+                        component_id
+                    );
+                }
+                jira::projects::delete_component(&component_id, move_issues_to.as_deref(), client)
+                    .await
+            }
+        },
+        JiraSubcommand::Version { action } => match action {
+            VersionAction::List { project, status } => {
+                jira::projects::list_versions(&project, status.as_deref(), client).await
+            }
+            VersionAction::Create {
+                project,
+                name,
+                description,
+                start_date,
+                release_date,
+            } => {
+                let fields = jira::projects::VersionFields {
+                    name: Some(name),
+                    description,
+                    start_date,
+                    release_date,
+                };
+                jira::projects::create_version(&project, &fields, client).await
+            }
+            VersionAction::Update {
+                version_id,
+                name,
+                description,
+                start_date,
+                release_date,
+            } => {
+                let fields = jira::projects::VersionFields {
+                    name,
+                    description,
+                    start_date,
+                    release_date,
+                };
+                jira::projects::update_version(&version_id, &fields, client).await
+            }
+            VersionAction::Release {
+                version_id,
+                release_date,
+                move_unresolved_to,
+            } => {
+                jira::projects::release_version(
+                    &version_id,
+                    release_date.as_deref(),
+                    move_unresolved_to.as_deref(),
+                    client,
+                )
+                .await
+            }
+            VersionAction::Archive { version_id } => {
+                jira::projects::archive_version(&version_id, client).await
+            }
+            VersionAction::Merge {
+                version_id,
+                into,
+                yes,
+            } => {
+                if !yes {
+                    anyhow::bail!(
+                        "Merging version {} into {} deletes {}. Re-run with --yes to confirm.",
+                        version_id,
+                        into,
+                        version_id
+                    );
+                }
+                jira::projects::merge_version(&version_id, &into, client).await
+            }
+        },
         JiraSubcommand::Board { action } => match action {
             BoardAction::List { project } => jira::get_boards(&project, client).await,
         },
@@ -1785,17 +2974,59 @@ async fn handle_jira(
                 project,
                 state,
             } => {
-                let board_id = match board {
-                    Some(id) => id,
-                    None => {
-                        let project_key = project.ok_or_else(|| {
-                            anyhow::anyhow!("Either --board or --project is required")
-                        })?;
-                        jira::resolve_board_id(&project_key, client).await?
-                    }
-                };
+                let board_id = board_of(board, project, client).await?;
                 jira::get_sprints(board_id, &state, client).await
             }
+            SprintAction::Create {
+                name,
+                board,
+                project,
+                goal,
+                start_date,
+                end_date,
+            } => {
+                let board_id = board_of(board, project, client).await?;
+                let fields = jira::SprintFields {
+                    name: Some(name),
+                    goal,
+                    start_date,
+                    end_date,
+                };
+                jira::create_sprint(board_id, &fields, client).await
+            }
+            SprintAction::Start {
+                sprint_id,
+                goal,
+                start_date,
+                end_date,
+            } => {
+                let fields = jira::SprintFields {
+                    goal,
+                    start_date,
+                    end_date,
+                    ..Default::default()
+                };
+                jira::start_sprint(sprint_id, &fields, client).await
+            }
+            SprintAction::Update {
+                sprint_id,
+                name,
+                goal,
+                start_date,
+                end_date,
+            } => {
+                let fields = jira::SprintFields {
+                    name,
+                    goal,
+                    start_date,
+                    end_date,
+                };
+                jira::update_sprint(sprint_id, &fields, client).await
+            }
+            SprintAction::Complete {
+                sprint_id,
+                move_open_to,
+            } => jira::complete_sprint(sprint_id, move_open_to, client).await,
             SprintAction::Move { sprint_id, issues } => {
                 jira::move_issues_to_sprint(sprint_id, &issues, client).await
             }
@@ -1809,11 +3040,101 @@ async fn handle_jira(
                 jira::unassign_issues_from_epic(&issues, client).await
             }
         },
+        JiraSubcommand::Adf { .. } => {
+            unreachable!("`jira adf` is handled before a client is built")
+        }
+    }
+}
+
+/// The report goes to stdout either way, so a caller reads every violation
+/// from one place; a document with any is still a failed run, so a script can
+/// gate on the exit code alone.
+fn handle_adf(action: AdfAction, pretty: bool) -> Result<()> {
+    use anyhow::Context;
+
+    match action {
+        AdfAction::Validate { file } => {
+            let raw = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let document: serde_json::Value = serde_json::from_str(&raw)
+                .with_context(|| format!("{} is not JSON", file.display()))?;
+            let violations = atlassian_cli::jira::adf::check_schema(&document);
+            let report = serde_json::json!({
+                "valid": violations.is_empty(),
+                "violations": violations
+                    .iter()
+                    .map(|v| serde_json::json!({ "path": v.path, "message": v.message }))
+                    .collect::<Vec<_>>(),
+            });
+            output_json(&report, pretty);
+            if !violations.is_empty() {
+                anyhow::bail!(
+                    "{} is not valid ADF: {} violation(s)",
+                    file.display(),
+                    violations.len()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// A Jira body argument as the value `jira::adf::process_adf_input` takes. Text
+/// stays a string so that function keeps deciding between literal prose and a
+/// pasted ADF document; Markdown is converted here, already as ADF.
+fn jira_body(text: String, format: InputFormat) -> serde_json::Value {
+    match format {
+        InputFormat::Text => serde_json::Value::String(text),
+        InputFormat::Markdown => atlassian_cli::markdown::markdown_to_adf(&text),
+    }
+}
+
+/// How a Jira read renders its ADF bodies. `--lossless` only changes what the
+/// Markdown looks like, so it is refused without `--format markdown` rather
+/// than silently ignored.
+fn markdown_mode(
+    format: OutputFormat,
+    lossless: bool,
+) -> Result<Option<atlassian_cli::markdown::MarkdownMode>> {
+    use atlassian_cli::markdown::MarkdownMode;
+
+    match (format, lossless) {
+        (OutputFormat::Markdown, false) => Ok(Some(MarkdownMode::Readable)),
+        (OutputFormat::Markdown, true) => Ok(Some(MarkdownMode::Lossless)),
+        (OutputFormat::Html, false) => Ok(None),
+        (OutputFormat::Html, true) => anyhow::bail!("--lossless requires --format markdown"),
+    }
+}
+
+/// A page body argument as the string the write sends: Markdown converted to
+/// storage, storage and ADF as given. `page_body` then says which it is.
+fn page_content(content: String, format: PageBodyFormat) -> String {
+    match format {
+        PageBodyFormat::Markdown => atlassian_cli::markdown::markdown_to_storage(&content),
+        PageBodyFormat::Storage | PageBodyFormat::Adf => content,
+    }
+}
+
+fn page_body(content: &str, format: PageBodyFormat) -> atlassian_cli::confluence::PageBody<'_> {
+    use atlassian_cli::confluence::PageBody;
+
+    match format {
+        PageBodyFormat::Storage | PageBodyFormat::Markdown => PageBody::Storage(content),
+        PageBodyFormat::Adf => PageBody::Adf(content),
+    }
+}
+
+/// A Confluence body argument as the storage-format string the API sends.
+fn confluence_body(content: String, format: BodyFormat) -> String {
+    match format {
+        BodyFormat::Storage => content,
+        BodyFormat::Markdown => atlassian_cli::markdown::markdown_to_storage(&content),
     }
 }
 
 async fn handle_confluence(
     cmd: ConfluenceCommand,
+    printing: &Printing,
     client: &atlassian_cli::ApiClient,
 ) -> Result<serde_json::Value> {
     use atlassian_cli::confluence;
@@ -1832,26 +3153,60 @@ async fn handle_confluence(
             }
             let as_markdown = matches!(format, OutputFormat::Markdown);
             if all {
-                confluence::search_all(&query, limit, None, expand, stream, as_markdown, client)
-                    .await
+                let mut sink = if stream {
+                    Some(printing.stream()?)
+                } else {
+                    None
+                };
+                confluence::search_all(
+                    &query,
+                    limit,
+                    None,
+                    expand,
+                    sink.as_mut(),
+                    as_markdown,
+                    client,
+                )
+                .await
             } else {
                 confluence::search(&query, limit, None, expand, as_markdown, client).await
             }
         }
-        ConfluenceSubcommand::Get { page_id, format } => {
-            let as_markdown = matches!(format, OutputFormat::Markdown);
-            confluence::get_page(&page_id, None, None, as_markdown, client).await
+        ConfluenceSubcommand::Get {
+            page_id,
+            format,
+            media_dir,
+        } => {
+            if printing.output == Output::Md {
+                if matches!(format, PageFormat::Adf) {
+                    anyhow::bail!("--output md writes the body as Markdown; drop --format adf");
+                }
+                let document =
+                    confluence::get_page_document(&page_id, media_dir.as_deref(), client).await?;
+                return print_document(&document);
+            }
+            if media_dir.is_some() && !matches!(format, PageFormat::Markdown) {
+                anyhow::bail!("--media-dir requires --format markdown");
+            }
+            let format = match format {
+                PageFormat::Html => confluence::PageFormat::Storage,
+                PageFormat::Markdown => confluence::PageFormat::Markdown,
+                PageFormat::Adf => confluence::PageFormat::Adf,
+            };
+            confluence::get_page(&page_id, None, None, format, media_dir.as_deref(), client).await
         }
         ConfluenceSubcommand::Create {
             space,
             title,
             content,
             parent,
+            body_format,
         } => {
+            let content = page_content(content, body_format);
             confluence::create_page(
                 &space,
                 &title,
-                &content,
+                page_body(&content, body_format),
                 parent.as_deref(),
                 None,
                 None,
@@ -1864,11 +3219,13 @@ async fn handle_confluence(
             title,
             content,
             parent,
+            body_format,
         } => {
+            let content = page_content(content, body_format);
             confluence::update_page(
                 &page_id,
                 &title,
-                &content,
+                page_body(&content, body_format),
                 parent.as_deref(),
                 None,
                 None,
@@ -1929,8 +3286,17 @@ async fn handle_confluence(
                 page_id,
                 body,
                 reply_to,
-            } => confluence::add_comment(&page_id, &body, reply_to.as_deref(), client).await,
-            ConfluenceCommentAction::Update { comment_id, body } => {
+                body_format,
+            } => {
+                let body = confluence_body(body, body_format);
+                confluence::add_comment(&page_id, &body, reply_to.as_deref(), client).await
+            }
+            ConfluenceCommentAction::Update {
+                comment_id,
+                body,
+                body_format,
+            } => {
+                let body = confluence_body(body, body_format);
                 confluence::update_comment(&comment_id, &body, client).await
             }
             ConfluenceCommentAction::Delete { comment_id } => {
@@ -1996,6 +3362,29 @@ async fn handle_confluence(
                 )
                 .await
             }
+            ConfluenceAttachmentAction::Download {
+                target,
+                all,
+                dir,
+                to,
+            } => {
+                let dir = dir.unwrap_or_else(|| PathBuf::from("."));
+                if all {
+                    confluence::download_attachments(&target, &dir, client).await
+                } else {
+                    let destination = download_destination(to.as_deref(), &dir);
+                    confluence::download_attachment(&target, destination, client).await
+                }
+            }
+            ConfluenceAttachmentAction::Delete { attachment_id, yes } => {
+                if !yes {
+                    anyhow::bail!(
+                        "Deleting attachment {} moves it to the trash. Re-run with --yes to confirm.",
+                        attachment_id
+                    );
+                }
+                confluence::delete_attachment(&attachment_id, client).await
+            }
         },
         ConfluenceSubcommand::Delete { page_id, yes } => {
             if !yes {
@@ -2009,6 +3398,43 @@ async fn handle_confluence(
     }
 }
 
+/// The board `--board` names, or the one board of `--project`.
+async fn board_of(
+    board: Option<u64>,
+    project: Option<String>,
+    client: &atlassian_cli::ApiClient,
+) -> Result<u64> {
+    match board {
+        Some(id) => Ok(id),
+        None => {
+            let project_key = project
+                .ok_or_else(|| anyhow::anyhow!("Either --board or --project is required"))?;
+            atlassian_cli::jira::resolve_board_id(&project_key, client).await
+        }
+    }
+}
+
+/// Where `attachment download` writes one file: `--to` names the file, and
+/// `-` there means stdout; otherwise it goes into `dir` under its own name.
+fn download_destination<'a>(
+    to: Option<&'a std::path::Path>,
+    dir: &'a std::path::Path,
+) -> atlassian_cli::Destination<'a> {
+    use atlassian_cli::Destination;
+    match to {
+        Some(file) if file.as_os_str() == "-" => Destination::Stdout,
+        Some(file) => Destination::File(file),
+        None => Destination::Dir(dir),
+    }
+}
+
+/// Prints a Markdown document as it is, and hands back the `Null` that tells
+/// `output_json` stdout is already written.
+fn print_document(document: &str) -> Result<serde_json::Value> {
+    print!("{document}");
+    Ok(serde_json::Value::Null)
+}
+
 fn output_json(value: &serde_json::Value, pretty: bool) {
     // Null is a sentinel used by streaming commands that have already
     // written to stdout — emitting "null" would corrupt that output.
@@ -2372,6 +3798,64 @@ mod tests {
         }
     }
 
+    /// `--output` is written after the subcommand as often as before it, and
+    /// each format is refused up front where it cannot be honoured — before a
+    /// write is made whose answer would then go unprinted.
+    #[test]
+    fn output_formats_are_settled_before_the_command_runs() {
+        const BIN: &str = "atlassian-cli";
+        let printing = |args: &[&str]| Printing::new(&Cli::try_parse_from(args).unwrap());
+
+        assert!(printing(&[BIN, "jira", "get", "A-1", "--output", "md"]).is_ok());
+        assert!(printing(&[BIN, "--output", "md", "confluence", "get", "1"]).is_ok());
+        assert!(printing(&[BIN, "jira", "search", "x", "--output", "md"]).is_err());
+
+        let csv = printing(&[
+            BIN,
+            "jira",
+            "search",
+            "x",
+            "--output",
+            "csv",
+            "--columns",
+            "key,/fields/summary",
+        ])
+        .unwrap();
+        assert!(csv.columns.is_some());
+        assert!(printing(&[BIN, "jira", "search", "x", "--output", "tsv"]).is_err());
+        assert!(printing(&[BIN, "jira", "search", "x", "--columns", "key"]).is_err());
+        let table = printing(&[BIN, "confluence", "children", "1", "--output", "table"]).unwrap();
+        assert!(table.columns.is_none());
+        assert!(table.stream().is_err());
+
+        let select =
+            |paths: &str| printing(&[BIN, "jira", "search", "x", "--all", "--select", paths]);
+        assert!(select("items[].key,/total").unwrap().stream().is_ok());
+        assert!(select("/total").unwrap().stream().is_err());
+        assert!(
+            printing(&[
+                BIN, "jira", "get", "A-1", "--output", "md", "--select", "/key"
+            ])
+            .is_err()
+        );
+        assert!(printing(&[BIN, "config", "show", "--output", "csv", "--columns", "a"]).is_err());
+    }
+
+    /// `jira get` and `confluence get` answered `--output json|md` before the
+    /// flag served every command, and still answer it the same way.
+    #[test]
+    fn get_keeps_its_output_flag() {
+        const BIN: &str = "atlassian-cli";
+        let output = |args: &[&str]| Printing::new(&Cli::try_parse_from(args).unwrap()).unwrap();
+
+        for get in [&["jira", "get", "A-1"][..], &["confluence", "get", "1"][..]] {
+            let args = |rest: &[&'static str]| [&[BIN][..], get, rest].concat();
+            assert!(output(&args(&[])).output == Output::Json);
+            assert!(output(&args(&["--output", "json"])).output == Output::Json);
+            assert!(output(&args(&["--output", "md"])).output == Output::Md);
+        }
+    }
+
     /// Where every home-managed artifact is kept by name, only the binary is
     /// to go and its path is known — the guard has nothing to protect.
     #[cfg(unix)]
@@ -119,38 +119,39 @@ fn convert_ordered_list(node: &Value, depth: usize) -> Option<String> {
 
 fn convert_list_item(node: &Value, depth: usize) -> Option<String> {
     let content = node.get("content")?.as_array()?;
-    let mut parts: Vec<String> = Vec::new();
+    let mut result = String::new();
 
+    // Text parts are space-joined; a nested list starts on its own line, with
+    // no trailing space left on the line before it.
     for child in content {
         let child_type = child.get("type").and_then(|t| t.as_str()).unwrap_or("");
-        match child_type {
-            "paragraph" => {
-                if let Some(text) = convert_paragraph(child) {
-                    parts.push(text);
-                }
-            }
-            "bulletList" => {
-                if let Some(list) = convert_bullet_list(child, depth + 1) {
-                    parts.push(format!("\n{}", list));
-                }
-            }
-            "orderedList" => {
-                if let Some(list) = convert_ordered_list(child, depth + 1) {
-                    parts.push(format!("\n{}", list));
-                }
-            }
-            _ => {
-                if let Some(text) = convert_block_node(child, depth + 1) {
-                    parts.push(text);
-                }
+        let nested = match child_type {
+            "bulletList" => convert_bullet_list(child, depth + 1),
+            "orderedList" => convert_ordered_list(child, depth + 1),
+            _ => None,
+        };
+        if let Some(list) = nested {
+            result.push('\n');
+            result.push_str(&list);
+            continue;
+        }
+        let text = match child_type {
+            "paragraph" => convert_paragraph(child),
+            "bulletList" | "orderedList" => None,
+            _ => convert_block_node(child, depth + 1),
+        };
+        if let Some(text) = text {
+            if !result.is_empty() {
+                result.push(' ');
             }
+            result.push_str(&text);
         }
     }
 
-    if parts.is_empty() {
+    if result.is_empty() {
         None
     } else {
-        Some(parts.join(" "))
+        Some(result)
     }
 }
 
@@ -277,20 +278,42 @@ fn convert_cell_content(cell: &Value) -> String {
     content.replace('|', "\\|")
 }
 
+/// Each media of a `mediaSingle` or `mediaGroup`, a paragraph apiece. One
+/// that `link_media` pointed at its file shows it; one still stored by media
+/// id has no URL and is named instead.
 fn convert_media(node: &Value) -> Option<String> {
     let content = node.get("content").and_then(|c| c.as_array())?;
 
-    for media in content {
-        if let Some(attrs) = media.get("attrs") {
-            let alt = attrs
-                .get("alt")
-                .and_then(|a| a.as_str())
-                .or_else(|| attrs.get("id").and_then(|i| i.as_str()))
-                .unwrap_or("media");
-            return Some(format!("[Media: {}]", alt));
-        }
+    let items: Vec<String> = content
+        .iter()
+        .map(|child| match child.get("type").and_then(|t| t.as_str()) {
+            Some("media") => media_markdown(child),
+            _ => convert_inline_nodes(std::slice::from_ref(child)),
+        })
+        .filter(|item| !item.is_empty())
+        .collect();
+
+    if items.is_empty() {
+        Some("[Media]".into())
+    } else {
+        Some(items.join("\n\n"))
+    }
+}
+
+/// `![alt](url)` for media at a URL, `[Media: alt]` for the rest.
+pub(super) fn media_markdown(node: &Value) -> String {
+    let attrs = node.get("attrs");
+    let text = |key: &str| attrs.and_then(|a| a.get(key)).and_then(|v| v.as_str());
+
+    if text("type") == Some("external")
+        && let Some(url) = text("url")
+    {
+        return format!("![{}]({})", text("alt").unwrap_or_default(), url);
     }
-    Some("[Media]".into())
+    format!(
+        "[Media: {}]",
+        text("alt").or_else(|| text("id")).unwrap_or("media")
+    )
 }
 
 fn convert_expand(node: &Value) -> Option<String> {
@@ -336,12 +359,7 @@ fn convert_task_item(node: &Value) -> Option<String> {
     let content = node
         .get("content")
         .and_then(|c| c.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|n| convert_block_node(n, 0))
-                .collect::<Vec<_>>()
-                .join(" ")
-        })
+        .map(|arr| convert_item_content(arr))
         .unwrap_or_default();
 
     Some(format!("- {} {}", checkbox, content))
@@ -370,17 +388,28 @@ fn convert_decision_item(node: &Value) -> Option<String> {
     let content = node
         .get("content")
         .and_then(|c| c.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|n| convert_block_node(n, 0))
-                .collect::<Vec<_>>()
-                .join(" ")
-        })
+        .map(|arr| convert_item_content(arr))
         .unwrap_or_default();
 
     Some(format!("- {} {}", icon, content))
 }
 
+/// Task and decision items hold inline content directly in the ADF schema,
+/// though older documents wrap it in paragraphs — read both.
+fn convert_item_content(content: &[Value]) -> String {
+    let is_inline = |n: &Value| {
+        n.get("content").is_none() && n.get("type").and_then(|t| t.as_str()) != Some("rule")
+    };
+    if content.iter().all(is_inline) {
+        return convert_inline_nodes(content);
+    }
+    content
+        .iter()
+        .filter_map(|n| convert_block_node(n, 0))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 fn convert_layout_section(node: &Value) -> Option<String> {
     let content = node.get("content")?.as_array()?;
     let columns: Vec<String> = content.iter().filter_map(convert_layout_column).collect();
@@ -0,0 +1,178 @@
+//! ADF documents shaped like the ones Jira returns, shared by the round-trip
+//! tests. Each covers one family of nodes; together they cover every node and
+//! mark `blocks` and `inline` know, plus some they do not.
+
+use serde_json::{Value, json};
+
+fn doc(content: Value) -> Value {
+    json!({"type": "doc", "version": 1, "content": content})
+}
+
+fn para(content: Value) -> Value {
+    json!({"type": "paragraph", "content": content})
+}
+
+fn text(t: &str) -> Value {
+    json!({"type": "text", "text": t})
+}
+
+pub(super) fn documents() -> Vec<Value> {
+    vec![
+        // Text and marks.
+        doc(json!([
+            {"type": "heading", "attrs": {"level": 1}, "content": [text("Title")]},
+            {"type": "heading", "attrs": {"level": 6}, "content": [text("Deep")]},
+            para(json!([
+                text("plain "),
+                {"type": "text", "text": "bold", "marks": [{"type": "strong"}]},
+                text(" "),
+                {"type": "text", "text": "em", "marks": [{"type": "em"}]},
+                text(" "),
+                {"type": "text", "text": "code", "marks": [{"type": "code"}]},
+                text(" "),
+                {"type": "text", "text": "gone", "marks": [{"type": "strike"}]},
+                text(" "),
+                {"type": "text", "text": "under", "marks": [{"type": "underline"}]},
+                text(" "),
+                {"type": "text", "text": "2", "marks": [{"type": "subsup", "attrs": {"type": "sup"}}]},
+                text(" "),
+                {"type": "text", "text": "red", "marks": [{"type": "textColor", "attrs": {"color": "#ff5630"}}]},
+                text(" "),
+                {"type": "text", "text": "hi", "marks": [{"type": "backgroundColor", "attrs": {"color": "#fedec8"}}]}
+            ])),
+            para(json!([
+                {"type": "text", "text": "docs", "marks": [{"type": "link", "attrs": {"href": "https://example.com/a?b=1&c=2"}}]},
+                text(" and "),
+                {"type": "text", "text": "both", "marks": [{"type": "strong"}, {"type": "link", "attrs": {"href": "https://example.com"}}]}
+            ])),
+            para(json!([text("line one"), {"type": "hardBreak"}, text("line two")])),
+            para(json!([text("literal *stars*, _underscores_, [brackets], <tags>, a | pipe & # hash!")]))
+        ])),
+        // Marks and links Markdown cannot say.
+        doc(json!([
+            para(json!([
+                {"type": "text", "text": "annotated", "marks": [{"type": "annotation", "attrs": {"id": "a1", "annotationType": "inlineComment"}}]},
+                text(" "),
+                {"type": "text", "text": "evil", "marks": [{"type": "link", "attrs": {"href": "javascript:alert(1)"}}]}
+            ])),
+            para(json!([])),
+            para(json!([text("  padded  ")]))
+        ])),
+        // Lists.
+        doc(json!([
+            {"type": "bulletList", "content": [
+                {"type": "listItem", "content": [para(json!([text("one")]))]},
+                {"type": "listItem", "content": [
+                    para(json!([text("two")])),
+                    {"type": "orderedList", "content": [
+                        {"type": "listItem", "content": [para(json!([text("nested")]))]}
+                    ]}
+                ]}
+            ]},
+            {"type": "orderedList", "attrs": {"order": 9}, "content": [
+                {"type": "listItem", "content": [
+                    para(json!([text("nine")])),
+                    {"type": "bulletList", "content": [
+                        {"type": "listItem", "content": [para(json!([text("under ten")]))]}
+                    ]}
+                ]},
+                {"type": "listItem", "content": [para(json!([text("ten")]))]}
+            ]},
+            {"type": "orderedList", "attrs": {"order": 1}, "content": [
+                {"type": "listItem", "content": [para(json!([text("explicit order")]))]}
+            ]},
+            {"type": "bulletList", "content": [
+                {"type": "listItem", "content": [
+                    para(json!([text("with a code block")])),
+                    {"type": "codeBlock", "content": [text("x")]}
+                ]}
+            ]}
+        ])),
+        // Code, quotes, rules.
+        doc(json!([
+            {"type": "codeBlock", "attrs": {"language": "rust"}, "content": [text("fn main() {\n\n\n    println!(\"hi\");\n}")]},
+            {"type": "codeBlock", "content": [text("has ``` a fence")]},
+            {"type": "codeBlock", "attrs": {"language": "text"}},
+            {"type": "blockquote", "content": [
+                para(json!([text("quoted")])),
+                para(json!([text("twice")]))
+            ]},
+            {"type": "rule"}
+        ])),
+        // Tables.
+        doc(json!([
+            {"type": "table", "content": [
+                {"type": "tableRow", "content": [
+                    {"type": "tableHeader", "content": [para(json!([text("A")]))]},
+                    {"type": "tableHeader", "content": [para(json!([text("B")]))]}
+                ]},
+                {"type": "tableRow", "content": [
+                    {"type": "tableCell", "content": [para(json!([text("1")]))]},
+                    {"type": "tableCell", "content": [para(json!([text("2")]))]}
+                ]}
+            ]},
+            {"type": "table", "attrs": {"isNumberColumnEnabled": false, "layout": "default"}, "content": [
+                {"type": "tableRow", "content": [
+                    {"type": "tableHeader", "attrs": {"colspan": 2}, "content": [para(json!([text("wide")]))]}
+                ]}
+            ]}
+        ])),
+        // Inline nodes.
+        doc(json!([
+            para(json!([
+                text("ping "),
+                {"type": "mention", "attrs": {"id": "557058:f58131cb", "text": "@Jane Doe", "accessLevel": ""}},
+                text(" at "),
+                {"type": "date", "attrs": {"timestamp": "1735689600123"}},
+                text(", "),
+                {"type": "status", "attrs": {"text": "In Progress", "color": "blue", "localId": "s1"}},
+                text(" "),
+                {"type": "emoji", "attrs": {"shortName": ":thumbsup:", "id": "1f44d", "text": "👍"}},
+                text(" "),
+                {"type": "inlineCard", "attrs": {"url": "https://example.atlassian.net/browse/PROJ-1"}},
+                text(" "),
+                {"type": "mediaInline", "attrs": {"id": "m1", "collection": "c", "type": "file"}},
+                text(" "),
+                {"type": "placeholder", "attrs": {"text": "Type here"}},
+                {"type": "futureInline", "attrs": {"x": 1}}
+            ])),
+            para(json!([{"type": "mention", "attrs": {"id": "abc", "text": "@Lead"}}]))
+        ])),
+        // Panels, expands, media, tasks, decisions, layouts, extensions.
+        doc(json!([
+            {"type": "panel", "attrs": {"panelType": "info"}, "content": [para(json!([text("Heads up")]))]},
+            {"type": "panel", "attrs": {"panelType": "error"}, "content": [
+                para(json!([text("Broken")])),
+                {"type": "bulletList", "content": [
+                    {"type": "listItem", "content": [para(json!([text("step")]))]}
+                ]}
+            ]},
+            {"type": "expand", "attrs": {"title": "More"}, "content": [
+                para(json!([text("hidden")])),
+                {"type": "nestedExpand", "attrs": {"title": "Inner"}, "content": [para(json!([text("deeper")]))]}
+            ]},
+            {"type": "mediaSingle", "attrs": {"layout": "center", "width": 50}, "content": [
+                {"type": "media", "attrs": {"id": "6e7c7f2c", "type": "file", "collection": "contentId-1", "alt": "diagram.png", "width": 800, "height": 600}}
+            ]},
+            {"type": "mediaGroup", "content": [
+                {"type": "media", "attrs": {"id": "f1", "type": "file", "collection": "c"}},
+                {"type": "media", "attrs": {"id": "f2", "type": "file", "collection": "c"}}
+            ]},
+            {"type": "taskList", "attrs": {"localId": "tl-1"}, "content": [
+                {"type": "taskItem", "attrs": {"localId": "t-1", "state": "TODO"}, "content": [text("write it")]},
+                {"type": "taskItem", "attrs": {"localId": "t-2", "state": "DONE"}, "content": [text("ship it")]}
+            ]},
+            {"type": "decisionList", "attrs": {"localId": "dl-1"}, "content": [
+                {"type": "decisionItem", "attrs": {"localId": "d-1", "state": "DECIDED"}, "content": [text("Use Rust")]}
+            ]},
+            {"type": "layoutSection", "content": [
+                {"type": "layoutColumn", "attrs": {"width": 50}, "content": [para(json!([text("left")]))]},
+                {"type": "layoutColumn", "attrs": {"width": 50}, "content": [para(json!([text("right")]))]}
+            ]},
+            {"type": "extension", "attrs": {"extensionType": "com.atlassian.confluence.macro.core", "extensionKey": "toc"}},
+            {"type": "bodiedExtension", "attrs": {"extensionType": "com.example", "extensionKey": "box"}, "content": [para(json!([text("inside")]))]},
+            {"type": "embedCard", "attrs": {"url": "https://example.com/embed", "layout": "wide"}},
+            {"type": "futureBlock", "attrs": {"x": "<!-- tricky -->"}, "content": [para(json!([text("unknown")]))]}
+        ])),
+    ]
+}
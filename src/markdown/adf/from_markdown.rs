@@ -0,0 +1,775 @@
+//! Markdown → ADF, the reverse of `adf_to_markdown`.
+//!
+//! The output is deliberately confined to the nodes and marks that `blocks`,
+//! `inline` and `marks` already render, so a description fetched with
+//! `--format markdown`, edited, and written back with `--input-format markdown`
+//! survives the round trip as the same structure rather than as literal
+//! asterisks and backticks. Anything Markdown can say that ADF has no node for
+//! (raw HTML, images without an uploaded attachment) degrades to text or a
+//! link instead of failing — a body that cannot be posted is worse than one
+//! that is posted plainly.
+//!
+//! The markers `adf_to_markdown_lossless` writes are read here too: each puts
+//! back the exact node it carries, so that mode's output round-trips.
+
+use pulldown_cmark::{BlockQuoteKind, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use serde_json::{Map, Value, json};
+
+/// Convert a Markdown string into an ADF `doc`.
+///
+/// GitHub-flavoured extensions are on — tables, strikethrough, task lists and
+/// `> [!NOTE]` alerts — because that is the dialect people paste from. A soft
+/// line break becomes a `hardBreak`: the reader renders `hardBreak` as a bare
+/// newline, so treating that newline as anything else would collapse lines on
+/// every round trip.
+pub fn markdown_to_adf(markdown: &str) -> Value {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_GFM;
+
+    let mut builder = Builder::new();
+    for event in Parser::new_ext(markdown, options) {
+        builder.event(event);
+    }
+    builder.finish()
+}
+
+/// A node under construction: its `type`/`attrs` and the children gathered so
+/// far. `implicit` marks a paragraph the builder opened itself because inline
+/// content arrived in a container that only holds blocks (a tight list item,
+/// a table cell) — Markdown has no paragraph there, ADF requires one.
+/// `marker` marks a node opened by an `adf-begin` marker, which only the
+/// matching `adf-end` closes.
+struct Frame {
+    node: Map<String, Value>,
+    content: Vec<Value>,
+    implicit: bool,
+    marker: bool,
+}
+
+impl Frame {
+    fn new(node_type: &str, attrs: Option<Value>) -> Self {
+        let mut node = Map::new();
+        node.insert("type".into(), Value::String(node_type.into()));
+        if let Some(attrs) = attrs {
+            node.insert("attrs".into(), attrs);
+        }
+        Self {
+            node,
+            content: Vec::new(),
+            implicit: false,
+            marker: false,
+        }
+    }
+
+    fn node_type(&self) -> &str {
+        self.node
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+    }
+
+    fn accepts_inline(&self) -> bool {
+        matches!(
+            self.node_type(),
+            "paragraph" | "heading" | "codeBlock" | "taskItem"
+        )
+    }
+}
+
+struct Builder {
+    /// `stack[0]` is the `doc` itself and is never popped until `finish`.
+    stack: Vec<Frame>,
+    /// Active marks, outermost first — the order Markdown opens them in.
+    marks: Vec<Value>,
+    in_table_head: bool,
+    next_local_id: usize,
+    /// An inline marker's node, held while the reader's text between its
+    /// tags goes by unused.
+    inline_marker: Option<Value>,
+    /// A block marker's node, held the same way until `<!-- /adf -->`.
+    block_marker: Option<Value>,
+}
+
+impl Builder {
+    fn new() -> Self {
+        Self {
+            stack: vec![Frame::new("doc", None)],
+            marks: Vec::new(),
+            in_table_head: false,
+            next_local_id: 0,
+            inline_marker: None,
+            block_marker: None,
+        }
+    }
+
+    fn finish(mut self) -> Value {
+        while self.stack.len() > 1 {
+            self.close();
+        }
+        let doc = self.stack.pop().expect("doc frame");
+        json!({"type": "doc", "version": 1, "content": doc.content})
+    }
+
+    fn top(&mut self) -> &mut Frame {
+        self.stack.last_mut().expect("doc frame")
+    }
+
+    fn event(&mut self, event: Event) {
+        if self.block_marker.is_some() {
+            if let Event::Html(html) | Event::InlineHtml(html) = &event
+                && html.trim() == "<!-- /adf -->"
+                && let Some(node) = self.block_marker.take()
+            {
+                self.close_implicit();
+                self.top().content.push(node);
+            }
+            return;
+        }
+        if self.inline_marker.is_some() {
+            if let Event::InlineHtml(html) = &event
+                && html.trim() == "</span>"
+                && let Some(node) = self.inline_marker.take()
+            {
+                self.inline(node);
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => {
+                if self.top().node_type() == "codeBlock" {
+                    self.top()
+                        .content
+                        .push(json!({"type": "text", "text": text.as_ref()}));
+                } else {
+                    self.text(&text);
+                }
+            }
+            Event::Code(text) => self.code(&text),
+            Event::SoftBreak | Event::HardBreak => self.inline(json!({"type": "hardBreak"})),
+            Event::Rule => {
+                self.open_block("rule", None);
+                self.close();
+            }
+            Event::TaskListMarker(checked) => self.task_marker(checked),
+            Event::InlineHtml(html) => self.inline_html(&html),
+            Event::Html(html) => self.block_html(&html),
+            Event::InlineMath(text) | Event::DisplayMath(text) => self.text(&text),
+            Event::FootnoteReference(label) => self.text(&format!("[^{}]", label)),
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {
+                // A task item holds inline content directly; a loose list's
+                // paragraph wrapper is dropped rather than nested.
+                if self.top().node_type() != "taskItem" {
+                    self.open_block("paragraph", None);
+                }
+            }
+            Tag::Heading { level, .. } => {
+                self.open_block("heading", Some(json!({"level": level as u8})));
+            }
+            Tag::BlockQuote(kind) => match kind.map(panel_type) {
+                Some(panel) => self.open_block("panel", Some(json!({"panelType": panel}))),
+                None => self.open_block("blockquote", None),
+            },
+            Tag::CodeBlock(kind) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .map(str::to_string)
+                        .unwrap_or_default(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                let attrs = (!language.is_empty()).then(|| json!({"language": language}));
+                self.open_block("codeBlock", attrs);
+            }
+            Tag::List(Some(start)) => {
+                let attrs = (start != 1).then(|| json!({"order": start}));
+                self.open_block("orderedList", attrs);
+            }
+            Tag::List(None) => self.open_block("bulletList", None),
+            Tag::Item => self.open_block("listItem", None),
+            Tag::Table(_) => self.open_block("table", None),
+            Tag::TableHead => {
+                self.in_table_head = true;
+                self.open_block("tableRow", None);
+            }
+            Tag::TableRow => self.open_block("tableRow", None),
+            Tag::TableCell => {
+                let cell = if self.in_table_head {
+                    "tableHeader"
+                } else {
+                    "tableCell"
+                };
+                self.open_block(cell, None);
+            }
+            Tag::Emphasis => self.marks.push(json!({"type": "em"})),
+            Tag::Strong => self.marks.push(json!({"type": "strong"})),
+            Tag::Strikethrough => self.marks.push(json!({"type": "strike"})),
+            Tag::Link {
+                dest_url, title, ..
+            }
+            | Tag::Image {
+                dest_url, title, ..
+            } => self.marks.push(link_mark(&dest_url, &title)),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => {
+                if self.top().node_type() != "taskItem" {
+                    self.close_block();
+                }
+            }
+            TagEnd::TableHead => {
+                self.in_table_head = false;
+                self.close_block();
+            }
+            TagEnd::Heading(_)
+            | TagEnd::BlockQuote(_)
+            | TagEnd::CodeBlock
+            | TagEnd::List(_)
+            | TagEnd::Item
+            | TagEnd::Table
+            | TagEnd::TableRow
+            | TagEnd::TableCell => self.close_block(),
+            TagEnd::HtmlBlock => self.close_implicit(),
+            TagEnd::Emphasis => self.pop_mark("em"),
+            TagEnd::Strong => self.pop_mark("strong"),
+            TagEnd::Strikethrough => self.pop_mark("strike"),
+            TagEnd::Link | TagEnd::Image => self.pop_mark("link"),
+            _ => {}
+        }
+    }
+
+    fn open_block(&mut self, node_type: &str, attrs: Option<Value>) {
+        self.close_implicit();
+        self.stack.push(Frame::new(node_type, attrs));
+    }
+
+    fn close_block(&mut self) {
+        self.close_implicit();
+        if self.stack.len() > 1 {
+            self.close();
+        }
+    }
+
+    /// Close everything opened since the innermost `adf-begin`, then the
+    /// marker's node itself. A stray `adf-end` closes nothing.
+    fn close_marker(&mut self) {
+        if !self.stack.iter().skip(1).any(|f| f.marker) {
+            return;
+        }
+        while self.stack.len() > 1 {
+            let was_marker = self.top().marker;
+            self.close();
+            if was_marker {
+                break;
+            }
+        }
+    }
+
+    fn close_implicit(&mut self) {
+        if self.top().implicit {
+            self.close();
+        }
+    }
+
+    /// Pop the top frame and attach it to its parent, applying the fix-ups
+    /// ADF demands that Markdown does not: no empty text, a paragraph in every
+    /// list item and cell, task items only under a task list.
+    fn close(&mut self) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        let node = finish_frame(frame, &mut self.next_local_id);
+        self.top().content.push(node);
+    }
+
+    fn inline(&mut self, node: Value) {
+        if !self.top().accepts_inline() {
+            let mut paragraph = Frame::new("paragraph", None);
+            paragraph.implicit = true;
+            self.stack.push(paragraph);
+        }
+        push_inline(&mut self.top().content, node);
+    }
+
+    fn text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let mut node = json!({"type": "text", "text": text});
+        if !self.marks.is_empty() {
+            // The reader wraps marks in array order, innermost first; Markdown
+            // opened them outermost first.
+            let marks: Vec<Value> = self.marks.iter().rev().cloned().collect();
+            node["marks"] = Value::Array(marks);
+        }
+        self.inline(node);
+    }
+
+    /// Inline code. ADF allows `code` alongside a link but no other mark, so
+    /// emphasis around a code span is dropped rather than rejected by Jira.
+    fn code(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let mut marks = vec![json!({"type": "code"})];
+        marks.extend(
+            self.marks
+                .iter()
+                .filter(|m| m.get("type").and_then(|t| t.as_str()) == Some("link"))
+                .cloned(),
+        );
+        self.inline(json!({"type": "text", "text": text, "marks": marks}));
+    }
+
+    fn pop_mark(&mut self, mark_type: &str) {
+        if let Some(pos) = self
+            .marks
+            .iter()
+            .rposition(|m| m.get("type").and_then(|t| t.as_str()) == Some(mark_type))
+        {
+            self.marks.remove(pos);
+        }
+    }
+
+    /// Turn the enclosing `listItem` into a `taskItem`. In a loose list the
+    /// marker arrives inside the item's (still empty) paragraph, which is
+    /// discarded — a task item holds its inline content directly.
+    fn task_marker(&mut self, checked: bool) {
+        if self.top().node_type() == "paragraph" && self.top().content.is_empty() {
+            self.stack.pop();
+        }
+        let item = self.top();
+        if item.node_type() != "listItem" {
+            return;
+        }
+        item.node
+            .insert("type".into(), Value::String("taskItem".into()));
+        let state = if checked { "DONE" } else { "TODO" };
+        item.node
+            .insert("attrs".into(), json!({"localId": "", "state": state}));
+    }
+
+    /// The reader emits underline, sub/superscript and colours as inline HTML,
+    /// so those tags come back as marks; any other tag stays literal text.
+    fn inline_html(&mut self, html: &str) {
+        let tag = html.trim();
+        if let Some(node) = tag
+            .strip_prefix("<span data-adf='")
+            .and_then(|t| t.strip_suffix("'>"))
+            .and_then(marker_node)
+        {
+            self.inline_marker = Some(node);
+            return;
+        }
+        if let Some(name) = tag.strip_prefix("</").and_then(|t| t.strip_suffix('>')) {
+            match name.trim() {
+                "u" => return self.pop_mark("underline"),
+                "sub" | "sup" => return self.pop_mark("subsup"),
+                "span" => return self.pop_mark("textColor"),
+                "mark" => return self.pop_mark("backgroundColor"),
+                _ => {}
+            }
+        } else if let Some(mark) = html_open_mark(tag) {
+            self.marks.push(mark);
+            return;
+        }
+        self.text(html);
+    }
+
+    /// Block-level HTML has no ADF counterpart, the lossless markers aside.
+    /// Other comments are dropped — they were invisible in the Markdown too —
+    /// and anything else is kept as text so nothing the author wrote silently
+    /// disappears.
+    fn block_html(&mut self, html: &str) {
+        let trimmed = html.trim();
+        if let Some(comment) = trimmed
+            .strip_prefix("<!--")
+            .and_then(|t| t.strip_suffix("-->"))
+            .map(str::trim)
+        {
+            if let Some(Value::Object(shell)) =
+                comment.strip_prefix("adf-begin ").and_then(marker_node)
+            {
+                self.close_implicit();
+                self.stack.push(Frame {
+                    node: shell,
+                    content: Vec::new(),
+                    implicit: false,
+                    marker: true,
+                });
+                return;
+            }
+            if comment == "adf-end" {
+                return self.close_marker();
+            }
+            if let Some(node) = comment.strip_prefix("adf ").and_then(marker_node) {
+                self.block_marker = Some(node);
+                return;
+            }
+        }
+        if trimmed.is_empty() || (trimmed.starts_with("<!--") && trimmed.ends_with("-->")) {
+            return;
+        }
+        for (i, line) in trimmed.lines().enumerate() {
+            if i > 0 {
+                self.inline(json!({"type": "hardBreak"}));
+            }
+            self.text(line);
+        }
+    }
+}
+
+/// The JSON a marker carries; only an object with a `type` is a node. A
+/// marker mangled past parsing is an ordinary comment again.
+fn marker_node(json: &str) -> Option<Value> {
+    serde_json::from_str::<Value>(json.trim())
+        .ok()
+        .filter(|node| node.get("type").is_some_and(Value::is_string))
+}
+
+fn finish_frame(frame: Frame, next_local_id: &mut usize) -> Value {
+    let Frame {
+        mut node,
+        mut content,
+        ..
+    } = frame;
+    let node_type = node
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    match node_type.as_str() {
+        "rule" => return Value::Object(node),
+        "codeBlock" => {
+            let code: String = content
+                .iter()
+                .filter_map(|n| n.get("text").and_then(|t| t.as_str()))
+                .collect();
+            let code = code.strip_suffix('\n').unwrap_or(&code);
+            content = if code.is_empty() {
+                Vec::new()
+            } else {
+                vec![json!({"type": "text", "text": code})]
+            };
+        }
+        "listItem" | "tableCell" | "tableHeader" => {
+            if content.is_empty() {
+                content.push(json!({"type": "paragraph", "content": []}));
+            }
+        }
+        "taskItem" => {
+            *next_local_id += 1;
+            node["attrs"]["localId"] = Value::String(format!("task-{}", next_local_id));
+        }
+        "bulletList" | "orderedList" => {
+            let tasks = content
+                .iter()
+                .filter(|c| c.get("type").and_then(|t| t.as_str()) == Some("taskItem"))
+                .count();
+            if tasks > 0 && tasks == content.len() {
+                *next_local_id += 1;
+                node.insert("type".into(), Value::String("taskList".into()));
+                node.insert(
+                    "attrs".into(),
+                    json!({"localId": format!("tasks-{}", next_local_id)}),
+                );
+            } else if tasks > 0 {
+                // A list mixing checkboxes with plain items has no ADF shape;
+                // the checkbox survives as text instead.
+                content = content.into_iter().map(task_item_to_list_item).collect();
+            }
+        }
+        _ => {}
+    }
+
+    node.insert("content".into(), Value::Array(content));
+    Value::Object(node)
+}
+
+fn task_item_to_list_item(item: Value) -> Value {
+    if item.get("type").and_then(|t| t.as_str()) != Some("taskItem") {
+        return item;
+    }
+    let checkbox = if item["attrs"]["state"] == "DONE" {
+        "[x] "
+    } else {
+        "[ ] "
+    };
+    let mut inline = vec![json!({"type": "text", "text": checkbox})];
+    if let Some(children) = item.get("content").and_then(|c| c.as_array()) {
+        for child in children {
+            push_inline(&mut inline, child.clone());
+        }
+    }
+    json!({"type": "listItem", "content": [{"type": "paragraph", "content": inline}]})
+}
+
+/// Append an inline node, merging it into a preceding text node with the same
+/// marks — the parser splits text at every character it considered special,
+/// and a run of one-character nodes would survive into Jira's storage.
+fn push_inline(content: &mut Vec<Value>, node: Value) {
+    if node.get("type").and_then(|t| t.as_str()) == Some("text")
+        && let Some(last) = content.last_mut()
+        && last.get("type").and_then(|t| t.as_str()) == Some("text")
+        && last.get("marks") == node.get("marks")
+        && let (Some(prev), Some(next)) = (
+            last.get("text").and_then(|t| t.as_str()),
+            node.get("text").and_then(|t| t.as_str()),
+        )
+    {
+        last["text"] = Value::String(format!("{}{}", prev, next));
+        return;
+    }
+    content.push(node);
+}
+
+fn panel_type(kind: BlockQuoteKind) -> &'static str {
+    match kind {
+        BlockQuoteKind::Note => "info",
+        BlockQuoteKind::Tip => "success",
+        BlockQuoteKind::Important => "note",
+        BlockQuoteKind::Warning => "warning",
+        BlockQuoteKind::Caution => "error",
+    }
+}
+
+fn link_mark(href: &str, title: &str) -> Value {
+    if title.is_empty() {
+        json!({"type": "link", "attrs": {"href": href}})
+    } else {
+        json!({"type": "link", "attrs": {"href": href, "title": title}})
+    }
+}
+
+/// Recognise the opening tags `marks::apply_marks` writes.
+fn html_open_mark(tag: &str) -> Option<Value> {
+    let inner = tag.strip_prefix('<')?.strip_suffix('>')?.trim();
+    match inner {
+        "u" => return Some(json!({"type": "underline"})),
+        "sub" => return Some(json!({"type": "subsup", "attrs": {"type": "sub"}})),
+        "sup" => return Some(json!({"type": "subsup", "attrs": {"type": "sup"}})),
+        _ => {}
+    }
+    let (name, rest) = inner.split_once(char::is_whitespace)?;
+    let style = rest
+        .trim()
+        .strip_prefix("style=\"")?
+        .strip_suffix('"')?
+        .trim();
+    match (name, style.split_once(':')?) {
+        ("span", ("color", color)) => {
+            Some(json!({"type": "textColor", "attrs": {"color": color.trim()}}))
+        }
+        ("mark", ("background", color)) => {
+            Some(json!({"type": "backgroundColor", "attrs": {"color": color.trim()}}))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::adf_to_markdown;
+
+    fn blocks(markdown: &str) -> Vec<Value> {
+        markdown_to_adf(markdown)["content"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_empty_input_is_an_empty_doc() {
+        assert_eq!(
+            markdown_to_adf(""),
+            json!({"type": "doc", "version": 1, "content": []})
+        );
+    }
+
+    #[test]
+    fn test_paragraph_with_marks() {
+        let content = blocks("plain **bold** *em* ~~gone~~ `code`");
+        assert_eq!(
+            content[0],
+            json!({"type": "paragraph", "content": [
+                {"type": "text", "text": "plain "},
+                {"type": "text", "text": "bold", "marks": [{"type": "strong"}]},
+                {"type": "text", "text": " "},
+                {"type": "text", "text": "em", "marks": [{"type": "em"}]},
+                {"type": "text", "text": " "},
+                {"type": "text", "text": "gone", "marks": [{"type": "strike"}]},
+                {"type": "text", "text": " "},
+                {"type": "text", "text": "code", "marks": [{"type": "code"}]}
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_heading_levels() {
+        let content = blocks("# One\n\n### Three");
+        assert_eq!(content[0]["type"], "heading");
+        assert_eq!(content[0]["attrs"]["level"], 1);
+        assert_eq!(content[1]["attrs"]["level"], 3);
+        assert_eq!(content[1]["content"][0]["text"], "Three");
+    }
+
+    #[test]
+    fn test_link_with_title_and_nested_mark() {
+        let content = blocks("[**docs**](https://example.com \"Docs\")");
+        assert_eq!(
+            content[0]["content"][0],
+            json!({"type": "text", "text": "docs", "marks": [
+                {"type": "strong"},
+                {"type": "link", "attrs": {"href": "https://example.com", "title": "Docs"}}
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_code_span_keeps_only_link_mark() {
+        let content = blocks("**[`x`](https://e.com)**");
+        assert_eq!(
+            content[0]["content"][0]["marks"],
+            json!([{"type": "code"}, {"type": "link", "attrs": {"href": "https://e.com"}}])
+        );
+    }
+
+    #[test]
+    fn test_tight_list_items_get_a_paragraph() {
+        let content = blocks("- a\n- b\n  1. nested");
+        let list = &content[0];
+        assert_eq!(list["type"], "bulletList");
+        assert_eq!(list["content"][0]["content"][0]["type"], "paragraph");
+        let nested = &list["content"][1]["content"][1];
+        assert_eq!(nested["type"], "orderedList");
+        assert_eq!(
+            nested["content"][0]["content"][0]["content"][0]["text"],
+            "nested"
+        );
+    }
+
+    #[test]
+    fn test_ordered_list_start() {
+        assert!(blocks("1. a")[0].get("attrs").is_none());
+        assert_eq!(blocks("3. a")[0]["attrs"]["order"], 3);
+    }
+
+    #[test]
+    fn test_code_block_language_and_trailing_newline() {
+        let content = blocks("```rust\nlet x = 1;\n```");
+        assert_eq!(
+            content[0],
+            json!({"type": "codeBlock", "attrs": {"language": "rust"},
+                   "content": [{"type": "text", "text": "let x = 1;"}]})
+        );
+    }
+
+    #[test]
+    fn test_empty_code_block_has_no_empty_text() {
+        let content = blocks("```\n```");
+        assert_eq!(content[0]["content"], json!([]));
+    }
+
+    #[test]
+    fn test_table_header_and_cells() {
+        let content = blocks("| A | B |\n| --- | --- |\n| 1 |  |");
+        let table = &content[0];
+        assert_eq!(table["type"], "table");
+        assert_eq!(table["content"][0]["content"][0]["type"], "tableHeader");
+        assert_eq!(table["content"][1]["content"][0]["type"], "tableCell");
+        assert_eq!(
+            table["content"][1]["content"][1]["content"],
+            json!([{"type": "paragraph", "content": []}])
+        );
+    }
+
+    #[test]
+    fn test_task_list() {
+        let content = blocks("- [ ] todo\n- [x] done");
+        let list = &content[0];
+        assert_eq!(list["type"], "taskList");
+        assert_eq!(list["content"][0]["type"], "taskItem");
+        assert_eq!(list["content"][0]["attrs"]["state"], "TODO");
+        assert_eq!(list["content"][1]["attrs"]["state"], "DONE");
+        assert_eq!(list["content"][1]["content"][0]["text"], "done");
+        assert_ne!(list["content"][0]["attrs"]["localId"], "");
+    }
+
+    #[test]
+    fn test_mixed_task_list_keeps_checkbox_as_text() {
+        let content = blocks("- [ ] todo\n- plain");
+        let list = &content[0];
+        assert_eq!(list["type"], "bulletList");
+        assert_eq!(
+            list["content"][0]["content"][0]["content"][0]["text"],
+            "[ ] todo"
+        );
+    }
+
+    #[test]
+    fn test_alert_becomes_panel() {
+        let content = blocks("> [!WARNING]\n> Careful");
+        assert_eq!(content[0]["type"], "panel");
+        assert_eq!(content[0]["attrs"]["panelType"], "warning");
+        assert_eq!(content[0]["content"][0]["content"][0]["text"], "Careful");
+    }
+
+    #[test]
+    fn test_soft_break_is_hard_break() {
+        let content = blocks("line one\nline two");
+        assert_eq!(content[0]["content"][1], json!({"type": "hardBreak"}));
+    }
+
+    #[test]
+    fn test_inline_html_marks() {
+        let content = blocks("<u>under</u> <span style=\"color:#ff0000\">red</span>");
+        assert_eq!(
+            content[0]["content"][0]["marks"],
+            json!([{"type": "underline"}])
+        );
+        assert_eq!(
+            content[0]["content"][2]["marks"],
+            json!([{"type": "textColor", "attrs": {"color": "#ff0000"}}])
+        );
+    }
+
+    #[test]
+    fn test_html_comment_block_is_dropped() {
+        let content = blocks("<!-- note -->\n\ntext");
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0]["content"][0]["text"], "text");
+    }
+
+    #[test]
+    fn test_round_trip_through_adf_to_markdown() {
+        let markdown = "# Title\n\n\
+            Some **bold**, *em*, `code` and [a link](https://example.com).\n\n\
+            - one\n- two\n  - nested\n\n\
+            1. first\n2. second\n\n\
+            ```rust\nfn main() {}\n```\n\n\
+            > quoted\n\n\
+            ---\n\n\
+            | A | B |\n| --- | --- |\n| 1 | 2 |\n\n\
+            - [ ] todo\n- [x] done";
+
+        let adf = markdown_to_adf(markdown);
+        assert_eq!(adf_to_markdown(&adf), markdown);
+        assert_eq!(markdown_to_adf(&adf_to_markdown(&adf)), adf);
+    }
+}
@@ -1,4 +1,6 @@
+use super::blocks::media_markdown;
 use super::marks::apply_marks;
+use crate::markdown::UserNames;
 use serde_json::Value;
 
 pub fn convert_inline_nodes(nodes: &[Value]) -> String {
@@ -16,7 +18,7 @@ fn convert_inline_node(node: &Value) -> String {
         "inlineCard" => convert_inline_card(node),
         "date" => convert_date(node),
         "status" => convert_status(node),
-        "mediaInline" => convert_media_inline(node),
+        "mediaInline" => media_markdown(node),
         "placeholder" => convert_placeholder(node),
         _ => String::new(),
     }
@@ -38,12 +40,81 @@ fn convert_mention(node: &Value) -> String {
     let text = attrs
         .and_then(|a| a.get("text"))
         .and_then(|t| t.as_str())
+        .filter(|t| !mention_text_is_bare(t))
         .or_else(|| attrs.and_then(|a| a.get("id")).and_then(|i| i.as_str()))
         .unwrap_or("user");
 
     format!("@{}", text.trim_start_matches('@'))
 }
 
+/// Mention text that names nobody: missing from the node, a lone `@`, which
+/// is what the editor leaves when a mention is pasted across sites.
+fn mention_text_is_bare(text: &str) -> bool {
+    text.trim().trim_start_matches('@').trim().is_empty()
+}
+
+/// The account ids of the mentions in `adf` that carry no name of their own,
+/// each once: what a caller has to look up before `name_mentions` can name
+/// them. A mention whose text is its own id counts as unnamed.
+pub fn unnamed_mentions(adf: &Value) -> Vec<String> {
+    let mut ids = Vec::new();
+    visit_mentions(adf, &mut |attrs| {
+        let Some(id) = attrs.get("id").and_then(|i| i.as_str()) else {
+            return;
+        };
+        let text = attrs.get("text").and_then(|t| t.as_str()).unwrap_or("");
+        let unnamed = mention_text_is_bare(text) || text.trim_start_matches('@') == id;
+        if unnamed && !id.is_empty() && !ids.iter().any(|known| known == id) {
+            ids.push(id.to_string());
+        }
+    });
+    ids
+}
+
+/// Writes the looked-up display name into each mention `names` knows, as the
+/// `@Name` text the editor would have stored. The rest keep what they have,
+/// and render as their id.
+pub fn name_mentions(adf: &mut Value, names: &UserNames) {
+    visit_mentions_mut(adf, &mut |attrs| {
+        let name = attrs
+            .get("id")
+            .and_then(|i| i.as_str())
+            .and_then(|id| names.get(id));
+        if let Some(name) = name {
+            attrs.insert("text".into(), Value::String(format!("@{}", name)));
+        }
+    });
+}
+
+fn visit_mentions(node: &Value, visit: &mut dyn FnMut(&serde_json::Map<String, Value>)) {
+    if node.get("type").and_then(|t| t.as_str()) == Some("mention")
+        && let Some(attrs) = node.get("attrs").and_then(|a| a.as_object())
+    {
+        visit(attrs);
+    }
+    if let Some(content) = node.get("content").and_then(|c| c.as_array()) {
+        for child in content {
+            visit_mentions(child, visit);
+        }
+    }
+}
+
+fn visit_mentions_mut(
+    node: &mut Value,
+    visit: &mut dyn FnMut(&mut serde_json::Map<String, Value>),
+) {
+    if node.get("type").and_then(|t| t.as_str()) == Some("mention")
+        && let Some(attrs) = node.get_mut("attrs").and_then(|a| a.as_object_mut())
+    {
+        visit(attrs);
+    }
+    if let Some(content) = node.get_mut("content").and_then(|c| c.as_array_mut()) {
+        for child in content {
+            visit_mentions_mut(child, visit);
+        }
+    }
+}
+
 fn convert_emoji(node: &Value) -> String {
     let shortname = node
         .get("attrs")
@@ -163,17 +234,6 @@ fn convert_status(node: &Value) -> String {
     format!("{} {}", indicator, text.to_uppercase())
 }
 
-fn convert_media_inline(node: &Value) -> String {
-    let attrs = node.get("attrs");
-    let alt = attrs
-        .and_then(|a| a.get("alt"))
-        .and_then(|a| a.as_str())
-        .or_else(|| attrs.and_then(|a| a.get("id")).and_then(|i| i.as_str()))
-        .unwrap_or("media");
-
-    format!("[Media: {}]", alt)
-}
-
 fn convert_placeholder(node: &Value) -> String {
     let text = node
         .get("attrs")
@@ -223,6 +283,36 @@ mod tests {
         assert_eq!(convert_inline_node(&node), "@user123");
     }
 
+    #[test]
+    fn test_mention_with_bare_at_falls_back_to_id() {
+        let node = json!({"type": "mention", "attrs": {"id": "user123", "text": "@"}});
+        assert_eq!(convert_inline_node(&node), "@user123");
+    }
+
+    #[test]
+    fn test_unnamed_mentions_are_collected_once() {
+        let adf = json!({"type": "doc", "content": [{"type": "paragraph", "content": [
+            {"type": "mention", "attrs": {"id": "a", "text": "@Ann"}},
+            {"type": "mention", "attrs": {"id": "b"}},
+            {"type": "mention", "attrs": {"id": "c", "text": "@c"}},
+            {"type": "mention", "attrs": {"id": "b", "text": "@"}}
+        ]}]});
+        assert_eq!(unnamed_mentions(&adf), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_name_mentions_writes_known_names_only() {
+        let mut adf = json!({"type": "doc", "content": [{"type": "paragraph", "content": [
+            {"type": "mention", "attrs": {"id": "b"}},
+            {"type": "mention", "attrs": {"id": "z"}}
+        ]}]});
+        let mut names = UserNames::default();
+        names.insert("b", "Bea Lee");
+        name_mentions(&mut adf, &names);
+        let inline = adf["content"][0]["content"].as_array().unwrap();
+        assert_eq!(convert_inline_nodes(inline), "@Bea Lee@z");
+    }
+
     #[test]
     fn test_emoji() {
         let node = json!({"type": "emoji", "attrs": {"shortName": ":smile:", "text": "😄"}});
@@ -0,0 +1,416 @@
+//! Lossless ADF → Markdown, for a description that will be edited and written
+//! back.
+//!
+//! `adf_to_markdown` is for reading: a mention becomes `@Name`, an image
+//! `[Media: x]`, and what Jira needs to restore them is gone. Here every block
+//! is rendered as plain Markdown only when `markdown_to_adf` turns that
+//! Markdown back into the identical node — the check is made, not assumed.
+//! Whatever fails it carries its original JSON in a marker instead:
+//!
+//! - an inline node: `<span data-adf='{json}'>@Name</span>` — the text between
+//!   the tags is for the reader and is ignored on the way back;
+//! - a block whose children are themselves Markdown (a panel, an expand):
+//!   `<!-- adf-begin {json without content} -->` … `<!-- adf-end -->`, so the
+//!   body stays editable;
+//! - any other block: `<!-- adf {json} -->`, the readable rendering, and
+//!   `<!-- /adf -->` — the rendering between is ignored on the way back.
+//!
+//! The markers are ordinary HTML, so the Markdown still renders anywhere.
+
+use super::blocks::convert_block_node;
+use super::from_markdown::markdown_to_adf;
+use super::inline::convert_inline_nodes;
+use super::marks::apply_marks;
+use serde_json::Value;
+
+/// Marks `apply_marks` writes and `markdown_to_adf` reads back. Text carrying
+/// any other mark (an annotation, an alignment) goes through a marker.
+const MARKDOWN_MARKS: &[&str] = &[
+    "strong",
+    "em",
+    "code",
+    "strike",
+    "underline",
+    "link",
+    "subsup",
+    "textColor",
+    "backgroundColor",
+];
+
+/// Node types whose content is a list of blocks, rendered between
+/// `adf-begin`/`adf-end` markers when they cannot be plain Markdown.
+const CONTAINERS: &[&str] = &[
+    "panel",
+    "expand",
+    "nestedExpand",
+    "bodiedExtension",
+    "layoutSection",
+    "layoutColumn",
+    "extensionFrame",
+    "multiBodiedExtension",
+];
+
+pub fn adf_to_markdown_lossless(adf: &Value) -> String {
+    let Some(content) = adf.get("content").and_then(|c| c.as_array()) else {
+        return String::new();
+    };
+
+    // Not `normalize_whitespace`: collapsing blank lines would rewrite code
+    // blocks, and the whole point is that nothing is rewritten.
+    blocks(content)
+}
+
+/// Render sibling blocks. Two lists of the same kind in a row would be read
+/// back as one list, so an empty comment — dropped on the way back — keeps
+/// them apart.
+fn blocks(nodes: &[Value]) -> String {
+    let mut parts = Vec::with_capacity(nodes.len());
+    let mut previous: Option<(&str, bool)> = None;
+    for node in nodes {
+        let rendered = block(node);
+        let is_marker = rendered.starts_with("<!--");
+        let kind = node_type(node);
+        if let Some((previous_kind, previous_marker)) = previous
+            && previous_kind == kind
+            && !previous_marker
+            && !is_marker
+            && matches!(kind, "bulletList" | "orderedList")
+        {
+            parts.push("<!-- -->".to_string());
+        }
+        previous = Some((kind, is_marker));
+        parts.push(rendered);
+    }
+    parts.join("\n\n")
+}
+
+/// Render one block, as Markdown if that survives the trip back and as a
+/// marker otherwise.
+fn block(node: &Value) -> String {
+    let node_type = node_type(node);
+    let candidate = if CONTAINERS.contains(&node_type) {
+        container(node)
+    } else {
+        readable(node)
+    };
+
+    if let Some(markdown) = candidate
+        && round_trips(&markdown, node)
+    {
+        return markdown;
+    }
+    leaf(node)
+}
+
+fn round_trips(markdown: &str, node: &Value) -> bool {
+    markdown_to_adf(markdown)
+        .get("content")
+        .and_then(|c| c.as_array())
+        .is_some_and(|content| content.len() == 1 && &content[0] == node)
+}
+
+/// Plain Markdown for the blocks that have it; `None` for any that holds
+/// something only a marker can carry, so the caller falls back.
+fn readable(node: &Value) -> Option<String> {
+    let content = node.get("content").and_then(|c| c.as_array());
+    match node_type(node) {
+        "paragraph" => Some(inline(content?)),
+        "heading" => {
+            let level = node.get("attrs")?.get("level")?.as_u64()? as usize;
+            Some(format!("{} {}", "#".repeat(level), inline(content?)))
+        }
+        "bulletList" | "orderedList" => list(node),
+        "blockquote" => Some(
+            blocks(content?)
+                .lines()
+                .map(|l| {
+                    if l.is_empty() {
+                        ">".into()
+                    } else {
+                        format!("> {}", l)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        _ => convert_block_node(node, 0),
+    }
+}
+
+/// A list whose items are one paragraph, optionally followed by a nested
+/// list — the shape Markdown lists have. Continuation lines are indented to
+/// the item's content column, which for `10. ` is four spaces, not two.
+fn list(node: &Value) -> Option<String> {
+    let ordered = node_type(node) == "orderedList";
+    let start = node
+        .get("attrs")
+        .and_then(|a| a.get("order"))
+        .and_then(|o| o.as_u64())
+        .unwrap_or(1);
+
+    let mut lines = Vec::new();
+    for (i, item) in node.get("content")?.as_array()?.iter().enumerate() {
+        if node_type(item) != "listItem" {
+            return None;
+        }
+        let marker = if ordered {
+            format!("{}. ", start + i as u64)
+        } else {
+            "- ".to_string()
+        };
+        let children = item.get("content")?.as_array()?;
+        let (first, rest) = children.split_first()?;
+        if node_type(first) != "paragraph" {
+            return None;
+        }
+        let mut parts = vec![readable(first)?];
+        for child in rest {
+            if !matches!(node_type(child), "bulletList" | "orderedList") {
+                return None;
+            }
+            parts.push(list(child)?);
+        }
+        let indent = " ".repeat(marker.len());
+        let body = parts.join("\n");
+        for (n, line) in body.lines().enumerate() {
+            lines.push(match n {
+                0 => format!("{}{}", marker, line),
+                _ if line.is_empty() => String::new(),
+                _ => format!("{}{}", indent, line),
+            });
+        }
+    }
+    Some(lines.join("\n"))
+}
+
+fn container(node: &Value) -> Option<String> {
+    let children = node.get("content")?.as_array()?;
+    let mut shell = node.clone();
+    shell.as_object_mut()?.remove("content");
+
+    Some(format!(
+        "<!-- adf-begin {} -->\n\n{}\n\n<!-- adf-end -->",
+        marker_json(&shell),
+        blocks(children)
+    ))
+}
+
+/// The last resort, and always exact: the node's own JSON, with its readable
+/// rendering alongside for whoever opens the file. The rendering is dropped
+/// when it would not stay between the markers (an unclosed fence would
+/// swallow the closing one).
+fn leaf(node: &Value) -> String {
+    let open = format!("<!-- adf {} -->", marker_json(node));
+    if let Some(readable) = convert_block_node(node, 0) {
+        let marked = format!("{}\n\n{}\n\n<!-- /adf -->", open, readable);
+        if round_trips(&marked, node) {
+            return marked;
+        }
+    }
+    format!("{}\n<!-- /adf -->", open)
+}
+
+fn inline(nodes: &[Value]) -> String {
+    nodes
+        .iter()
+        .map(|node| match node_type(node) {
+            "text" if has_only_markdown_marks(node) => text(node),
+            "hardBreak" => "\n".into(),
+            _ => format!(
+                "<span data-adf='{}'>{}</span>",
+                marker_json(node),
+                escape(&convert_inline_nodes(std::slice::from_ref(node)))
+            ),
+        })
+        .collect()
+}
+
+fn text(node: &Value) -> String {
+    let raw = node
+        .get("text")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default();
+    let marks = node.get("marks").and_then(|m| m.as_array());
+    // Backslash escapes are literal inside a code span, so code text goes in
+    // as it is; a backtick in it fails the round trip and falls back.
+    let is_code = marks.is_some_and(|m| m.iter().any(|mark| node_type(mark) == "code"));
+    let text = if is_code {
+        raw.to_string()
+    } else {
+        escape(raw)
+    };
+    apply_marks(text, marks)
+}
+
+fn has_only_markdown_marks(node: &Value) -> bool {
+    node.get("marks")
+        .and_then(|m| m.as_array())
+        .is_none_or(|marks| {
+            marks
+                .iter()
+                .all(|mark| MARKDOWN_MARKS.contains(&node_type(mark)))
+        })
+}
+
+/// Backslash-escape the punctuation that opens inline syntax anywhere in a
+/// line. Characters that only matter at the start of one (`#`, `-`, `1.`) are
+/// left alone: the rare block they turn into fails the round trip and falls
+/// back to a marker, and everything else stays readable.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '~') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Node JSON made safe inside an HTML comment or a single-quoted attribute.
+/// Those characters only ever occur inside JSON strings, where the `\u`
+/// escapes decode back to the same text.
+pub(super) fn marker_json(node: &Value) -> String {
+    serde_json::to_string(node)
+        .unwrap_or_default()
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('\'', "\\u0027")
+}
+
+fn node_type(node: &Value) -> &str {
+    node.get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures;
+    use super::*;
+    use serde_json::json;
+
+    fn doc(content: Vec<Value>) -> Value {
+        json!({"type": "doc", "version": 1, "content": content})
+    }
+
+    fn assert_round_trips(adf: &Value) {
+        let markdown = adf_to_markdown_lossless(adf);
+        assert_eq!(
+            &markdown_to_adf(&markdown),
+            adf,
+            "lost in round trip:\n{}",
+            markdown
+        );
+    }
+
+    /// Every fixture document, every block of each on its own, and every
+    /// ordered pair of blocks survives Markdown and back unchanged — the pairs
+    /// catch a marker that only works when nothing follows it.
+    #[test]
+    fn every_fixture_survives_the_round_trip() {
+        let documents = fixtures::documents();
+        let blocks: Vec<Value> = documents
+            .iter()
+            .flat_map(|d| d["content"].as_array().cloned().unwrap_or_default())
+            .collect();
+
+        for document in &documents {
+            assert_round_trips(document);
+        }
+        for a in &blocks {
+            assert_round_trips(&doc(vec![a.clone()]));
+            for b in &blocks {
+                assert_round_trips(&doc(vec![a.clone(), b.clone()]));
+            }
+        }
+    }
+
+    #[test]
+    fn rendering_the_restored_document_is_stable() {
+        for document in fixtures::documents() {
+            let once = adf_to_markdown_lossless(&document);
+            let twice = adf_to_markdown_lossless(&markdown_to_adf(&once));
+            assert_eq!(once, twice);
+        }
+    }
+
+    #[test]
+    fn plain_content_stays_plain_markdown() {
+        let adf = doc(vec![
+            json!({"type": "heading", "attrs": {"level": 2}, "content": [{"type": "text", "text": "Plan"}]}),
+            json!({"type": "paragraph", "content": [
+                {"type": "text", "text": "Ship "},
+                {"type": "text", "text": "it", "marks": [{"type": "strong"}]}
+            ]}),
+        ]);
+        assert_eq!(adf_to_markdown_lossless(&adf), "## Plan\n\nShip **it**");
+    }
+
+    #[test]
+    fn a_mention_is_an_inline_marker_in_otherwise_plain_text() {
+        let mention = json!({"type": "mention", "attrs": {"id": "5b10a", "text": "@Jane"}});
+        let adf = doc(vec![json!({"type": "paragraph", "content": [
+            {"type": "text", "text": "ask "},
+            mention.clone(),
+        ]})]);
+        let markdown = adf_to_markdown_lossless(&adf);
+        assert_eq!(
+            markdown,
+            format!(
+                "ask <span data-adf='{}'>@Jane</span>",
+                serde_json::to_string(&mention).unwrap()
+            )
+        );
+        assert_round_trips(&adf);
+    }
+
+    #[test]
+    fn a_panel_keeps_its_body_editable() {
+        let adf = doc(vec![json!({
+            "type": "panel",
+            "attrs": {"panelType": "warning"},
+            "content": [{"type": "paragraph", "content": [{"type": "text", "text": "Hot"}]}]
+        })]);
+        assert_eq!(
+            adf_to_markdown_lossless(&adf),
+            "<!-- adf-begin {\"attrs\":{\"panelType\":\"warning\"},\"type\":\"panel\"} -->\n\nHot\n\n<!-- adf-end -->"
+        );
+    }
+
+    #[test]
+    fn an_edit_between_container_markers_is_kept() {
+        let adf = doc(vec![json!({
+            "type": "panel",
+            "attrs": {"panelType": "info"},
+            "content": [{"type": "paragraph", "content": [{"type": "text", "text": "Old"}]}]
+        })]);
+        let edited = adf_to_markdown_lossless(&adf).replace("Old", "New **text**");
+        assert_eq!(
+            markdown_to_adf(&edited)["content"][0]["content"][0]["content"][1],
+            json!({"type": "text", "text": "text", "marks": [{"type": "strong"}]})
+        );
+    }
+
+    #[test]
+    fn media_is_restored_whatever_its_placeholder_says() {
+        let media = json!({
+            "type": "mediaSingle",
+            "attrs": {"layout": "center"},
+            "content": [{"type": "media", "attrs": {"id": "abc-123", "type": "file", "collection": "c"}}]
+        });
+        let markdown = adf_to_markdown_lossless(&doc(vec![media.clone()]));
+        assert!(markdown.contains("[Media: abc-123]"));
+        let edited = markdown.replace("[Media: abc-123]", "anything");
+        assert_eq!(markdown_to_adf(&edited), doc(vec![media]));
+    }
+
+    #[test]
+    fn marker_json_cannot_close_its_comment() {
+        let node = json!({"type": "mystery", "attrs": {"note": "a --> b <c> 'd'"}});
+        let marker = marker_json(&node);
+        assert!(!marker.contains('>') && !marker.contains('\''));
+        assert_eq!(serde_json::from_str::<Value>(&marker).unwrap(), node);
+        assert_round_trips(&doc(vec![node]));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::markdown::Attachments;
+use serde_json::{Map, Value, json};
+
+/// Whether `adf` shows any media at all: a caller with nothing to link need
+/// not fetch an attachment list.
+pub fn has_media(adf: &Value) -> bool {
+    is_media(adf)
+        || adf
+            .get("content")
+            .and_then(|c| c.as_array())
+            .is_some_and(|content| content.iter().any(has_media))
+}
+
+/// Points each media node `attachments` knows at the file, ahead of a
+/// readable render.
+///
+/// A `file` media names its file by media id and, as the editor writes it,
+/// by filename in `alt`; neither opens anything. An image becomes the
+/// `external` media ADF already has for an image at a URL, which renders as
+/// `![alt](url)`. Any other file becomes its filename linked to the URL,
+/// since a PDF shown as an image is a broken image. Media that are not in
+/// the list keep their placeholder.
+///
+/// The document is no longer the one that was stored afterwards, so this is
+/// for output only: a lossless read, which is written back, leaves media as
+/// they are.
+pub fn link_media(adf: &mut Value, attachments: &Attachments) {
+    if attachments.is_empty() {
+        return;
+    }
+    let Some(content) = adf.get_mut("content").and_then(|c| c.as_array_mut()) else {
+        return;
+    };
+    for child in content {
+        if is_media(child) {
+            link_one(child, attachments);
+        } else {
+            link_media(child, attachments);
+        }
+    }
+}
+
+fn is_media(node: &Value) -> bool {
+    matches!(
+        node.get("type").and_then(|t| t.as_str()),
+        Some("media" | "mediaInline")
+    )
+}
+
+fn link_one(node: &mut Value, attachments: &Attachments) {
+    let Some(attrs) = node.get("attrs").and_then(|a| a.as_object()) else {
+        return;
+    };
+    let text = |key: &str| attrs.get(key).and_then(|v| v.as_str());
+    let Some(found) = attachments.find(text("id"), text("alt")) else {
+        return;
+    };
+
+    if found.is_image() {
+        let mut linked = Map::new();
+        linked.insert("type".into(), json!("external"));
+        linked.insert("url".into(), json!(found.href));
+        linked.insert("alt".into(), json!(text("alt").unwrap_or(&found.filename)));
+        for key in ["width", "height"] {
+            if let Some(value) = attrs.get(key) {
+                linked.insert(key.into(), value.clone());
+            }
+        }
+        node["attrs"] = Value::Object(linked);
+    } else {
+        *node = json!({
+            "type": "text",
+            "text": found.filename,
+            "marks": [{"type": "link", "attrs": {"href": found.href}}]
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::adf_to_markdown;
+    use super::*;
+    use crate::markdown::Attachment;
+
+    fn attachments() -> Attachments {
+        let mut attachments = Attachments::default();
+        for (id, filename, media_type) in [
+            ("10001", "diagram.png", "image/png"),
+            ("10002", "spec.pdf", "application/pdf"),
+        ] {
+            attachments.insert(Attachment {
+                id: id.into(),
+                filename: filename.into(),
+                media_type: Some(media_type.into()),
+                href: format!("https://example.com/attachment/{}", id),
+            });
+        }
+        attachments
+    }
+
+    fn doc(content: Value) -> Value {
+        json!({"type": "doc", "version": 1, "content": content})
+    }
+
+    #[test]
+    fn test_an_attached_image_is_shown_from_its_url() {
+        let mut adf = doc(json!([{"type": "mediaSingle", "content": [
+            {"type": "media", "attrs": {"id": "6e7c", "type": "file", "collection": "c", "alt": "diagram.png"}}
+        ]}]));
+        assert!(has_media(&adf));
+        link_media(&mut adf, &attachments());
+        assert_eq!(
+            adf_to_markdown(&adf),
+            "![diagram.png](https://example.com/attachment/10001)"
+        );
+    }
+
+    #[test]
+    fn test_other_files_are_linked_and_unknown_ones_keep_their_placeholder() {
+        let mut adf = doc(json!([{"type": "mediaGroup", "content": [
+            {"type": "media", "attrs": {"id": "10002", "type": "file", "collection": "c"}},
+            {"type": "media", "attrs": {"id": "gone", "type": "file", "collection": "c"}}
+        ]}]));
+        link_media(&mut adf, &attachments());
+        assert_eq!(
+            adf_to_markdown(&adf),
+            "[spec.pdf](https://example.com/attachment/10002)\n\n[Media: gone]"
+        );
+    }
+
+    #[test]
+    fn test_inline_media_is_linked_in_place() {
+        let mut adf = doc(json!([{"type": "paragraph", "content": [
+            {"type": "text", "text": "see "},
+            {"type": "mediaInline", "attrs": {"id": "x", "type": "file", "collection": "c", "alt": "spec.pdf"}}
+        ]}]));
+        link_media(&mut adf, &attachments());
+        assert_eq!(
+            adf_to_markdown(&adf),
+            "see [spec.pdf](https://example.com/attachment/10002)"
+        );
+    }
+
+    #[test]
+    fn test_a_body_without_media_has_none() {
+        let adf = doc(json!([{"type": "paragraph", "content": [{"type": "text", "text": "hi"}]}]));
+        assert!(!has_media(&adf));
+    }
+}
@@ -1,10 +1,39 @@
 mod blocks;
+#[cfg(test)]
+mod fixtures;
+mod from_markdown;
 mod inline;
+mod lossless;
 mod marks;
+mod media;
+
+pub use from_markdown::markdown_to_adf;
+pub use inline::{name_mentions, unnamed_mentions};
+pub use lossless::adf_to_markdown_lossless;
+pub use media::{has_media, link_media};
 
 use crate::markdown::common::normalize_whitespace;
 use serde_json::Value;
 
+/// Which Markdown an ADF body is read back as. `Readable` is for people and
+/// agents that only read it; `Lossless` keeps every node Markdown cannot say
+/// as a marker, so the text can be edited and sent back through
+/// `markdown_to_adf` without dropping mentions, panels or media.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkdownMode {
+    Readable,
+    Lossless,
+}
+
+impl MarkdownMode {
+    pub fn render(self, adf: &Value) -> String {
+        match self {
+            MarkdownMode::Readable => adf_to_markdown(adf),
+            MarkdownMode::Lossless => adf_to_markdown_lossless(adf),
+        }
+    }
+}
+
 pub fn adf_to_markdown(adf: &Value) -> String {
     let Some(content) = adf.get("content").and_then(|c| c.as_array()) else {
         return String::new();
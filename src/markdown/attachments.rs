@@ -0,0 +1,111 @@
+use crate::http_utils::{content_type_for_filename, encode_path_segment};
+use std::path::Path;
+
+/// One file attached to the issue or page a body belongs to, and where a
+/// link to it should point.
+#[derive(Clone, Debug)]
+pub struct Attachment {
+    pub id: String,
+    pub filename: String,
+    /// The media type the site reported, when it reported one.
+    pub media_type: Option<String>,
+    pub href: String,
+}
+
+impl Attachment {
+    /// An image is shown (`![..](..)`), anything else is linked. Without a
+    /// reported type the filename decides, by the same table uploads use.
+    pub fn is_image(&self) -> bool {
+        self.media_type
+            .as_deref()
+            .unwrap_or_else(|| content_type_for_filename(&self.filename))
+            .starts_with("image/")
+    }
+}
+
+/// The attachments a body's media can point at.
+///
+/// A body refers to a file by name (a storage `ri:attachment`, an ADF
+/// media's `alt`) or by id, never by a URL a reader can open. The renderers
+/// look each reference up here and link what they find; a reference that
+/// is not here keeps its placeholder, which says what was there even where
+/// it cannot say where.
+#[derive(Clone, Debug, Default)]
+pub struct Attachments(Vec<Attachment>);
+
+impl Attachments {
+    pub fn insert(&mut self, attachment: Attachment) {
+        self.0.push(attachment);
+    }
+
+    /// Points every attachment at `<dir>/<filename>` instead of the site:
+    /// the links a Markdown file saved next to the downloaded attachments
+    /// needs. The filename is percent-encoded, since a space would end a
+    /// Markdown link target.
+    pub fn localize(&mut self, dir: &Path) {
+        let dir = dir.to_string_lossy();
+        let dir = dir.trim_end_matches('/');
+        for attachment in &mut self.0 {
+            attachment.href = format!("{}/{}", dir, encode_path_segment(&attachment.filename));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The attachment a reference names: by id first, since two versions of
+    /// a page can carry two files of one name, then by filename. The newest
+    /// is listed first by both APIs, so the first match wins.
+    pub fn find(&self, id: Option<&str>, filename: Option<&str>) -> Option<&Attachment> {
+        let by_id = id
+            .filter(|id| !id.is_empty())
+            .and_then(|id| self.0.iter().find(|a| a.id == id));
+        by_id.or_else(|| {
+            filename
+                .filter(|f| !f.is_empty())
+                .and_then(|f| self.0.iter().find(|a| a.filename == f))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(id: &str, filename: &str, media_type: Option<&str>) -> Attachment {
+        Attachment {
+            id: id.into(),
+            filename: filename.into(),
+            media_type: media_type.map(Into::into),
+            href: format!("https://example.com/{}", id),
+        }
+    }
+
+    #[test]
+    fn test_an_id_match_wins_over_a_filename_match() {
+        let mut attachments = Attachments::default();
+        attachments.insert(attachment("1", "a.png", None));
+        attachments.insert(attachment("2", "b.png", None));
+        let found = attachments.find(Some("2"), Some("a.png")).unwrap();
+        assert_eq!(found.id, "2");
+        assert_eq!(attachments.find(Some("9"), Some("a.png")).unwrap().id, "1");
+        assert!(attachments.find(None, Some("c.png")).is_none());
+    }
+
+    #[test]
+    fn test_the_reported_type_decides_before_the_filename() {
+        assert!(attachment("1", "shot.png", None).is_image());
+        assert!(!attachment("1", "shot.png", Some("application/pdf")).is_image());
+        assert!(attachment("1", "blob", Some("image/gif")).is_image());
+    }
+
+    #[test]
+    fn test_localized_links_are_relative_and_encoded() {
+        let mut attachments = Attachments::default();
+        attachments.insert(attachment("1", "my diagram.png", None));
+        attachments.localize(Path::new("media/"));
+        let found = attachments.find(None, Some("my diagram.png")).unwrap();
+        assert_eq!(found.href, "media/my%20diagram.png");
+    }
+}
@@ -1,27 +1,6 @@
 use regex::Regex;
 use std::sync::LazyLock;
 
-static MACRO_ID_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"\s*ac:macro-id="[^"]*""#).unwrap());
-
-static SCHEMA_VERSION_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"\s*ac:schema-version="[^"]*""#).unwrap());
-
-static DATA_LAYOUT_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"\s*data-layout="[^"]*""#).unwrap());
-
-static EMPTY_PARAM_SELF_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"<ac:parameter ac:name=""\s*/>"#).unwrap());
-
-static EMPTY_PARAM_PAIR_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"<ac:parameter ac:name="">[^<]*</ac:parameter>"#).unwrap());
-
-static CDATA_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"<!\[CDATA\[([\s\S]*?)\]\]>"#).unwrap());
-
-static ADF_ATTRIBUTE_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"<ac:adf-attribute[^>]*>.*?</ac:adf-attribute>"#).unwrap());
-
 static MX_GRAPH_MODEL_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"<mxGraphModel[\s\S]*?</mxGraphModel>"#).unwrap());
 
@@ -32,23 +11,10 @@ static LONG_BASE64_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"[A-Za-z0-9+/=]{500,}"#).unwrap());
 
 // Match 10+ consecutive whitespace characters (spaces, tabs, but not newlines)
-// AI agents parse structure via delimiters (|, \n), not visual alignment
+// after text. AI agents parse structure via delimiters (|, \n), not visual
+// alignment; indentation at the start of a line is a code block's, and stays.
 static CONSECUTIVE_SPACES_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"[^\S\n]{10,}"#).unwrap());
-
-pub fn clean_metadata(html: &str) -> String {
-    let mut result = html.to_string();
-
-    result = MACRO_ID_RE.replace_all(&result, "").to_string();
-    result = SCHEMA_VERSION_RE.replace_all(&result, "").to_string();
-    result = DATA_LAYOUT_RE.replace_all(&result, "").to_string();
-    result = EMPTY_PARAM_SELF_RE.replace_all(&result, "").to_string();
-    result = EMPTY_PARAM_PAIR_RE.replace_all(&result, "").to_string();
-    result = ADF_ATTRIBUTE_RE.replace_all(&result, "").to_string();
-    result = CDATA_RE.replace_all(&result, "$1").to_string();
-
-    result
-}
+    LazyLock::new(|| Regex::new(r#"(\S)[^\S\n]{10,}"#).unwrap());
 
 pub fn clean_binary_data(content: &str) -> String {
     let mut result = content.to_string();
@@ -56,7 +22,9 @@ pub fn clean_binary_data(content: &str) -> String {
     result = MX_GRAPH_MODEL_RE.replace_all(&result, "").to_string();
     result = MX_FILE_RE.replace_all(&result, "").to_string();
     result = LONG_BASE64_RE.replace_all(&result, "").to_string();
-    result = CONSECUTIVE_SPACES_RE.replace_all(&result, " ").to_string();
+    result = CONSECUTIVE_SPACES_RE
+        .replace_all(&result, "$1 ")
+        .to_string();
 
     result.trim().to_string()
 }
@@ -65,42 +33,6 @@ pub fn clean_binary_data(content: &str) -> String {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_remove_macro_id() {
-        let html = r#"<ac:structured-macro ac:name="code" ac:macro-id="abc-123">"#;
-        let result = clean_metadata(html);
-        assert!(!result.contains("ac:macro-id"));
-        assert!(result.contains("ac:name=\"code\""));
-    }
-
-    #[test]
-    fn test_remove_schema_version() {
-        let html = r#"<ac:structured-macro ac:name="code" ac:schema-version="1">"#;
-        let result = clean_metadata(html);
-        assert!(!result.contains("ac:schema-version"));
-    }
-
-    #[test]
-    fn test_unwrap_cdata() {
-        let html = r#"<![CDATA[let x = 1;]]>"#;
-        let result = clean_metadata(html);
-        assert_eq!(result, "let x = 1;");
-    }
-
-    #[test]
-    fn test_remove_empty_params() {
-        let html = r#"<ac:parameter ac:name="" /><ac:parameter ac:name="">value</ac:parameter>"#;
-        let result = clean_metadata(html);
-        assert!(!result.contains("ac:parameter"));
-    }
-
-    #[test]
-    fn test_remove_adf_attribute() {
-        let html = r#"<ac:adf-attribute key="panel-type">note</ac:adf-attribute>"#;
-        let result = clean_metadata(html);
-        assert!(result.is_empty());
-    }
-
     #[test]
     fn test_remove_mxgraphmodel() {
         let content = r#"<mxGraphModel><root></root></mxGraphModel> text"#;
@@ -142,6 +74,12 @@ mod tests {
         assert_eq!(result, "col1 col2");
     }
 
+    #[test]
+    fn test_preserve_indentation() {
+        let content = format!("fn main() {{\n{}deep();\n}}", " ".repeat(12));
+        assert_eq!(clean_binary_data(&content), content);
+    }
+
     #[test]
     fn test_preserve_newlines() {
         // Newlines should be preserved, only horizontal whitespace collapsed
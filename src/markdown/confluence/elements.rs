@@ -1,588 +1,384 @@
-use super::macros::process_macro;
+use super::macros::{panel, process_macro};
+use super::storage::{Element, Image, Link, LinkBody, Node, Resource, escape_html, text_content};
+use crate::config::MacroRule;
+use crate::markdown::{Attachments, UserNames};
+use std::collections::HashMap;
+
+/// Renders a storage tree to HTML that htmd can finish.
+///
+/// Standard HTML is written back as it came; Confluence nodes become what
+/// the Markdown should say for them — a fenced code block, a quoted panel,
+/// a `[Draw.io: name]` token. A few constructs have no HTML that htmd would
+/// turn into the right Markdown (a task list is not a bulleted list with
+/// brackets escaped in it), so they are rendered to Markdown directly and
+/// parked as blocks: the HTML carries a placeholder, and [`Self::restore`]
+/// swaps the Markdown in once htmd is done.
+pub(super) struct Renderer<'a> {
+    blocks: Vec<String>,
+    names: &'a UserNames,
+    attachments: &'a Attachments,
+    macros: &'a HashMap<String, MacroRule>,
+}
 
-pub fn clean_confluence_html(html: &str) -> String {
-    let mut result = html.to_string();
+const BLOCK_START: char = '\u{e000}';
+const BLOCK_END: char = '\u{e001}';
 
-    result = process_emoticons(&result);
-    result = process_images(&result);
-    result = process_links(&result);
-    result = process_macros(&result);
-    result = process_tasks(&result);
-    result = process_adf_extensions(&result);
-    result = remove_remaining_tags(&result);
+/// Elements that hold binary diagram data rather than content; a draw.io
+/// macro that lost its wrapper leaves these behind.
+const RESIDUE_ELEMENTS: &[&str] = &["mxGraphModel", "mxfile"];
 
-    result
+/// Storage attributes that describe the editor's state, not the content.
+fn is_metadata_attr(name: &str) -> bool {
+    name.contains(':') || name.starts_with("data-")
 }
 
-fn process_emoticons(html: &str) -> String {
-    let mut result = html.to_string();
-    let mut iterations = 0;
-    const MAX_ITERATIONS: usize = 1000;
-
-    while iterations < MAX_ITERATIONS {
-        let Some(start) = result.find("<ac:emoticon") else {
-            break;
-        };
-
-        // Self-closing
-        if let Some(rel_end) = result[start..].find("/>") {
-            let end = start + rel_end + 2;
-            let tag = &result[start..end];
-            let replacement = extract_emoticon_shortname(tag);
-            result = format!("{}{}{}", &result[..start], replacement, &result[end..]);
-        } else if let Some(rel_end) = result[start..].find("</ac:emoticon>") {
-            let end = start + rel_end + 14;
-            let tag = &result[start..end];
-            let replacement = extract_emoticon_shortname(tag);
-            result = format!("{}{}{}", &result[..start], replacement, &result[end..]);
-        } else {
-            break;
+impl<'a> Renderer<'a> {
+    pub fn new(
+        names: &'a UserNames,
+        attachments: &'a Attachments,
+        macros: &'a HashMap<String, MacroRule>,
+    ) -> Self {
+        Self {
+            blocks: Vec::new(),
+            names,
+            attachments,
+            macros,
         }
-        iterations += 1;
     }
-    result
-}
 
-fn extract_emoticon_shortname(tag: &str) -> String {
-    if let Some(start) = tag.find("ac:name=\"") {
-        let rest = &tag[start + 9..];
-        if let Some(end) = rest.find('"') {
-            let name = &rest[..end];
-            return match name {
-                "smile" | "smiley" => ":)",
-                "sad" => ":(",
-                "wink" => ";)",
-                "laugh" => ":D",
-                "thumbs-up" => "(y)",
-                "thumbs-down" => "(n)",
-                "tick" | "check" => "[x]",
-                "cross" | "error" => "[!]",
-                "warning" => "[!]",
-                "information" | "info" => "(i)",
-                "question" => "(?)",
-                "light-on" | "idea" => "(!)",
-                "star" => "(*)",
-                "heart" => "<3",
-                _ => name,
-            }
-            .to_string();
-        }
+    pub fn attachments(&self) -> &Attachments {
+        self.attachments
     }
-    String::new()
-}
 
-fn process_images(html: &str) -> String {
-    let mut result = html.to_string();
-    let mut iterations = 0;
-    const MAX_ITERATIONS: usize = 1000;
-
-    while iterations < MAX_ITERATIONS {
-        let Some(start) = result.find("<ac:image") else {
-            break;
-        };
-
-        if let Some(rel_end) = result[start..].find("</ac:image>") {
-            let end = start + rel_end + 11;
-            let block = &result[start..end];
-            let replacement = extract_image_info(block);
-            result = format!("{}{}{}", &result[..start], replacement, &result[end..]);
-        } else if let Some(rel_end) = result[start..].find("/>") {
-            let end = start + rel_end + 2;
-            let block = &result[start..end];
-            let replacement = extract_image_info(block);
-            result = format!("{}{}{}", &result[..start], replacement, &result[end..]);
-        } else {
-            break;
-        }
-        iterations += 1;
+    /// The configured rule for a macro, if the config has one for it.
+    pub fn macro_rule(&self, name: &str) -> Option<&'a MacroRule> {
+        self.macros.get(name)
     }
-    result
-}
 
-fn extract_image_info(block: &str) -> String {
-    // Try ri:filename
-    if let Some(start) = block.find("ri:filename=\"") {
-        let rest = &block[start + 13..];
-        if let Some(end) = rest.find('"') {
-            let filename = &rest[..end];
-            return format!("[Image: {}]", filename);
+    pub fn render(&mut self, nodes: &[Node]) -> String {
+        let mut out = String::new();
+        for node in nodes {
+            self.node(node, &mut out);
         }
+        out
     }
 
-    // Try ri:url
-    if let Some(start) = block.find("ri:value=\"") {
-        let rest = &block[start + 10..];
-        if let Some(end) = rest.find('"') {
-            let url = &rest[..end];
-            return format!("![Image]({})", url);
-        }
-    }
-
-    // Try ac:alt
-    if let Some(start) = block.find("ac:alt=\"") {
-        let rest = &block[start + 8..];
-        if let Some(end) = rest.find('"') {
-            let alt = &rest[..end];
-            if !alt.is_empty() {
-                return format!("[Image: {}]", alt);
+    fn node(&mut self, node: &Node, out: &mut String) {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Cdata(text) => out.push_str(&escape_html(text)),
+            Node::Element(element) => self.element(element, out),
+            Node::Macro(m) => out.push_str(&process_macro(m, self)),
+            Node::Link(link) => {
+                out.push_str(&escape_html(&link_text(link, self.names, self.attachments)))
+            }
+            Node::Image(image) => out.push_str(&escape_html(&image_text(image, self.attachments))),
+            Node::Emoticon(name) => out.push_str(&escape_html(emoticon_shortname(name))),
+            Node::TaskList(tasks) => {
+                let markdown = self.task_list(tasks, 0);
+                out.push_str(&format!("<div>{}</div>", self.block(markdown)));
             }
         }
     }
 
-    "[Image]".into()
-}
-
-fn process_links(html: &str) -> String {
-    let mut result = html.to_string();
-    let mut iterations = 0;
-    const MAX_ITERATIONS: usize = 1000;
-
-    while iterations < MAX_ITERATIONS {
-        let Some(start) = result.find("<ac:link") else {
-            break;
-        };
-
-        if let Some(rel_end) = result[start..].find("</ac:link>") {
-            let end = start + rel_end + 10;
-            let block = &result[start..end];
-            let replacement = extract_link_info(block);
-            result = format!("{}{}{}", &result[..start], replacement, &result[end..]);
-        } else if let Some(rel_end) = result[start..].find("/>") {
-            let end = start + rel_end + 2;
-            result = format!("{}{}", &result[..start], &result[end..]);
-        } else {
-            break;
+    fn element(&mut self, element: &Element, out: &mut String) {
+        if RESIDUE_ELEMENTS.iter().any(|r| element.is(r)) {
+            return;
         }
-        iterations += 1;
-    }
-    result
-}
-
-fn extract_link_info(block: &str) -> String {
-    // Get display text
-    let display_text = extract_link_body(block)
-        .or_else(|| extract_plain_text_body(block))
-        .or_else(|| extract_ri_content_title(block));
-
-    // Get target
-    let target = extract_link_target(block);
-
-    match (display_text, target) {
-        (Some(text), Some(url)) => format!("[{}]({})", text.trim(), url),
-        (Some(text), None) => text.trim().to_string(),
-        (None, Some(url)) => format!("[{}]({})", url, url),
-        (None, None) => String::new(),
-    }
-}
-
-fn extract_link_body(block: &str) -> Option<String> {
-    let start = block.find("<ac:link-body>")? + 14;
-    let rest = &block[start..];
-    let end = rest.find("</ac:link-body>")?;
-    let content = &rest[..end];
-
-    // Strip HTML tags for simple display
-    let text = strip_html_tags(content);
-    if text.trim().is_empty() {
-        None
-    } else {
-        Some(text)
-    }
-}
-
-fn extract_plain_text_body(block: &str) -> Option<String> {
-    let start = block.find("<ac:plain-text-link-body>")? + 25;
-    let rest = &block[start..];
-    let end = rest.find("</ac:plain-text-link-body>")?;
-    let text = rest[..end]
-        .replace("<![CDATA[", "")
-        .replace("]]>", "")
-        .trim()
-        .to_string();
-
-    if text.is_empty() { None } else { Some(text) }
-}
-
-fn extract_ri_content_title(block: &str) -> Option<String> {
-    let start = block.find("ri:content-title=\"")? + 18;
-    let rest = &block[start..];
-    let end = rest.find('"')?;
-    Some(rest[..end].to_string())
-}
-
-fn extract_link_target(block: &str) -> Option<String> {
-    // ri:page
-    if block.contains("<ri:page") {
-        let title = extract_ri_content_title(block)?;
-        let space = extract_attr(block, "ri:space-key");
-        return match space {
-            Some(s) => Some(format!("page:{}/{}", s, title)),
-            None => Some(format!("page:{}", title)),
-        };
-    }
-
-    // ri:user
-    if block.contains("<ri:user") {
-        let id =
-            extract_attr(block, "ri:account-id").or_else(|| extract_attr(block, "ri:userkey"))?;
-        return Some(format!("@{}", id));
-    }
-
-    // ri:attachment
-    if block.contains("<ri:attachment") {
-        let filename = extract_attr(block, "ri:filename")?;
-        return Some(format!("attachment:{}", filename));
-    }
-
-    // ri:url
-    if block.contains("<ri:url") {
-        return extract_attr(block, "ri:value");
-    }
-
-    // ac:anchor
-    if let Some(anchor) = extract_attr(block, "ac:anchor") {
-        return Some(format!("#{}", anchor));
-    }
-
-    None
-}
-
-fn extract_attr(block: &str, attr: &str) -> Option<String> {
-    let pattern = format!("{}=\"", attr);
-    let start = block.find(&pattern)? + pattern.len();
-    let rest = &block[start..];
-    let end = rest.find('"')?;
-    Some(rest[..end].to_string())
-}
-
-fn strip_html_tags(html: &str) -> String {
-    let mut result = String::new();
-    let mut in_tag = false;
-
-    for ch in html.chars() {
-        match ch {
-            '<' => in_tag = true,
-            '>' => in_tag = false,
-            _ if !in_tag => result.push(ch),
-            _ => {}
+        if element.is("ac:adf-extension") {
+            out.push_str(&self.adf_extension(element));
+            return;
         }
-    }
-
-    result
-}
-
-fn process_macros(html: &str) -> String {
-    let mut result = html.to_string();
-    let mut iterations = 0;
-    const MAX_ITERATIONS: usize = 1000;
-
-    while iterations < MAX_ITERATIONS {
-        let Some(start) = result.find("<ac:structured-macro") else {
-            break;
-        };
-
-        let rest = &result[start..];
-
-        // Check for self-closing first
-        if let Some(self_close) = find_self_closing_end(rest) {
-            let tag = &rest[..self_close + 2];
-            let replacement = process_macro(tag);
-            result = format!(
-                "{}{}{}",
-                &result[..start],
-                replacement,
-                &result[start + self_close + 2..]
-            );
-        }
-        // Then check for paired tags
-        else if let Some(rel_end) = rest.find("</ac:structured-macro>") {
-            let end = start + rel_end + 22;
-            let block = &result[start..end];
-            let replacement = process_macro(block);
-            result = format!("{}{}{}", &result[..start], replacement, &result[end..]);
+        // Parameters and attributes outside the node that owns them are
+        // configuration, not text.
+        if element.is("ac:parameter")
+            || element.is("ac:adf-parameter")
+            || element.is("ac:adf-attribute")
+        {
+            return;
         }
-        // Skip unprocessable
-        else {
-            result = format!("{}{}", &result[..start], &result[start + 20..]);
+        // Any other namespaced element is a wrapper (a layout cell, an
+        // inline comment marker): keep what it wraps.
+        if element.name.contains(':') {
+            out.push_str(&self.render(&element.children));
+            return;
         }
 
-        iterations += 1;
-    }
-    result
-}
-
-fn find_self_closing_end(s: &str) -> Option<usize> {
-    // Look for /> before finding </ac:structured-macro>
-    let close_tag = s.find("</ac:structured-macro>");
-    let self_close = s.find("/>");
-
-    match (self_close, close_tag) {
-        (Some(sc), Some(ct)) if sc < ct => Some(sc),
-        (Some(sc), None) => Some(sc),
-        _ => None,
-    }
-}
-
-fn process_tasks(html: &str) -> String {
-    let mut result = html.to_string();
-    let mut iterations = 0;
-    const MAX_ITERATIONS: usize = 1000;
-
-    while iterations < MAX_ITERATIONS {
-        let Some(start) = result.find("<ac:task-list>") else {
-            break;
-        };
-
-        if let Some(rel_end) = result[start..].find("</ac:task-list>") {
-            let end = start + rel_end + 15;
-            let block = &result[start..end];
-            let replacement = process_task_list(block);
-            result = format!("{}{}{}", &result[..start], replacement, &result[end..]);
-        } else {
-            break;
+        out.push('<');
+        out.push_str(&element.name);
+        for (name, value) in &element.attrs {
+            if !is_metadata_attr(name) {
+                out.push_str(&format!(" {}=\"{}\"", name, value.replace('"', "&quot;")));
+            }
         }
-        iterations += 1;
-    }
-    result
-}
-
-fn process_task_list(block: &str) -> String {
-    let mut tasks = Vec::new();
-    let mut search_start = 0;
-
-    while let Some(task_start) = block[search_start..].find("<ac:task>") {
-        let abs_start = search_start + task_start;
-        if let Some(rel_end) = block[abs_start..].find("</ac:task>") {
-            let task_block = &block[abs_start..abs_start + rel_end + 10];
-            tasks.push(process_task(task_block));
-            search_start = abs_start + rel_end + 10;
-        } else {
-            break;
+        out.push('>');
+        if element.children.is_empty() && is_void(&element.name) {
+            return;
         }
+        out.push_str(&self.render(&element.children));
+        out.push_str(&format!("</{}>", element.name));
     }
 
-    tasks.join("\n")
-}
-
-fn process_task(task: &str) -> String {
-    let status = if task.contains("<ac:task-status>complete</ac:task-status>") {
-        "[x]"
-    } else {
-        "[ ]"
-    };
+    /// Parks finished Markdown and returns the placeholder that stands for
+    /// it in the HTML.
+    pub fn block(&mut self, markdown: String) -> String {
+        self.blocks.push(markdown);
+        format!("{BLOCK_START}{}{BLOCK_END}", self.blocks.len() - 1)
+    }
 
-    let body = if let Some(start) = task.find("<ac:task-body>") {
-        let rest = &task[start + 14..];
-        if let Some(end) = rest.find("</ac:task-body>") {
-            strip_html_tags(&rest[..end]).trim().to_string()
-        } else {
-            String::new()
+    /// Swaps parked blocks back into htmd's output. A block on a line of its
+    /// own inside a quote or list keeps that line's prefix on every line;
+    /// anywhere else (a table cell) it has to stay on one line.
+    pub fn restore(&self, markdown: &str) -> String {
+        let mut out = String::with_capacity(markdown.len());
+        let mut rest = markdown;
+        while let Some(start) = rest.find(BLOCK_START) {
+            let Some(len) = rest[start..].find(BLOCK_END) else {
+                break;
+            };
+            let index: Option<usize> = rest[start + BLOCK_START.len_utf8()..start + len]
+                .parse()
+                .ok();
+            let Some(block) = index.and_then(|i| self.blocks.get(i)) else {
+                out.push_str(&rest[..start + len + BLOCK_END.len_utf8()]);
+                rest = &rest[start + len + BLOCK_END.len_utf8()..];
+                continue;
+            };
+            out.push_str(&rest[..start]);
+            let line_start = out.rfind('\n').map_or(0, |i| i + 1);
+            let prefix = out[line_start..].to_string();
+            if prefix.chars().all(|c| c == '>' || c.is_whitespace()) {
+                out.push_str(&block.replace('\n', &format!("\n{prefix}")));
+            } else {
+                out.push_str(&block.replace('\n', " "));
+            }
+            rest = &rest[start + len + BLOCK_END.len_utf8()..];
         }
-    } else {
-        String::new()
-    };
-
-    format!("- {} {}", status, body)
-}
-
-fn process_adf_extensions(html: &str) -> String {
-    let mut result = html.to_string();
-    let mut iterations = 0;
-    const MAX_ITERATIONS: usize = 1000;
-
-    while iterations < MAX_ITERATIONS {
-        let Some(start) = result.find("<ac:adf-extension>") else {
-            break;
-        };
-
-        if let Some(rel_end) = result[start..].find("</ac:adf-extension>") {
-            let end = start + rel_end + 19;
-            let block = &result[start..end];
-            let replacement = process_adf_extension(block);
-            result = format!("{}{}{}", &result[..start], replacement, &result[end..]);
-        } else {
-            break;
+        out.push_str(rest);
+        out
+    }
+
+    /// Renders nodes to Markdown on their own, blocks restored: what a macro
+    /// template's `{body}` stands for.
+    pub fn markdown(&mut self, nodes: &[Node]) -> String {
+        let html = self.render(nodes);
+        self.restore(&super::html_to_markdown(&html))
+            .trim()
+            .to_string()
+    }
+
+    /// Renders nodes to a single line of Markdown: a task's text.
+    fn inline(&mut self, nodes: &[Node]) -> String {
+        let html = self.render(nodes);
+        let markdown = self.restore(&super::html_to_markdown(&html));
+        markdown.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    fn task_list(&mut self, tasks: &[super::storage::Task], depth: usize) -> String {
+        let indent = "  ".repeat(depth);
+        let mut lines = Vec::new();
+        for task in tasks {
+            let (nested, body): (Vec<&Node>, Vec<&Node>) = task
+                .body
+                .iter()
+                .partition(|n| matches!(n, Node::TaskList(_)));
+            let body: Vec<Node> = body.into_iter().cloned().collect();
+            let status = if task.complete { 'x' } else { ' ' };
+            let text = self.inline(&body);
+            lines.push(
+                format!("{indent}- [{status}] {text}")
+                    .trim_end()
+                    .to_string(),
+            );
+            for list in nested {
+                if let Node::TaskList(subtasks) = list {
+                    lines.push(self.task_list(subtasks, depth + 1));
+                }
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// An `ac:adf-extension` wraps an ADF node Confluence has no storage
+    /// form for, with a storage fallback.
+    fn adf_extension(&mut self, extension: &Element) -> String {
+        let node_type = extension
+            .find(&|e| e.is("ac:adf-node"))
+            .and_then(|n| n.attr("type"));
+        let is_extension = node_type.as_deref() == Some("extension")
+            || extension
+                .find(&|e| e.attr("key").as_deref() == Some("extension-type"))
+                .is_some();
+
+        if is_extension {
+            let name = ["diagram-display-name", "diagramDisplayName", "diagram-name"]
+                .iter()
+                .find_map(|key| adf_parameter(extension, key));
+            if let Some(name) = name {
+                return escape_html(&format!("[Draw.io: {}]", name));
+            }
+            if let Some(title) = adf_attribute(extension, "extension-title") {
+                return escape_html(&format!("[{}]", title));
+            }
+            return "[Embedded Diagram]".into();
         }
-        iterations += 1;
-    }
-    result
-}
 
-fn process_adf_extension(block: &str) -> String {
-    // Check if this is an extension type (e.g., draw.io diagram)
-    if block.contains("type=\"extension\"") || block.contains("extension-type") {
-        // Extract diagram name if available
-        if let Some(name) = extract_adf_param_value(block, "diagram-display-name")
-            .or_else(|| extract_adf_param_value(block, "diagramDisplayName"))
-            .or_else(|| extract_adf_param_value(block, "diagram-name"))
+        if let Some(content) = extension.find(&|e| e.is("ac:adf-content"))
+            && !text_content(&content.children).trim().is_empty()
         {
-            return format!("[Draw.io: {}]", name);
-        }
-        // Check for extension title
-        if let Some(title) = extract_adf_attribute(block, "extension-title") {
-            return format!("[{}]", title);
-        }
-        return "[Embedded Diagram]".into();
-    }
-
-    // Get panel type from adf-attribute
-    let panel_type = extract_adf_attribute(block, "panel-type");
-
-    // Try adf-content first
-    if let Some(content) = extract_adf_content(block) {
-        let text = strip_html_tags(&content).trim().to_string();
-        if !text.is_empty() {
-            return match panel_type.as_deref() {
-                Some("note") => format!("> **NOTE**: {}", text),
-                Some("info") => format!("> **INFO**: {}", text),
-                Some("warning") => format!("> **WARNING**: {}", text),
-                Some("error") => format!("> **ERROR**: {}", text),
-                Some("success") => format!("> **SUCCESS**: {}", text),
-                _ => text,
+            return match adf_attribute(extension, "panel-type").as_deref() {
+                Some(kind @ ("note" | "info" | "warning" | "error" | "success")) => {
+                    panel(self, &kind.to_uppercase(), &content.children)
+                }
+                _ => self.render(&content.children),
             };
         }
-    }
 
-    // Fallback to adf-fallback
-    if let Some(fallback) = extract_adf_fallback(block) {
-        return strip_html_tags(&fallback).trim().to_string();
+        match extension.find(&|e| e.is("ac:adf-fallback")) {
+            Some(fallback) => self.render(&fallback.children),
+            None => String::new(),
+        }
     }
-
-    String::new()
 }
 
-fn extract_adf_param_value(block: &str, key: &str) -> Option<String> {
-    // Look for patterns like: <ac:adf-parameter key="diagram-name"><ac:adf-parameter key="value">NAME</ac:adf-parameter>
-    let key_pattern = format!("key=\"{}\"", key);
-    let key_pos = block.find(&key_pattern)?;
-
-    // Find the value parameter after this key
-    let after_key = &block[key_pos..];
-    let value_start = after_key.find("key=\"value\"")? + 11;
-    let after_value = &after_key[value_start..];
-
-    // Find the > that closes the value tag
-    let content_start = after_value.find('>')? + 1;
-    let content = &after_value[content_start..];
-
-    // Find the closing </ac:adf-parameter>
-    let content_end = content.find("</ac:adf-parameter>")?;
-    let value = content[..content_end].trim();
-
-    if value.is_empty() {
-        None
-    } else {
-        Some(value.to_string())
-    }
+fn is_void(name: &str) -> bool {
+    ["br", "hr", "img", "col", "wbr", "input", "area", "source"]
+        .iter()
+        .any(|v| name.eq_ignore_ascii_case(v))
 }
 
-fn extract_adf_attribute(block: &str, key: &str) -> Option<String> {
-    let pattern = format!("key=\"{}\"", key);
-    let attr_start = block.find(&pattern)?;
-
-    // Find the closing > of this tag
-    let tag_end = block[attr_start..].find('>')?;
-    let content_start = attr_start + tag_end + 1;
-
-    // Find the closing </ac:adf-attribute>
-    let close_tag = "</ac:adf-attribute>";
-    let close_start = block[content_start..].find(close_tag)?;
-
-    Some(block[content_start..content_start + close_start].to_string())
+fn adf_attribute(extension: &Element, key: &str) -> Option<String> {
+    extension
+        .find(&|e| e.is("ac:adf-attribute") && e.attr("key").as_deref() == Some(key))
+        .map(|a| text_content(&a.children).trim().to_string())
 }
 
-fn extract_adf_content(block: &str) -> Option<String> {
-    let start = block.find("<ac:adf-content>")? + 16;
-    let rest = &block[start..];
-    let end = rest.find("</ac:adf-content>")?;
-    Some(rest[..end].to_string())
+/// The value of an ADF parameter, which holds it in a nested `value`
+/// parameter.
+fn adf_parameter(extension: &Element, key: &str) -> Option<String> {
+    let parameter =
+        extension.find(&|e| e.is("ac:adf-parameter") && e.attr("key").as_deref() == Some(key))?;
+    let value = parameter
+        .find(&|e| e.is("ac:adf-parameter") && e.attr("key").as_deref() == Some("value"))?;
+    let text = text_content(&value.children).trim().to_string();
+    (!text.is_empty()).then_some(text)
 }
 
-fn extract_adf_fallback(block: &str) -> Option<String> {
-    let start = block.find("<ac:adf-fallback>")? + 17;
-    let rest = &block[start..];
-    let end = rest.find("</ac:adf-fallback>")?;
-    Some(rest[..end].to_string())
+fn emoticon_shortname(name: &str) -> &str {
+    match name {
+        "smile" | "smiley" => ":)",
+        "sad" => ":(",
+        "wink" => ";)",
+        "laugh" => ":D",
+        "thumbs-up" => "(y)",
+        "thumbs-down" => "(n)",
+        "tick" | "check" => "[x]",
+        "cross" | "error" => "[!]",
+        "warning" => "[!]",
+        "information" | "info" => "(i)",
+        "question" => "(?)",
+        "light-on" | "idea" => "(!)",
+        "star" => "(*)",
+        "heart" => "<3",
+        _ => name,
+    }
 }
 
-fn remove_remaining_tags(html: &str) -> String {
-    let mut result = html.to_string();
-
-    // First, remove paired tags with their content (like ac:parameter)
-    let paired_tags = ["ac:parameter", "ac:adf-parameter", "ac:adf-parameter-value"];
-    for tag in paired_tags {
-        let mut iterations = 0;
-        const MAX_ITERATIONS: usize = 1000;
-
-        while iterations < MAX_ITERATIONS {
-            let open_tag = format!("<{}", tag);
-            let close_tag = format!("</{}>", tag);
-
-            let Some(start) = result.find(&open_tag) else {
-                break;
-            };
-
-            // Find the closing tag
-            if let Some(rel_close) = result[start..].find(&close_tag) {
-                let end = start + rel_close + close_tag.len();
-                result = format!("{}{}", &result[..start], &result[end..]);
-            } else {
-                // Self-closing or malformed - just remove the opening tag
-                if let Some(rel_end) = result[start..].find('>') {
-                    let end = start + rel_end + 1;
-                    result = format!("{}{}", &result[..start], &result[end..]);
-                } else {
-                    break;
-                }
-            }
-            iterations += 1;
-        }
+/// An attached image the page's attachment list knows is shown from where
+/// it lives; one it does not know keeps its name, the one thing the storage
+/// says about it.
+fn image_text(image: &Image, attachments: &Attachments) -> String {
+    match &image.resource {
+        Some(Resource::Attachment { filename }) => match attachments.find(None, Some(filename)) {
+            Some(found) => format!("![{}]({})", filename, found.href),
+            None => format!("[Image: {}]", filename),
+        },
+        Some(Resource::Url(url)) => format!("![Image]({})", url),
+        _ => match image.alt.as_deref() {
+            Some(alt) if !alt.is_empty() => format!("[Image: {}]", alt),
+            _ => "[Image]".into(),
+        },
     }
+}
 
-    // Then remove remaining unpaired tags
-    let patterns = ["<ac:", "<ri:", "</ac:", "</ri:"];
-    for pattern in patterns {
-        let mut iterations = 0;
-        const MAX_ITERATIONS: usize = 1000;
-
-        while iterations < MAX_ITERATIONS {
-            let Some(start) = result.find(pattern) else {
-                break;
-            };
+fn link_text(link: &Link, names: &UserNames, attachments: &Attachments) -> String {
+    let body = match &link.body {
+        LinkBody::Rich(nodes) => text_content(nodes),
+        LinkBody::Plain(text) => text.clone(),
+        LinkBody::None => String::new(),
+    };
+    let display = Some(body.trim().to_string())
+        .filter(|b| !b.is_empty())
+        .or_else(|| match &link.resource {
+            Some(Resource::Page { title, .. }) => Some(title.clone()),
+            Some(Resource::User { account_id }) => names.get(account_id).map(|n| format!("@{}", n)),
+            Some(Resource::Attachment { filename }) => attachments
+                .find(None, Some(filename))
+                .map(|_| filename.clone()),
+            _ => None,
+        });
+
+    let target = match &link.resource {
+        Some(Resource::Page {
+            space_key: Some(space),
+            title,
+        }) => Some(format!("page:{}/{}", space, title)),
+        Some(Resource::Page {
+            space_key: None,
+            title,
+        }) => Some(format!("page:{}", title)),
+        Some(Resource::User { account_id }) => Some(format!("@{}", account_id)),
+        Some(Resource::Attachment { filename }) => Some(
+            attachments
+                .find(None, Some(filename))
+                .map(|found| found.href.clone())
+                .unwrap_or_else(|| format!("attachment:{}", filename)),
+        ),
+        Some(Resource::Url(url)) => Some(url.clone()),
+        None => link.anchor.as_ref().map(|a| format!("#{}", a)),
+    };
 
-            if let Some(rel_end) = result[start..].find('>') {
-                let end = start + rel_end + 1;
-                result = format!("{}{}", &result[..start], &result[end..]);
-            } else {
-                break;
-            }
-            iterations += 1;
-        }
+    match (display, target) {
+        (Some(text), Some(url)) => format!("[{}]({})", text, url),
+        (Some(text), None) => text,
+        (None, Some(url)) => format!("[{}]({})", url, url),
+        (None, None) => String::new(),
     }
-    result
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::storage::parse;
+    use super::super::{confluence_to_markdown, confluence_to_markdown_with};
     use super::*;
+    use crate::markdown::Attachment;
+
+    fn html(storage: &str) -> String {
+        Renderer::new(
+            &UserNames::default(),
+            &Attachments::default(),
+            &HashMap::new(),
+        )
+        .render(&parse(storage))
+    }
 
     #[test]
     fn test_emoticon() {
         let html = r#"<ac:emoticon ac:name="smile" />"#;
-        assert_eq!(clean_confluence_html(html), ":)");
+        assert_eq!(confluence_to_markdown(html), ":)");
     }
 
     #[test]
     fn test_image_with_filename() {
         let html = r#"<ac:image><ri:attachment ri:filename="diagram.png"/></ac:image>"#;
-        assert_eq!(clean_confluence_html(html), "[Image: diagram.png]");
+        assert_eq!(confluence_to_markdown(html), "[Image: diagram.png]");
     }
 
     #[test]
     fn test_image_with_url() {
         let html = r#"<ac:image><ri:url ri:value="https://example.com/img.png"/></ac:image>"#;
         assert_eq!(
-            clean_confluence_html(html),
+            confluence_to_markdown(html),
             "![Image](https://example.com/img.png)"
         );
     }
@@ -590,52 +386,130 @@ mod tests {
     #[test]
     fn test_link_with_body() {
         let html = r#"<ac:link><ri:page ri:content-title="My Page"/><ac:link-body>Click here</ac:link-body></ac:link>"#;
-        assert_eq!(clean_confluence_html(html), "[Click here](page:My Page)");
+        assert_eq!(confluence_to_markdown(html), "[Click here](page:My Page)");
     }
 
     #[test]
     fn test_link_with_plain_text() {
         let html = r#"<ac:link><ri:page ri:content-title="My Page"/><ac:plain-text-link-body><![CDATA[Link text]]></ac:plain-text-link-body></ac:link>"#;
-        assert_eq!(clean_confluence_html(html), "[Link text](page:My Page)");
+        assert_eq!(confluence_to_markdown(html), "[Link text](page:My Page)");
     }
 
     #[test]
     fn test_link_to_user() {
         let html = r#"<ac:link><ri:user ri:account-id="user123"/></ac:link>"#;
-        assert_eq!(clean_confluence_html(html), "[@user123](@user123)");
+        assert_eq!(confluence_to_markdown(html), "[@user123](@user123)");
+    }
+
+    #[test]
+    fn test_link_to_user_with_a_known_name() {
+        let html = r#"<p>Ask <ac:link><ri:user ri:account-id="user123"/></ac:link></p>"#;
+        let mut names = UserNames::default();
+        names.insert("user123", "Jane Doe");
+        assert_eq!(
+            confluence_to_markdown_with(html, &names, &Attachments::default(), &HashMap::new()),
+            "Ask [@Jane Doe](@user123)"
+        );
+    }
+
+    fn attached(filename: &str, href: &str) -> Attachments {
+        let mut attachments = Attachments::default();
+        attachments.insert(Attachment {
+            id: "att1".into(),
+            filename: filename.into(),
+            media_type: None,
+            href: href.into(),
+        });
+        attachments
+    }
+
+    #[test]
+    fn test_image_in_the_attachment_list_is_shown_from_it() {
+        let html = r#"<ac:image><ri:attachment ri:filename="diagram.png"/></ac:image>"#;
+        let attachments = attached("diagram.png", "https://x/download/diagram.png");
+        assert_eq!(
+            confluence_to_markdown_with(html, &UserNames::default(), &attachments, &HashMap::new()),
+            "![diagram.png](https://x/download/diagram.png)"
+        );
+    }
+
+    #[test]
+    fn test_link_to_an_attachment_in_the_list() {
+        let html = r#"<p><ac:link><ri:attachment ri:filename="spec.pdf"/></ac:link></p>"#;
+        let attachments = attached("spec.pdf", "media/spec.pdf");
+        assert_eq!(
+            confluence_to_markdown_with(html, &UserNames::default(), &attachments, &HashMap::new()),
+            "[spec.pdf](media/spec.pdf)"
+        );
+        assert_eq!(
+            confluence_to_markdown(html),
+            "[attachment:spec.pdf](attachment:spec.pdf)"
+        );
+    }
+
+    #[test]
+    fn test_link_to_anchor() {
+        let html = r#"<ac:link ac:anchor="setup"><ac:plain-text-link-body><![CDATA[Setup]]></ac:plain-text-link-body></ac:link>"#;
+        assert_eq!(confluence_to_markdown(html), "[Setup](#setup)");
     }
 
     #[test]
     fn test_self_closing_macro() {
         let html = r#"<ac:structured-macro ac:name="toc" /><p>After</p>"#;
-        let result = clean_confluence_html(html);
-        assert!(result.contains("<p>After</p>"));
+        assert_eq!(self::html(html), "<p>After</p>");
     }
 
     #[test]
     fn test_task_list() {
         let html = r#"<ac:task-list>
             <ac:task><ac:task-status>incomplete</ac:task-status><ac:task-body>Todo item</ac:task-body></ac:task>
-            <ac:task><ac:task-status>complete</ac:task-status><ac:task-body>Done item</ac:task-body></ac:task>
+            <ac:task><ac:task-status>complete</ac:task-status><ac:task-body><strong>Done</strong> item</ac:task-body></ac:task>
         </ac:task-list>"#;
-        let result = clean_confluence_html(html);
-        assert!(result.contains("- [ ] Todo item"));
-        assert!(result.contains("- [x] Done item"));
+        assert_eq!(
+            confluence_to_markdown(html),
+            "- [ ] Todo item\n- [x] **Done** item"
+        );
+    }
+
+    #[test]
+    fn test_nested_task_list() {
+        let html = r#"<ac:task-list><ac:task><ac:task-status>incomplete</ac:task-status><ac:task-body>Parent<ac:task-list><ac:task><ac:task-status>complete</ac:task-status><ac:task-body>Child</ac:task-body></ac:task></ac:task-list></ac:task-body></ac:task></ac:task-list>"#;
+        assert_eq!(confluence_to_markdown(html), "- [ ] Parent\n  - [x] Child");
+    }
+
+    #[test]
+    fn test_task_list_inside_a_panel_stays_quoted() {
+        let html = r#"<ac:structured-macro ac:name="note"><ac:rich-text-body><p>Before release:</p><ac:task-list><ac:task><ac:task-status>incomplete</ac:task-status><ac:task-body>Tag</ac:task-body></ac:task><ac:task><ac:task-status>incomplete</ac:task-status><ac:task-body>Publish</ac:task-body></ac:task></ac:task-list></ac:rich-text-body></ac:structured-macro>"#;
+        assert_eq!(
+            confluence_to_markdown(html),
+            "> **NOTE**: Before release:\n> \n> - [ ] Tag\n> - [ ] Publish"
+        );
     }
 
     #[test]
     fn test_adf_extension_panel() {
         let html = r#"<ac:adf-extension><ac:adf-node type="panel"><ac:adf-attribute key="panel-type">note</ac:adf-attribute><ac:adf-content><p>Content</p></ac:adf-content></ac:adf-node></ac:adf-extension>"#;
-        let result = clean_confluence_html(html);
-        assert!(result.contains("> **NOTE**"));
-        assert!(result.contains("Content"));
-        assert!(!result.contains("panel-type"));
+        assert_eq!(confluence_to_markdown(html), "> **NOTE**: Content");
+    }
+
+    #[test]
+    fn test_adf_extension_diagram() {
+        let html = r#"<ac:adf-extension><ac:adf-node type="extension"><ac:adf-parameter key="macro-params"><ac:adf-parameter key="diagram-display-name"><ac:adf-parameter key="value">Flow</ac:adf-parameter></ac:adf-parameter></ac:adf-parameter></ac:adf-node><ac:adf-fallback><p>fallback</p></ac:adf-fallback></ac:adf-extension>"#;
+        assert_eq!(confluence_to_markdown(html), "[Draw.io: Flow]");
     }
 
     #[test]
-    fn test_remove_remaining_tags() {
+    fn test_unknown_namespaced_elements_keep_their_text() {
         let html = r#"<ac:unknown>text</ac:unknown><ri:unknown />"#;
-        let result = clean_confluence_html(html);
-        assert_eq!(result, "text");
+        assert_eq!(self::html(html), "text");
+    }
+
+    #[test]
+    fn test_editor_metadata_is_not_written() {
+        let html = r#"<table data-layout="wide" ac:local-id="1"><tbody><tr><td data-highlight-colour="red" colspan="2">x</td></tr></tbody></table>"#;
+        assert_eq!(
+            self::html(html),
+            r#"<table><tbody><tr><td colspan="2">x</td></tr></tbody></table>"#
+        );
     }
 }
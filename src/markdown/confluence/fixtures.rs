@@ -0,0 +1,102 @@
+//! Storage documents shaped like the ones real pages contain, where the
+//! string-scanning reader used to fall over: macros inside macros and
+//! tables, attributes with `>` in them, CDATA holding markup, markup that is
+//! not well-formed, and the residue diagram macros leave behind.
+
+pub(super) struct Fixture {
+    pub name: &'static str,
+    pub storage: &'static str,
+    /// Markdown the page must contain.
+    pub contains: &'static [&'static str],
+    /// Text that must not survive into the Markdown.
+    pub absent: &'static [&'static str],
+}
+
+pub(super) const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "code macro inside an expand",
+        storage: r#"<ac:structured-macro ac:name="expand" ac:schema-version="1" ac:macro-id="a1"><ac:parameter ac:name="title">Show config</ac:parameter><ac:rich-text-body><ac:structured-macro ac:name="code" ac:schema-version="1" ac:macro-id="b2"><ac:parameter ac:name="language">yaml</ac:parameter><ac:plain-text-body><![CDATA[server:
+  port: 8080]]></ac:plain-text-body></ac:structured-macro><p>Restart afterwards.</p></ac:rich-text-body></ac:structured-macro>"#,
+        contains: &[
+            "**Show config**",
+            "```yaml\nserver:\n  port: 8080\n```",
+            "Restart afterwards.",
+        ],
+        absent: &["a1", "b2"],
+    },
+    Fixture {
+        name: "panel inside a panel",
+        storage: r#"<ac:structured-macro ac:name="info"><ac:rich-text-body><p>Outer</p><ac:structured-macro ac:name="warning"><ac:rich-text-body><p>Inner</p></ac:rich-text-body></ac:structured-macro><p>Outer again</p></ac:rich-text-body></ac:structured-macro>"#,
+        contains: &[
+            "> **INFO**: Outer",
+            "> > **WARNING**: Inner",
+            "> Outer again",
+        ],
+        absent: &[],
+    },
+    Fixture {
+        name: "macros inside table cells",
+        storage: r#"<table data-layout="default" ac:local-id="t1"><colgroup><col style="width: 100.0px;" /><col /></colgroup><tbody><tr><th><p>Ticket</p></th><th><p>State</p></th></tr><tr><td><p><ac:structured-macro ac:name="jira"><ac:parameter ac:name="key">OPS-7</ac:parameter></ac:structured-macro></p></td><td><p><ac:structured-macro ac:name="status"><ac:parameter ac:name="colour">Yellow</ac:parameter><ac:parameter ac:name="title">In review</ac:parameter></ac:structured-macro></p></td></tr></tbody></table>"#,
+        contains: &["Ticket", "[JIRA: OPS-7]", "[WARN] IN REVIEW"],
+        absent: &["t1", "width"],
+    },
+    Fixture {
+        name: "attribute values containing >",
+        storage: r#"<p><a href="https://example.com/search?q=a>b&amp;sort=asc" title="x > y">search</a> then <ac:link><ri:page ri:content-title="A &gt; B" /></ac:link></p>"#,
+        contains: &[
+            "[search](https://example.com/search?q=a>b&sort=asc \"x > y\")",
+            "[A > B](page:A > B)",
+        ],
+        absent: &["q=a>b&amp;"],
+    },
+    Fixture {
+        name: "CDATA holding markup and a split terminator",
+        storage: r#"<ac:structured-macro ac:name="code"><ac:parameter ac:name="language">xml</ac:parameter><ac:plain-text-body><![CDATA[<config><item a="1"/>]]]]><![CDATA[></config>]]></ac:plain-text-body></ac:structured-macro>"#,
+        contains: &["```xml\n<config><item a=\"1\"/>]]></config>\n```"],
+        absent: &["CDATA"],
+    },
+    Fixture {
+        name: "unclosed and stray tags",
+        storage: r#"<p>First <strong>bold<p>Second</div></span><ul><li>one<li>two</ul><p>Last"#,
+        contains: &["First **bold", "Second", "one", "two", "Last"],
+        absent: &["</div>", "</span>"],
+    },
+    Fixture {
+        name: "entities",
+        storage: r#"<p>R&amp;D &lt;team&gt; &ndash; caf&eacute; &#x2713; &#10003;</p><ac:structured-macro ac:name="status"><ac:parameter ac:name="title">Q&amp;A</ac:parameter></ac:structured-macro>"#,
+        contains: &["R&D", "<team>", "–", "✓ ✓", "[STATUS] Q&A"],
+        absent: &["&amp;", "&#x2713;"],
+    },
+    Fixture {
+        name: "include macro naming a page",
+        storage: r#"<p>Shared steps:</p><ac:structured-macro ac:name="include" ac:schema-version="1"><ac:parameter ac:name=""><ac:link><ri:page ri:space-key="OPS" ri:content-title="Deploy &amp; Verify" /></ac:link></ac:parameter></ac:structured-macro>"#,
+        contains: &["Shared steps:", "[Include: Deploy & Verify]"],
+        absent: &["OPS"],
+    },
+    Fixture {
+        name: "nested task lists with marks and mentions",
+        storage: r#"<ac:task-list><ac:task><ac:task-id>1</ac:task-id><ac:task-status>incomplete</ac:task-status><ac:task-body><strong>Release</strong> for <ac:link><ri:user ri:account-id="557058:abc" /></ac:link><ac:task-list><ac:task><ac:task-id>2</ac:task-id><ac:task-status>complete</ac:task-status><ac:task-body>Tag <code>v1.2</code></ac:task-body></ac:task><ac:task><ac:task-id>3</ac:task-id><ac:task-status>incomplete</ac:task-status><ac:task-body>Publish</ac:task-body></ac:task></ac:task-list></ac:task-body></ac:task></ac:task-list>"#,
+        contains: &[
+            "- [ ] **Release** for [@557058:abc](@557058:abc)\n  - [x] Tag `v1.2`\n  - [ ] Publish",
+        ],
+        absent: &[],
+    },
+    Fixture {
+        name: "adf extension with a fallback",
+        storage: r#"<ac:adf-extension><ac:adf-node type="decision-list"><ac:adf-attribute key="local-id">d1</ac:adf-attribute><ac:adf-node type="decision-item"><ac:adf-attribute key="state">DECIDED</ac:adf-attribute><ac:adf-content>Use Postgres</ac:adf-content></ac:adf-node></ac:adf-node><ac:adf-fallback><ul><li>Use Postgres</li></ul></ac:adf-fallback></ac:adf-extension>"#,
+        contains: &["Use Postgres"],
+        absent: &["DECIDED", "d1"],
+    },
+    Fixture {
+        name: "draw.io residue",
+        storage: r#"<p>Architecture:</p><ac:structured-macro ac:name="drawio"><ac:parameter ac:name="diagramDisplayName">System view</ac:parameter><ac:parameter ac:name="contentId">991</ac:parameter></ac:structured-macro><mxfile host="app"><diagram id="x"><mxGraphModel dx="1"><root><mxCell id="0" value="&lt;b&gt;API&lt;/b&gt;"/></root></mxGraphModel></diagram></mxfile><p>End</p>"#,
+        contains: &["Architecture:", "[Draw.io: System view]", "End"],
+        absent: &["mxCell", "991", "API"],
+    },
+    Fixture {
+        name: "layout sections",
+        storage: r#"<ac:layout><ac:layout-section ac:type="two_equal" ac:breakout-mode="default"><ac:layout-cell><h2>Left</h2><p>L</p></ac:layout-cell><ac:layout-cell><h2>Right</h2><p>R <ac:emoticon ac:name="tick" ac:emoji-shortname=":check_mark:" /></p></ac:layout-cell></ac:layout-section></ac:layout>"#,
+        contains: &["## Left", "## Right", "R [x]"],
+        absent: &["two_equal"],
+    },
+];
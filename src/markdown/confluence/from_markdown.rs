@@ -0,0 +1,479 @@
+//! Markdown → Confluence storage format, the inverse of `confluence_to_markdown`.
+//!
+//! Plain Markdown constructs become their XHTML elements; the two that the
+//! reader turns into Markdown conventions go back to the macros they came
+//! from. A fenced block becomes the `code` structured macro (`format_code_block`
+//! reads it back as a fence), and a callout — either a GitHub `> [!NOTE]` alert
+//! or the `> **INFO**: …` quote the reader writes for one — becomes the matching
+//! panel macro. Raw HTML passes through untouched, so markup Markdown has no
+//! syntax for (`<u>`, a `<span style>`) can still be written inline.
+
+use pulldown_cmark::{BlockQuoteKind, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+/// Convert a Markdown string into a Confluence storage-format body.
+///
+/// The result is well-formed XHTML — void elements are self-closed and text is
+/// entity-escaped — because Confluence rejects storage that does not parse as
+/// XML. A soft line break becomes `<br />`, as it does on the Jira side.
+pub fn markdown_to_storage(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_GFM;
+
+    let mut writer = Writer::default();
+    for event in Parser::new_ext(markdown, options) {
+        writer.event(event);
+    }
+    writer.out
+}
+
+/// A list whose items are only known to be tasks once each item's marker has
+/// been seen, so its markup is rewritten when it closes.
+struct ListFrame {
+    start: usize,
+    ordered: bool,
+    /// Per item: its byte range in `out` and its checkbox, if it had one.
+    items: Vec<(usize, usize, Option<bool>)>,
+}
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+    /// Where each open blockquote's content starts, and its alert kind.
+    quotes: Vec<(usize, Option<BlockQuoteKind>)>,
+    lists: Vec<ListFrame>,
+    /// `Some` while inside a fenced or indented block: (language, code).
+    code: Option<(String, String)>,
+    /// `Some` while inside an image: (destination, alt text so far).
+    image: Option<(String, String)>,
+    in_table_head: bool,
+    next_task_id: usize,
+}
+
+impl Writer {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => {
+                if let Some((_, code)) = self.code.as_mut() {
+                    code.push_str(&text);
+                } else if let Some((_, alt)) = self.image.as_mut() {
+                    alt.push_str(&text);
+                } else {
+                    self.out.push_str(&escape(&text));
+                }
+            }
+            Event::Code(text) => {
+                self.out.push_str("<code>");
+                self.out.push_str(&escape(&text));
+                self.out.push_str("</code>");
+            }
+            Event::SoftBreak | Event::HardBreak => self.out.push_str("<br />"),
+            Event::Rule => self.out.push_str("<hr />"),
+            Event::Html(html) | Event::InlineHtml(html) => self.out.push_str(&html),
+            Event::TaskListMarker(checked) => {
+                if let Some(item) = self.lists.last_mut().and_then(|l| l.items.last_mut()) {
+                    item.2 = Some(checked);
+                }
+            }
+            Event::InlineMath(text) | Event::DisplayMath(text) => {
+                self.out.push_str(&escape(&text));
+            }
+            Event::FootnoteReference(label) => {
+                self.out.push_str(&escape(&format!("[^{}]", label)));
+            }
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.out.push_str("<p>"),
+            Tag::Heading { level, .. } => self.out.push_str(&format!("<{}>", level)),
+            Tag::BlockQuote(kind) => self.quotes.push((self.out.len(), kind)),
+            Tag::CodeBlock(kind) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .map(str::to_string)
+                        .unwrap_or_default(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((language, String::new()));
+            }
+            Tag::List(start) => {
+                self.lists.push(ListFrame {
+                    start: self.out.len(),
+                    ordered: start.is_some(),
+                    items: Vec::new(),
+                });
+                match start {
+                    Some(1) => self.out.push_str("<ol>"),
+                    Some(n) => self.out.push_str(&format!("<ol start=\"{}\">", n)),
+                    None => self.out.push_str("<ul>"),
+                }
+            }
+            Tag::Item => {
+                let start = self.out.len();
+                if let Some(list) = self.lists.last_mut() {
+                    list.items.push((start, start, None));
+                }
+                self.out.push_str("<li>");
+            }
+            Tag::Table(_) => self.out.push_str("<table><tbody>"),
+            Tag::TableHead => {
+                self.in_table_head = true;
+                self.out.push_str("<tr>");
+            }
+            Tag::TableRow => self.out.push_str("<tr>"),
+            Tag::TableCell => {
+                self.out
+                    .push_str(if self.in_table_head { "<th>" } else { "<td>" });
+            }
+            Tag::Emphasis => self.out.push_str("<em>"),
+            Tag::Strong => self.out.push_str("<strong>"),
+            Tag::Strikethrough => self.out.push_str("<del>"),
+            Tag::Link {
+                dest_url, title, ..
+            } => {
+                self.out
+                    .push_str(&format!("<a href=\"{}\"", escape_attr(&dest_url)));
+                if !title.is_empty() {
+                    self.out
+                        .push_str(&format!(" title=\"{}\"", escape_attr(&title)));
+                }
+                self.out.push('>');
+            }
+            Tag::Image { dest_url, .. } => {
+                self.image = Some((dest_url.to_string(), String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.out.push_str("</p>"),
+            TagEnd::Heading(level) => self.out.push_str(&format!("</{}>", level)),
+            TagEnd::BlockQuote(_) => self.close_quote(),
+            TagEnd::CodeBlock => {
+                if let Some((language, code)) = self.code.take() {
+                    self.out.push_str(&code_macro(&language, &code));
+                }
+            }
+            TagEnd::List(_) => self.close_list(),
+            TagEnd::Item => {
+                self.out.push_str("</li>");
+                let end = self.out.len();
+                if let Some(item) = self.lists.last_mut().and_then(|l| l.items.last_mut()) {
+                    item.1 = end;
+                }
+            }
+            TagEnd::Table => self.out.push_str("</tbody></table>"),
+            TagEnd::TableHead => {
+                self.in_table_head = false;
+                self.out.push_str("</tr>");
+            }
+            TagEnd::TableRow => self.out.push_str("</tr>"),
+            TagEnd::TableCell => {
+                self.out
+                    .push_str(if self.in_table_head { "</th>" } else { "</td>" });
+            }
+            TagEnd::Emphasis => self.out.push_str("</em>"),
+            TagEnd::Strong => self.out.push_str("</strong>"),
+            TagEnd::Strikethrough => self.out.push_str("</del>"),
+            TagEnd::Link => self.out.push_str("</a>"),
+            TagEnd::Image => {
+                if let Some((destination, alt)) = self.image.take() {
+                    self.out.push_str(&image(&destination, &alt));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// A quote's content is already written; wrap it as a panel when it is a
+    /// callout and as a `<blockquote>` otherwise.
+    fn close_quote(&mut self) {
+        let Some((start, kind)) = self.quotes.pop() else {
+            return;
+        };
+        let inner = self.out.split_off(start);
+        let wrapped = match kind {
+            Some(kind) => panel_macro(alert_panel(kind), None, &inner),
+            None => match panel_convention(&inner) {
+                Some((panel, title, body)) => panel_macro(&panel, title.as_deref(), &body),
+                None => format!("<blockquote>{}</blockquote>", inner),
+            },
+        };
+        self.out.push_str(&wrapped);
+    }
+
+    /// A list whose every item carried a checkbox is a Confluence task list.
+    /// In a list that mixes the two the checkbox has no storage shape, so it
+    /// is kept as text rather than dropped.
+    fn close_list(&mut self) {
+        let Some(list) = self.lists.pop() else {
+            return;
+        };
+        self.out
+            .push_str(if list.ordered { "</ol>" } else { "</ul>" });
+        let tasks = list.items.iter().filter(|i| i.2.is_some()).count();
+        if tasks == 0 {
+            return;
+        }
+
+        let html = self.out.split_off(list.start);
+        let all_tasks = tasks == list.items.len();
+        let mut rebuilt = String::new();
+        let mut cursor = 0;
+        if all_tasks {
+            rebuilt.push_str("<ac:task-list>");
+        }
+        for (start, end, checked) in &list.items {
+            let (start, end) = (start - list.start, end - list.start);
+            if !all_tasks {
+                rebuilt.push_str(&html[cursor..start]);
+            }
+            let item = &html[start..end];
+            let body = item
+                .strip_prefix("<li>")
+                .and_then(|i| i.strip_suffix("</li>"))
+                .unwrap_or(item);
+            match (checked, all_tasks) {
+                (Some(done), true) => {
+                    self.next_task_id += 1;
+                    let status = if *done { "complete" } else { "incomplete" };
+                    rebuilt.push_str(&format!(
+                        "<ac:task><ac:task-id>{}</ac:task-id><ac:task-status>{}</ac:task-status><ac:task-body>{}</ac:task-body></ac:task>",
+                        self.next_task_id, status, body
+                    ));
+                }
+                (Some(done), false) => {
+                    let checkbox = if *done { "[x] " } else { "[ ] " };
+                    rebuilt.push_str(&format!("<li>{}{}</li>", checkbox, body));
+                }
+                (None, _) => rebuilt.push_str(item),
+            }
+            cursor = end;
+        }
+        if all_tasks {
+            rebuilt.push_str("</ac:task-list>");
+        } else {
+            rebuilt.push_str(&html[cursor..]);
+        }
+        self.out.push_str(&rebuilt);
+    }
+}
+
+fn alert_panel(kind: BlockQuoteKind) -> &'static str {
+    match kind {
+        BlockQuoteKind::Note => "info",
+        BlockQuoteKind::Tip => "tip",
+        BlockQuoteKind::Important => "note",
+        BlockQuoteKind::Warning | BlockQuoteKind::Caution => "warning",
+    }
+}
+
+/// Recognise the quote `format_panel` writes — `> **INFO**: body` or
+/// `> **INFO - Title**: body` — as it arrives here rendered:
+/// `<p><strong>INFO</strong>: body</p>`.
+fn panel_convention(inner: &str) -> Option<(String, Option<String>, String)> {
+    let rest = inner.strip_prefix("<p><strong>")?;
+    let (label, rest) = rest.split_once("</strong>:")?;
+    let (panel, title) = match label.split_once(" - ") {
+        Some((panel, title)) => (panel, Some(title.to_string())),
+        None => (label, None),
+    };
+    if !matches!(panel, "INFO" | "NOTE" | "WARNING" | "TIP" | "ERROR") {
+        return None;
+    }
+    let body = format!("<p>{}", rest.trim_start());
+    Some((panel.to_lowercase(), title, body))
+}
+
+fn panel_macro(panel: &str, title: Option<&str>, body: &str) -> String {
+    let title = title
+        .map(|t| format!("<ac:parameter ac:name=\"title\">{}</ac:parameter>", t))
+        .unwrap_or_default();
+    format!(
+        "<ac:structured-macro ac:name=\"{}\">{}<ac:rich-text-body>{}</ac:rich-text-body></ac:structured-macro>",
+        panel, title, body
+    )
+}
+
+fn code_macro(language: &str, code: &str) -> String {
+    let language = if language.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<ac:parameter ac:name=\"language\">{}</ac:parameter>",
+            escape(language)
+        )
+    };
+    let code = code.strip_suffix('\n').unwrap_or(code);
+    format!(
+        "<ac:structured-macro ac:name=\"code\">{}<ac:plain-text-body><![CDATA[{}]]></ac:plain-text-body></ac:structured-macro>",
+        language,
+        // `]]>` would end the section early; split it across two sections.
+        code.replace("]]>", "]]]]><![CDATA[>")
+    )
+}
+
+/// An absolute URL is an external image; anything else is taken to name an
+/// attachment on the page, the form `[Image: filename]` reads back from.
+fn image(destination: &str, alt: &str) -> String {
+    let alt = if alt.is_empty() {
+        String::new()
+    } else {
+        format!(" ac:alt=\"{}\"", escape_attr(alt))
+    };
+    let resource = if destination.contains("://") {
+        format!("<ri:url ri:value=\"{}\" />", escape_attr(destination))
+    } else {
+        format!(
+            "<ri:attachment ri:filename=\"{}\" />",
+            escape_attr(destination)
+        )
+    };
+    format!("<ac:image{}>{}</ac:image>", alt, resource)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn escape_attr(text: &str) -> String {
+    escape(text).replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::confluence_to_markdown;
+
+    #[test]
+    fn test_paragraph_and_inline_marks() {
+        assert_eq!(
+            markdown_to_storage("a **b** *c* ~~d~~ `e<f>` & g"),
+            "<p>a <strong>b</strong> <em>c</em> <del>d</del> <code>e&lt;f&gt;</code> &amp; g</p>"
+        );
+    }
+
+    #[test]
+    fn test_headings_lists_and_rule() {
+        assert_eq!(
+            markdown_to_storage("## Title\n\n- a\n- b\n\n3. c\n\n---"),
+            "<h2>Title</h2><ul><li>a</li><li>b</li></ul><ol start=\"3\"><li>c</li></ol><hr />"
+        );
+    }
+
+    #[test]
+    fn test_link_with_title_is_escaped() {
+        assert_eq!(
+            markdown_to_storage("[x](https://e.com/?a=1&b=2 \"say \\\"hi\\\"\")"),
+            "<p><a href=\"https://e.com/?a=1&amp;b=2\" title=\"say &quot;hi&quot;\">x</a></p>"
+        );
+    }
+
+    #[test]
+    fn test_fenced_code_becomes_code_macro() {
+        assert_eq!(
+            markdown_to_storage("```rust\nlet x = a && b;\n```"),
+            "<ac:structured-macro ac:name=\"code\"><ac:parameter ac:name=\"language\">rust</ac:parameter><ac:plain-text-body><![CDATA[let x = a && b;]]></ac:plain-text-body></ac:structured-macro>"
+        );
+    }
+
+    #[test]
+    fn test_code_macro_splits_cdata_terminator() {
+        let storage = markdown_to_storage("```\na]]>b\n```");
+        assert!(storage.contains("<![CDATA[a]]]]><![CDATA[>b]]>"));
+        assert!(!storage.contains("language"));
+    }
+
+    #[test]
+    fn test_alerts_become_panels() {
+        assert_eq!(
+            markdown_to_storage("> [!NOTE]\n> Read me"),
+            "<ac:structured-macro ac:name=\"info\"><ac:rich-text-body><p>Read me</p></ac:rich-text-body></ac:structured-macro>"
+        );
+        assert!(markdown_to_storage("> [!WARNING]\n> x").contains("ac:name=\"warning\""));
+        assert!(markdown_to_storage("> [!IMPORTANT]\n> x").contains("ac:name=\"note\""));
+        assert!(markdown_to_storage("> [!TIP]\n> x").contains("ac:name=\"tip\""));
+    }
+
+    #[test]
+    fn test_format_panel_convention_becomes_panel() {
+        assert_eq!(
+            markdown_to_storage("> **WARNING - Careful**: hot"),
+            "<ac:structured-macro ac:name=\"warning\"><ac:parameter ac:name=\"title\">Careful</ac:parameter><ac:rich-text-body><p>hot</p></ac:rich-text-body></ac:structured-macro>"
+        );
+    }
+
+    #[test]
+    fn test_plain_quote_stays_blockquote() {
+        assert_eq!(
+            markdown_to_storage("> **Bold**: not a panel"),
+            "<blockquote><p><strong>Bold</strong>: not a panel</p></blockquote>"
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        assert_eq!(
+            markdown_to_storage("| A | B |\n| - | - |\n| 1 | 2 |"),
+            "<table><tbody><tr><th>A</th><th>B</th></tr><tr><td>1</td><td>2</td></tr></tbody></table>"
+        );
+    }
+
+    #[test]
+    fn test_task_list() {
+        assert_eq!(
+            markdown_to_storage("- [ ] todo\n- [x] done"),
+            "<ac:task-list><ac:task><ac:task-id>1</ac:task-id><ac:task-status>incomplete</ac:task-status><ac:task-body>todo</ac:task-body></ac:task><ac:task><ac:task-id>2</ac:task-id><ac:task-status>complete</ac:task-status><ac:task-body>done</ac:task-body></ac:task></ac:task-list>"
+        );
+    }
+
+    #[test]
+    fn test_mixed_task_list_keeps_checkbox_text() {
+        assert_eq!(
+            markdown_to_storage("- [ ] todo\n- plain"),
+            "<ul><li>[ ] todo</li><li>plain</li></ul>"
+        );
+    }
+
+    #[test]
+    fn test_images() {
+        assert_eq!(
+            markdown_to_storage("![diagram](arch.png) ![](https://e.com/a.png)"),
+            "<p><ac:image ac:alt=\"diagram\"><ri:attachment ri:filename=\"arch.png\" /></ac:image> <ac:image><ri:url ri:value=\"https://e.com/a.png\" /></ac:image></p>"
+        );
+    }
+
+    #[test]
+    fn test_raw_html_passes_through() {
+        assert_eq!(
+            markdown_to_storage("<u>under</u> & more"),
+            "<p><u>under</u> &amp; more</p>"
+        );
+    }
+
+    #[test]
+    fn test_soft_break() {
+        assert_eq!(markdown_to_storage("a\nb"), "<p>a<br />b</p>");
+    }
+
+    #[test]
+    fn test_reads_back_through_confluence_to_markdown() {
+        let markdown =
+            "# Title\n\nSome **bold** text.\n\n```python\nprint(1)\n```\n\n> **INFO**: Heads up";
+        assert_eq!(
+            confluence_to_markdown(&markdown_to_storage(markdown)),
+            markdown
+        );
+    }
+}
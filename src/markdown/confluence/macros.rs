@@ -1,152 +1,208 @@
-use super::cleanup::clean_binary_data;
-use std::collections::HashMap;
-
-pub fn process_macro(block: &str) -> String {
-    let macro_name = extract_macro_name(block).unwrap_or_default();
-    let params = extract_parameters(block);
-    let body = extract_body(block);
+use super::elements::Renderer;
+use super::storage::{Macro, MacroBody, Node, escape_html, text_content};
+use crate::config::MacroRule;
+
+/// The macros that preview an attached file named by their `name`
+/// parameter.
+pub(super) const FILE_MACROS: &[&str] = &["view-file", "viewpdf", "viewdoc", "viewxls", "viewppt"];
+
+/// Renders a macro to the HTML that stands for it. A rule the config has for
+/// the macro comes first; otherwise most macros become a text token, and the
+/// ones with a body render that body through `renderer`, so macros nested in
+/// them are handled the same way.
+pub(super) fn process_macro(m: &Macro, renderer: &mut Renderer) -> String {
+    match renderer.macro_rule(&m.name) {
+        Some(MacroRule::Drop) => return String::new(),
+        Some(MacroRule::Template(template)) => return apply_template(template, m, renderer),
+        None => {}
+    }
 
-    match macro_name {
-        "code" | "noformat" => format_code_block(&params, &body),
+    match m.name.as_str() {
+        "code" | "noformat" => format_code_block(m),
         "info" | "note" | "warning" | "tip" | "error" => {
-            format_panel(&macro_name.to_uppercase(), &params, &body)
+            let label = match m.param("title") {
+                Some(title) => format!("{} - {}", m.name.to_uppercase(), title),
+                None => m.name.to_uppercase(),
+            };
+            match &m.body {
+                MacroBody::Rich(nodes) => panel(renderer, &label, nodes),
+                MacroBody::Plain(text) => panel(renderer, &label, &[Node::Cdata(text.clone())]),
+                MacroBody::None => panel(renderer, &label, &[]),
+            }
         }
         "toc" => String::new(),
-        "expand" => format_expand(&params, &body),
-        "anchor" => format_anchor(&params),
-        "jira" => format_jira(&params),
-        "status" => format_status(&params),
-        "drawio" => format_diagram("Draw.io", &params, "diagramName", "diagramDisplayName"),
-        "gliffy" => format_diagram("Gliffy", &params, "name", "displayName"),
-        "lucidchart" => format_lucidchart(&params),
-        "miro" => format_miro(&params),
-        "plantuml" => format_plantuml(&body),
-        "children" => format_children(&params),
+        "expand" => format_expand(m, renderer),
+        "anchor" => format_anchor(m),
+        "jira" => escape_html(&format_jira(m)),
+        "status" => escape_html(&format_status(m)),
+        "drawio" => escape_html(&format_diagram(
+            "Draw.io",
+            m,
+            "diagramName",
+            "diagramDisplayName",
+        )),
+        "gliffy" => escape_html(&format_diagram("Gliffy", m, "name", "displayName")),
+        "lucidchart" => escape_html(&format_lucidchart(m)),
+        "miro" => escape_html(&format_miro(m)),
+        "plantuml" => format_plantuml(m),
+        "children" => escape_html(&format_children(m)),
         "pagetree" => "[Page Tree]".into(),
         "recently-updated" => "[Recently Updated]".into(),
-        "widget" | "iframe" | "html" => format_embed(&params),
-        _ => format_unknown_macro(macro_name, &params, &body),
+        "include" | "excerpt-include" => escape_html(&format_include(m)),
+        "widget" | "iframe" | "html" => escape_html(&format_embed(m)),
+        name if FILE_MACROS.contains(&name) => escape_html(&format_view_file(m, renderer)),
+        _ => format_unknown_macro(m, renderer),
     }
 }
 
-fn extract_macro_name(block: &str) -> Option<&str> {
-    let start = block.find("ac:name=\"")? + 9;
-    let rest = &block[start..];
-    let end = rest.find('"')?;
-    Some(&rest[..end])
-}
-
-fn extract_parameters(block: &str) -> HashMap<String, String> {
-    let mut params = HashMap::new();
-    let mut search_start = 0;
-
-    while let Some(param_start) = block[search_start..].find("<ac:parameter ac:name=\"") {
-        let abs_start = search_start + param_start;
-        let name_start = abs_start + 23;
-
-        if let Some(name_end_rel) = block[name_start..].find('"') {
-            let name = &block[name_start..name_start + name_end_rel];
-
-            if !name.is_empty() {
-                let after_name = name_start + name_end_rel + 1;
-
-                // Check for self-closing tag
-                if let Some(close_pos) = block[after_name..].find('>') {
-                    let tag_end = after_name + close_pos;
-
-                    if block[after_name..tag_end].contains("/>")
-                        || block[tag_end..].starts_with("/>")
-                    {
-                        search_start = tag_end + 2;
-                        continue;
-                    }
-
-                    // Find closing tag
-                    let content_start = tag_end + 1;
-                    if let Some(close_tag) = block[content_start..].find("</ac:parameter>") {
-                        let value = &block[content_start..content_start + close_tag];
-                        params.insert(name.to_string(), value.trim().to_string());
-                        search_start = content_start + close_tag + 15;
-                        continue;
-                    }
-                }
-            }
+/// A configured template, filled in. What it makes is Markdown already, so
+/// it is parked as a block rather than handed to htmd to escape: on a line
+/// of its own when it spans several, in place when it is one line. A
+/// placeholder the template language does not have is left as written.
+fn apply_template(template: &str, m: &Macro, renderer: &mut Renderer) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        match placeholder(&after[..end], m, renderer) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..start + end + 2]),
         }
-        search_start = abs_start + 23;
+        rest = &after[end + 1..];
     }
+    out.push_str(rest);
 
-    params
-}
-
-fn extract_body(block: &str) -> String {
-    // Try rich-text-body first
-    if let Some(body) = extract_body_tag(block, "ac:rich-text-body") {
-        return clean_binary_data(&body);
+    let markdown = out.trim();
+    if markdown.is_empty() {
+        String::new()
+    } else if markdown.contains('\n') {
+        format!("<div>{}</div>", renderer.block(markdown.to_string()))
+    } else {
+        renderer.block(markdown.to_string())
     }
+}
 
-    // Then plain-text-body
-    if let Some(body) = extract_body_tag(block, "ac:plain-text-body") {
-        return clean_binary_data(&body);
+/// `{name}` is the macro's name, `{body}` its body as Markdown, and
+/// `{param:NAME}` a parameter's value — empty where the macro does not set
+/// it, since a template written for the usual case should not print its own
+/// placeholder for the unusual one.
+fn placeholder(key: &str, m: &Macro, renderer: &mut Renderer) -> Option<String> {
+    match key {
+        "name" => Some(m.name.clone()),
+        "body" => Some(match &m.body {
+            MacroBody::Rich(nodes) => renderer.markdown(nodes),
+            MacroBody::Plain(text) => text.trim().to_string(),
+            MacroBody::None => String::new(),
+        }),
+        _ => key
+            .strip_prefix("param:")
+            .map(|name| m.param(name).unwrap_or_default().to_string()),
     }
-
-    String::new()
 }
 
-fn extract_body_tag(block: &str, tag: &str) -> Option<String> {
-    let open_tag = format!("<{}>", tag);
-    let close_tag = format!("</{}>", tag);
-
-    let start = block.find(&open_tag)? + open_tag.len();
-    let rest = &block[start..];
-    let end = rest.find(&close_tag)?;
+/// A quoted panel: `**LABEL**: ` leads its first paragraph, the way
+/// `markdown_to_storage` reads a panel back.
+pub(super) fn panel(renderer: &mut Renderer, label: &str, body: &[Node]) -> String {
+    let start = body
+        .iter()
+        .position(|n| !matches!(n, Node::Text(t) if t.trim().is_empty()))
+        .unwrap_or(body.len());
+    let body = &body[start..];
+
+    let (lead, rest) = match body.first() {
+        Some(Node::Element(p)) if p.is("p") => (renderer.render(&p.children), &body[1..]),
+        _ => {
+            let inline = body.iter().take_while(|n| !is_block(n)).count();
+            (renderer.render(&body[..inline]), &body[inline..])
+        }
+    };
 
-    Some(rest[..end].to_string())
+    format!(
+        "<blockquote><p><strong>{}</strong>: {}</p>{}</blockquote>",
+        escape_html(label),
+        lead.trim(),
+        renderer.render(rest)
+    )
 }
 
-fn format_code_block(params: &HashMap<String, String>, body: &str) -> String {
-    let language = params.get("language").map(|s| s.as_str()).unwrap_or("");
-    let title = params.get("title");
-
-    let mut result = String::new();
-    if let Some(t) = title {
-        result.push_str(&format!("**{}**\n", t));
+fn is_block(node: &Node) -> bool {
+    const BLOCK_ELEMENTS: &[&str] = &[
+        "p",
+        "div",
+        "ul",
+        "ol",
+        "table",
+        "pre",
+        "blockquote",
+        "hr",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+    ];
+    match node {
+        Node::Element(e) => BLOCK_ELEMENTS.iter().any(|b| e.is(b)),
+        Node::Macro(_) | Node::TaskList(_) => true,
+        _ => false,
     }
-    result.push_str(&format!("```{}\n{}\n```", language, body.trim()));
-    result
 }
 
-fn format_panel(panel_type: &str, params: &HashMap<String, String>, body: &str) -> String {
-    let title = params.get("title");
-    let content = body.trim();
-
-    match title {
-        Some(t) => format!("> **{} - {}**: {}", panel_type, t, content),
-        None => format!("> **{}**: {}", panel_type, content),
+fn plain_body(m: &Macro) -> String {
+    match &m.body {
+        MacroBody::Plain(text) => text.clone(),
+        MacroBody::Rich(nodes) => text_content(nodes),
+        MacroBody::None => String::new(),
     }
 }
 
-fn format_expand(params: &HashMap<String, String>, body: &str) -> String {
-    let title = params.get("title").map(|s| s.as_str()).unwrap_or("Details");
-    let content = body.trim();
+fn code_html(language: Option<&str>, code: &str) -> String {
+    let class = language
+        .filter(|l| !l.is_empty())
+        .map(|l| format!(" class=\"language-{}\"", escape_html(l)))
+        .unwrap_or_default();
+    // Leading blank lines go, leading indentation stays: it is the code's.
+    let code = code.trim_start_matches(['\n', '\r']).trim_end();
+    format!("<pre><code{}>{}</code></pre>", class, escape_html(code))
+}
 
-    if content.is_empty() {
-        format!("**{}**", title)
-    } else {
-        format!("**{}**\n\n{}", title, content)
-    }
+fn format_code_block(m: &Macro) -> String {
+    let title = m
+        .param("title")
+        .map(|t| format!("<p><strong>{}</strong></p>", escape_html(t)))
+        .unwrap_or_default();
+    format!(
+        "{}{}",
+        title,
+        code_html(m.param("language"), &plain_body(m))
+    )
 }
 
-fn format_anchor(params: &HashMap<String, String>) -> String {
-    params
-        .get("")
-        .or_else(|| params.get("name"))
-        .map(|name| format!("<a id=\"{}\"></a>", name))
+fn format_expand(m: &Macro, renderer: &mut Renderer) -> String {
+    let title = m.param("title").unwrap_or("Details");
+    let body = match &m.body {
+        MacroBody::Rich(nodes) => renderer.render(nodes),
+        MacroBody::Plain(text) => format!("<p>{}</p>", escape_html(text)),
+        MacroBody::None => String::new(),
+    };
+    format!("<p><strong>{}</strong></p>{}", escape_html(title), body)
+}
+
+fn format_anchor(m: &Macro) -> String {
+    m.param("")
+        .or_else(|| m.param("name"))
+        .map(|name| format!("<a id=\"{}\"></a>", escape_html(name)))
         .unwrap_or_default()
 }
 
-fn format_jira(params: &HashMap<String, String>) -> String {
-    let key = params.get("key").map(|s| s.as_str()).unwrap_or("JIRA");
-    let server = params.get("server").or_else(|| params.get("serverId"));
+fn format_jira(m: &Macro) -> String {
+    let key = m.param("key").unwrap_or("JIRA");
+    let server = m.param("server").or_else(|| m.param("serverId"));
 
     match server {
         Some(s) => format!("[{}]({})", key, s),
@@ -154,12 +210,11 @@ fn format_jira(params: &HashMap<String, String>) -> String {
     }
 }
 
-fn format_status(params: &HashMap<String, String>) -> String {
-    let title = params.get("title").map(|s| s.as_str()).unwrap_or("STATUS");
-    let color = params
-        .get("colour")
-        .or_else(|| params.get("color"))
-        .map(|s| s.as_str())
+fn format_status(m: &Macro) -> String {
+    let title = m.param("title").unwrap_or("STATUS");
+    let color = m
+        .param("colour")
+        .or_else(|| m.param("color"))
         .unwrap_or("Grey");
 
     let indicator = match color.to_lowercase().as_str() {
@@ -173,49 +228,66 @@ fn format_status(params: &HashMap<String, String>) -> String {
     format!("{} {}", indicator, title.to_uppercase())
 }
 
-fn format_diagram(tool: &str, params: &HashMap<String, String>, key1: &str, key2: &str) -> String {
-    let name = params
-        .get(key1)
-        .or_else(|| params.get(key2))
-        .map(|s| s.as_str())
-        .unwrap_or("diagram");
+fn format_diagram(tool: &str, m: &Macro, key1: &str, key2: &str) -> String {
+    let name = m.param(key1).or_else(|| m.param(key2)).unwrap_or("diagram");
 
     format!("[{}: {}]", tool, name)
 }
 
-fn format_lucidchart(params: &HashMap<String, String>) -> String {
-    match params.get("documentId") {
+fn format_lucidchart(m: &Macro) -> String {
+    match m.param("documentId") {
         Some(id) => format!("[Lucidchart](https://lucid.app/documents/view/{})", id),
         None => "[Lucidchart]".into(),
     }
 }
 
-fn format_miro(params: &HashMap<String, String>) -> String {
-    match params.get("boardId") {
+fn format_miro(m: &Macro) -> String {
+    match m.param("boardId") {
         Some(id) => format!("[Miro](https://miro.com/app/board/{})", id),
         None => "[Miro Board]".into(),
     }
 }
 
-fn format_plantuml(body: &str) -> String {
-    let content = body.trim();
-    if content.is_empty() {
+fn format_plantuml(m: &Macro) -> String {
+    let content = plain_body(m);
+    if content.trim().is_empty() {
         "[PlantUML Diagram]".into()
     } else {
-        format!("```plantuml\n{}\n```", content)
+        code_html(Some("plantuml"), &content)
     }
 }
 
-fn format_children(params: &HashMap<String, String>) -> String {
-    let depth = params.get("depth").map(|s| s.as_str()).unwrap_or("1");
+fn format_children(m: &Macro) -> String {
+    let depth = m.param("depth").unwrap_or("1");
     format!("[Child Pages (depth: {})]", depth)
 }
 
-fn format_embed(params: &HashMap<String, String>) -> String {
-    let url = params
-        .get("url")
-        .or_else(|| params.get("src"))
-        .or_else(|| params.get("name"));
+/// `include` names the page it pulls in with an unnamed `ri:page`
+/// parameter; the title is what a reader needs to go and find it.
+fn format_include(m: &Macro) -> String {
+    match m.param("").filter(|p| !p.is_empty()) {
+        Some(page) => format!("[Include: {}]", page),
+        None => "[Include]".into(),
+    }
+}
+
+/// The file-preview macros name an attachment in their `name` parameter;
+/// where the page's attachment list has it, the preview becomes a link.
+fn format_view_file(m: &Macro, renderer: &Renderer) -> String {
+    let Some(filename) = m.param("name").filter(|n| !n.is_empty()) else {
+        return "[File]".into();
+    };
+    match renderer.attachments().find(None, Some(filename)) {
+        Some(found) => format!("[{}]({})", filename, found.href),
+        None => format!("[File: {}]", filename),
+    }
+}
+
+fn format_embed(m: &Macro) -> String {
+    let url = m
+        .param("url")
+        .or_else(|| m.param("src"))
+        .or_else(|| m.param("name"));
 
     match url {
         Some(u) => format!("[Embed: {}]", u),
@@ -223,43 +295,75 @@ fn format_embed(params: &HashMap<String, String>) -> String {
     }
 }
 
-fn format_unknown_macro(name: &str, params: &HashMap<String, String>, body: &str) -> String {
-    let body = body.trim();
-
+fn format_unknown_macro(m: &Macro, renderer: &mut Renderer) -> String {
     // If body has meaningful content, return it
-    if !body.is_empty() && body.len() > 3 {
-        return body.to_string();
+    if plain_body(m).trim().len() > 3 {
+        return match &m.body {
+            MacroBody::Rich(nodes) => renderer.render(nodes),
+            _ => format!("<p>{}</p>", escape_html(plain_body(m).trim())),
+        };
     }
 
     // Otherwise, show macro info
-    let meaningful: Vec<String> = params
+    let meaningful: Vec<String> = m
+        .params
         .iter()
         .filter(|(k, _)| matches!(k.as_str(), "title" | "name" | "key" | "url"))
         .map(|(k, v)| format!("{}={}", k, v))
         .collect();
 
-    if meaningful.is_empty() {
-        format!("[Macro: {}]", name)
+    let token = if meaningful.is_empty() {
+        format!("[Macro: {}]", m.name)
     } else {
-        format!("[Macro: {} ({})]", name, meaningful.join(", "))
-    }
+        format!("[Macro: {} ({})]", m.name, meaningful.join(", "))
+    };
+    escape_html(&token)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use super::super::confluence_to_markdown as process_macro;
+    use super::super::confluence_to_markdown_with;
+    use crate::config::MacroRule;
+    use crate::markdown::{Attachments, UserNames};
+    use std::collections::HashMap;
+
+    fn with_rule(name: &str, rule: MacroRule, storage: &str) -> String {
+        let macros = HashMap::from([(name.to_string(), rule)]);
+        confluence_to_markdown_with(
+            storage,
+            &UserNames::default(),
+            &Attachments::default(),
+            &macros,
+        )
+    }
+
+    #[test]
+    fn test_template_fills_in_parameters_and_body() {
+        let storage = r#"<p>Plan:</p><ac:structured-macro ac:name="roadmap"><ac:parameter ac:name="title">Q3</ac:parameter><ac:rich-text-body><ul><li>ship *it*</li></ul></ac:rich-text-body></ac:structured-macro>"#;
+        let rule = MacroRule::Template(
+            "### {name}: {param:title}{param:missing}\n\n{body}\n\n{unknown}".into(),
+        );
+        assert_eq!(
+            with_rule("roadmap", rule, storage),
+            "Plan:\n\n### roadmap: Q3\n\n*   ship *it*\n\n{unknown}"
+        );
+    }
 
     #[test]
-    fn test_extract_macro_name() {
-        let block = r#"<ac:structured-macro ac:name="code">"#;
-        assert_eq!(extract_macro_name(block), Some("code"));
+    fn test_one_line_template_stays_in_its_paragraph() {
+        let storage = r#"<p>State: <ac:structured-macro ac:name="status"><ac:parameter ac:name="title">Done</ac:parameter></ac:structured-macro> today</p>"#;
+        let rule = MacroRule::Template("**{param:title}**".into());
+        assert_eq!(with_rule("status", rule, storage), "State: **Done** today");
     }
 
     #[test]
-    fn test_extract_parameters() {
-        let block = r#"<ac:parameter ac:name="language">rust</ac:parameter>"#;
-        let params = extract_parameters(block);
-        assert_eq!(params.get("language"), Some(&"rust".to_string()));
+    fn test_a_dropped_macro_leaves_nothing() {
+        let storage = r#"<p>Before</p><ac:structured-macro ac:name="livesearch"><ac:parameter ac:name="spaceKey">OPS</ac:parameter></ac:structured-macro><p>After</p>"#;
+        assert_eq!(
+            with_rule("livesearch", MacroRule::Drop, storage),
+            "Before\n\nAfter"
+        );
     }
 
     #[test]
@@ -268,9 +372,18 @@ mod tests {
             <ac:parameter ac:name="language">rust</ac:parameter>
             <ac:plain-text-body>let x = 1;</ac:plain-text-body>
         </ac:structured-macro>"#;
-        let result = process_macro(block);
-        assert!(result.contains("```rust"));
-        assert!(result.contains("let x = 1;"));
+        assert_eq!(process_macro(block), "```rust\nlet x = 1;\n```");
+    }
+
+    #[test]
+    fn test_code_macro_keeps_lines_indentation_and_angle_brackets() {
+        let block = r#"<ac:structured-macro ac:name="code"><ac:parameter ac:name="title">Main</ac:parameter><ac:parameter ac:name="language">rust</ac:parameter><ac:plain-text-body><![CDATA[fn main() {
+    if a < b && c > d {}
+}]]></ac:plain-text-body></ac:structured-macro>"#;
+        assert_eq!(
+            process_macro(block),
+            "**Main**\n\n```rust\nfn main() {\n    if a < b && c > d {}\n}\n```"
+        );
     }
 
     #[test]
@@ -278,9 +391,16 @@ mod tests {
         let block = r#"<ac:structured-macro ac:name="info">
             <ac:rich-text-body>Important info</ac:rich-text-body>
         </ac:structured-macro>"#;
-        let result = process_macro(block);
-        assert!(result.contains("> **INFO**"));
-        assert!(result.contains("Important info"));
+        assert_eq!(process_macro(block), "> **INFO**: Important info");
+    }
+
+    #[test]
+    fn test_panel_with_title_and_several_paragraphs() {
+        let block = r#"<ac:structured-macro ac:name="warning"><ac:parameter ac:name="title">Heads up</ac:parameter><ac:rich-text-body><p>First</p><p>Second</p></ac:rich-text-body></ac:structured-macro>"#;
+        assert_eq!(
+            process_macro(block),
+            "> **WARNING - Heads up**: First\n> \n> Second"
+        );
     }
 
     #[test]
@@ -288,8 +408,7 @@ mod tests {
         let block = r#"<ac:structured-macro ac:name="drawio">
             <ac:parameter ac:name="diagramName">architecture</ac:parameter>
         </ac:structured-macro>"#;
-        let result = process_macro(block);
-        assert_eq!(result, "[Draw.io: architecture]");
+        assert_eq!(process_macro(block), "[Draw.io: architecture]");
     }
 
     #[test]
@@ -298,8 +417,7 @@ mod tests {
             <ac:parameter ac:name="title">Done</ac:parameter>
             <ac:parameter ac:name="colour">Green</ac:parameter>
         </ac:structured-macro>"#;
-        let result = process_macro(block);
-        assert_eq!(result, "[OK] DONE");
+        assert_eq!(process_macro(block), "[OK] DONE");
     }
 
     #[test]
@@ -307,15 +425,13 @@ mod tests {
         let block = r#"<ac:structured-macro ac:name="jira">
             <ac:parameter ac:name="key">PROJ-123</ac:parameter>
         </ac:structured-macro>"#;
-        let result = process_macro(block);
-        assert_eq!(result, "[JIRA: PROJ-123]");
+        assert_eq!(process_macro(block), "[JIRA: PROJ-123]");
     }
 
     #[test]
     fn test_toc() {
         let block = r#"<ac:structured-macro ac:name="toc" />"#;
-        let result = process_macro(block);
-        assert!(result.is_empty());
+        assert!(process_macro(block).is_empty());
     }
 
     #[test]
@@ -324,8 +440,27 @@ mod tests {
             <ac:parameter ac:name="title">Click to expand</ac:parameter>
             <ac:rich-text-body>Hidden content</ac:rich-text-body>
         </ac:structured-macro>"#;
-        let result = process_macro(block);
-        assert!(result.contains("**Click to expand**"));
-        assert!(result.contains("Hidden content"));
+        assert_eq!(
+            process_macro(block),
+            "**Click to expand**\n\nHidden content"
+        );
+    }
+
+    #[test]
+    fn test_include_names_the_page() {
+        let block = r#"<ac:structured-macro ac:name="include"><ac:parameter ac:name=""><ac:link><ri:page ri:space-key="OPS" ri:content-title="Runbook" /></ac:link></ac:parameter></ac:structured-macro>"#;
+        assert_eq!(process_macro(block), "[Include: Runbook]");
+    }
+
+    #[test]
+    fn test_view_file_names_the_attachment() {
+        let block = r#"<ac:structured-macro ac:name="view-file"><ac:parameter ac:name="name"><ri:attachment ri:filename="report.pdf" /></ac:parameter></ac:structured-macro>"#;
+        assert_eq!(process_macro(block), "[File: report.pdf]");
+    }
+
+    #[test]
+    fn test_unknown_macro_lists_its_parameters_in_order() {
+        let block = r#"<ac:structured-macro ac:name="roadmap"><ac:parameter ac:name="url">u</ac:parameter><ac:parameter ac:name="title">t</ac:parameter></ac:structured-macro>"#;
+        assert_eq!(process_macro(block), "[Macro: roadmap (url=u, title=t)]");
     }
 }
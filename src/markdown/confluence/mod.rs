@@ -1,32 +1,106 @@
 mod cleanup;
 mod elements;
+#[cfg(test)]
+mod fixtures;
+mod from_markdown;
 mod macros;
+mod storage;
+
+pub use from_markdown::markdown_to_storage;
 
+use crate::config::MacroRule;
 use crate::markdown::common::normalize_whitespace;
+use crate::markdown::{Attachments, UserNames};
 use htmd::HtmlToMarkdown;
+use std::collections::HashMap;
 
 pub fn confluence_to_markdown(html: &str) -> String {
-    // 1. Pre-process: Process Confluence elements first (needs full metadata)
-    let processed = elements::clean_confluence_html(html);
-
-    // 2. Clean metadata after Confluence element processing
-    let cleaned = cleanup::clean_metadata(&processed);
+    confluence_to_markdown_with(
+        html,
+        &UserNames::default(),
+        &Attachments::default(),
+        &HashMap::new(),
+    )
+}
 
-    // 3. Convert standard HTML to Markdown
-    let converter = HtmlToMarkdown::builder()
-        .skip_tags(vec!["script", "style", "meta", "noscript"])
-        .build();
+/// `confluence_to_markdown`, with `ri:user` links shown by the display names
+/// in `names` rather than by account id, `ri:attachment` images and links
+/// pointing where `attachments` says the file is, and each macro `macros`
+/// has a rule for rendered by that rule.
+pub fn confluence_to_markdown_with(
+    html: &str,
+    names: &UserNames,
+    attachments: &Attachments,
+    macros: &HashMap<String, MacroRule>,
+) -> String {
+    // 1. Parse storage into a tree and render its Confluence nodes to HTML
+    let tree = storage::parse(html);
+    let mut renderer = elements::Renderer::new(names, attachments, macros);
+    let rendered = renderer.render(&tree);
 
-    let markdown = converter
-        .convert(&cleaned)
-        .unwrap_or_else(|_| cleaned.clone());
+    // 2. Convert standard HTML to Markdown, then put back the blocks the
+    //    renderer wrote as Markdown itself
+    let markdown = renderer.restore(&html_to_markdown(&rendered));
 
-    // 4. Post-process: Remove binary residue, unescape, and normalize
+    // 3. Post-process: Remove binary residue, unescape, and normalize
     let without_residue = cleanup::clean_binary_data(&markdown);
     let unescaped = unescape_markdown(&without_residue);
     normalize_whitespace(&unescaped)
 }
 
+/// The account ids a storage body links to with `ri:user`, each once: what a
+/// caller looks up for `confluence_to_markdown_with`.
+pub fn linked_users(html: &str) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    storage::walk(&storage::parse(html), &mut |node| {
+        if let storage::Node::Link(storage::Link {
+            resource: Some(storage::Resource::User { account_id }),
+            ..
+        }) = node
+            && !ids.contains(account_id)
+        {
+            ids.push(account_id.clone());
+        }
+    });
+    ids
+}
+
+/// The attachments a storage body shows or links to, by filename, each once:
+/// what a caller needs the page's attachment list for.
+pub fn linked_attachments(html: &str) -> Vec<String> {
+    let mut filenames: Vec<String> = Vec::new();
+    storage::walk(&storage::parse(html), &mut |node| {
+        let filename = match node {
+            storage::Node::Link(storage::Link {
+                resource: Some(storage::Resource::Attachment { filename }),
+                ..
+            })
+            | storage::Node::Image(storage::Image {
+                resource: Some(storage::Resource::Attachment { filename }),
+                ..
+            }) => Some(filename.as_str()),
+            storage::Node::Macro(m) if macros::FILE_MACROS.contains(&m.name.as_str()) => {
+                m.param("name")
+            }
+            _ => None,
+        };
+        if let Some(filename) = filename.filter(|f| !f.is_empty())
+            && !filenames.iter().any(|f| f == filename)
+        {
+            filenames.push(filename.to_string());
+        }
+    });
+    filenames
+}
+
+fn html_to_markdown(html: &str) -> String {
+    let converter = HtmlToMarkdown::builder()
+        .skip_tags(vec!["script", "style", "meta", "noscript"])
+        .build();
+
+    converter.convert(html).unwrap_or_else(|_| html.to_string())
+}
+
 fn unescape_markdown(text: &str) -> String {
     text.replace("\\[", "[")
         .replace("\\]", "]")
@@ -138,6 +212,18 @@ mod tests {
         assert!(result.contains("@user123"));
     }
 
+    #[test]
+    fn test_linked_attachments_cover_images_links_and_file_macros() {
+        let html = r#"<ac:image><ri:attachment ri:filename="a.png"/></ac:image><p><ac:link><ri:attachment ri:filename="b.pdf"/></ac:link></p><ac:structured-macro ac:name="view-file"><ac:parameter ac:name="name"><ri:attachment ri:filename="c.docx"/></ac:parameter></ac:structured-macro><ac:image><ri:attachment ri:filename="a.png"/></ac:image>"#;
+        assert_eq!(linked_attachments(html), vec!["a.png", "b.pdf", "c.docx"]);
+    }
+
+    #[test]
+    fn test_linked_users_reach_into_macros_and_tasks() {
+        let html = r#"<ac:link><ri:user ri:account-id="a"/></ac:link><ac:structured-macro ac:name="info"><ac:rich-text-body><ac:task-list><ac:task><ac:task-body><ac:link><ri:user ri:account-id="b"/></ac:link> <ac:link><ri:user ri:account-id="a"/></ac:link></ac:task-body></ac:task></ac:task-list></ac:rich-text-body></ac:structured-macro>"#;
+        assert_eq!(linked_users(html), vec!["a", "b"]);
+    }
+
     #[test]
     fn test_self_closing_toc() {
         let html = r#"<ac:structured-macro ac:name="toc" /><p>Content after TOC</p>"#;
@@ -217,6 +303,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fixture_corpus() {
+        for fixture in fixtures::FIXTURES {
+            let result = confluence_to_markdown(fixture.storage);
+            for expected in fixture.contains {
+                assert!(
+                    result.contains(expected),
+                    "{}: expected {:?} in\n{}",
+                    fixture.name,
+                    expected,
+                    result
+                );
+            }
+            for unwanted in fixture
+                .absent
+                .iter()
+                .chain(&["ac:", "ri:", "CDATA", "\u{e000}"])
+            {
+                assert!(
+                    !result.contains(unwanted),
+                    "{}: did not expect {:?} in\n{}",
+                    fixture.name,
+                    unwanted,
+                    result
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_removes_mxgraphmodel_residue() {
         let html = r#"<p>Before</p><mxGraphModel><root><mxCell id="0"/></root></mxGraphModel><p>After</p>"#;
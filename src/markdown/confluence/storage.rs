@@ -0,0 +1,755 @@
+//! Confluence storage format as a tree.
+//!
+//! Storage is XHTML with Confluence's own `ac:` elements (macros, links,
+//! images, tasks) and `ri:` resource identifiers mixed in. Scanning it as a
+//! string — find the next `<ac:structured-macro`, then the next
+//! `</ac:structured-macro>` — pairs an outer macro with an inner macro's
+//! closing tag, ends a tag at a `>` inside a quoted attribute, and loses
+//! track of which body a parameter belongs to once macros sit in tables or
+//! in each other. Here the markup is tokenized once, nested by the tags
+//! themselves, and the Confluence elements are lifted into typed nodes, so
+//! every later step asks the tree rather than searching text.
+//!
+//! The parser is tolerant the way a browser is: an unclosed element closes
+//! with its parent, a stray closing tag is ignored, and a `<` that starts no
+//! tag is text. Real pages are not always well-formed, and a page that reads
+//! badly is better than one that does not read at all.
+
+/// One node of a storage document.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Node {
+    /// Character data as written, entities still encoded, so it can be
+    /// written back into HTML unchanged.
+    Text(String),
+    /// The content of a CDATA section, literal.
+    Cdata(String),
+    /// HTML, and any `ac:`/`ri:` element without a type of its own.
+    Element(Element),
+    /// `ac:structured-macro` (or the older `ac:macro`).
+    Macro(Macro),
+    /// `ac:link`.
+    Link(Link),
+    /// `ac:image`.
+    Image(Image),
+    /// `ac:emoticon`, by its `ac:name`.
+    Emoticon(String),
+    /// `ac:task-list`.
+    TaskList(Vec<Task>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Element {
+    pub name: String,
+    /// Attributes as written, values still entity-encoded.
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    /// Element names compare without case: HTML's do, and Confluence's own
+    /// are lowercase anyway.
+    pub fn is(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+
+    /// An attribute's value, entities decoded.
+    pub fn attr(&self, name: &str) -> Option<String> {
+        self.attrs
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| decode_entities(v))
+    }
+
+    /// The element children, skipping text between them.
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|n| match n {
+            Node::Element(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.is(name))
+    }
+
+    /// The first element at any depth below this one that `matches`.
+    pub fn find(&self, matches: &dyn Fn(&Element) -> bool) -> Option<&Element> {
+        for child in self.elements() {
+            if matches(child) {
+                return Some(child);
+            }
+            if let Some(found) = child.find(matches) {
+                return Some(found);
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Macro {
+    pub name: String,
+    /// Parameters in document order; a parameter with no `ac:name` is kept
+    /// under `""`, which is where `anchor` keeps its name.
+    pub params: Vec<(String, String)>,
+    pub body: MacroBody,
+}
+
+impl Macro {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum MacroBody {
+    None,
+    /// `ac:rich-text-body`: storage of its own, parsed like the page.
+    Rich(Vec<Node>),
+    /// `ac:plain-text-body`: literal text, as a code macro holds it.
+    Plain(String),
+}
+
+/// What an `ri:` element points at.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Resource {
+    /// `ri:page`, and `ri:blog-post`, which is addressed the same way.
+    Page {
+        space_key: Option<String>,
+        title: String,
+    },
+    /// `ri:user`, by account id (or the server-era user key).
+    User {
+        account_id: String,
+    },
+    Attachment {
+        filename: String,
+    },
+    Url(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Link {
+    pub resource: Option<Resource>,
+    pub anchor: Option<String>,
+    pub body: LinkBody,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum LinkBody {
+    None,
+    Rich(Vec<Node>),
+    Plain(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Image {
+    pub resource: Option<Resource>,
+    pub alt: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Task {
+    pub complete: bool,
+    /// `ac:task-body`, which may hold a nested `ac:task-list`.
+    pub body: Vec<Node>,
+}
+
+/// Parses a storage-format document into its typed tree.
+pub(super) fn parse(storage: &str) -> Vec<Node> {
+    build(storage).into_iter().map(typed).collect()
+}
+
+/// The text a run of nodes reads as, entities decoded and markup dropped —
+/// what a parameter's value or a link's label is.
+pub(super) fn text_content(nodes: &[Node]) -> String {
+    let mut text = String::new();
+    collect_text(nodes, &mut text);
+    text
+}
+
+fn collect_text(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(t) => out.push_str(&decode_entities(t)),
+            Node::Cdata(t) => out.push_str(t),
+            Node::Element(e) => collect_text(&e.children, out),
+            Node::Macro(m) => match &m.body {
+                MacroBody::Rich(nodes) => collect_text(nodes, out),
+                MacroBody::Plain(t) => out.push_str(t),
+                MacroBody::None => {}
+            },
+            Node::Link(l) => match &l.body {
+                LinkBody::Rich(nodes) => collect_text(nodes, out),
+                LinkBody::Plain(t) => out.push_str(t),
+                LinkBody::None => {}
+            },
+            Node::TaskList(tasks) => {
+                for task in tasks {
+                    collect_text(&task.body, out);
+                }
+            }
+            Node::Image(_) | Node::Emoticon(_) => {}
+        }
+    }
+}
+
+/// Visits every node in document order, descending into elements, macro
+/// bodies, link bodies and tasks.
+pub(super) fn walk(nodes: &[Node], visit: &mut dyn FnMut(&Node)) {
+    for node in nodes {
+        visit(node);
+        match node {
+            Node::Element(e) => walk(&e.children, visit),
+            Node::Macro(Macro {
+                body: MacroBody::Rich(nodes),
+                ..
+            })
+            | Node::Link(Link {
+                body: LinkBody::Rich(nodes),
+                ..
+            }) => walk(nodes, visit),
+            Node::TaskList(tasks) => {
+                for task in tasks {
+                    walk(&task.body, visit);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Decodes the entities storage uses. One Confluence does not write, or a
+/// malformed one, stays as written rather than being guessed at.
+pub(super) fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&semi| semi <= 10).and_then(|semi| {
+            let entity = &rest[1..semi];
+            let ch = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                "hellip" => Some('…'),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                "lsquo" => Some('‘'),
+                "rsquo" => Some('’'),
+                "ldquo" => Some('“'),
+                "rdquo" => Some('”'),
+                "bull" => Some('•'),
+                "middot" => Some('·'),
+                "rarr" => Some('→'),
+                "larr" => Some('←'),
+                "times" => Some('×'),
+                "copy" => Some('©'),
+                "reg" => Some('®'),
+                "trade" => Some('™'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            ch.map(|c| (c, semi))
+        });
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Escapes text for an HTML text node or a double-quoted attribute.
+pub(super) fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose content is text up to their own closing tag, not markup.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// The untyped tree: elements nested by their tags, text and CDATA between.
+fn build(input: &str) -> Vec<Node> {
+    let mut tree = Tree::default();
+    let mut pos = 0;
+
+    while let Some(offset) = input[pos..].find('<') {
+        let at = pos + offset;
+        tree.text(&input[pos..at]);
+        let rest = &input[at..];
+
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            tree.push(Node::Cdata(cdata[..end].to_string()));
+            pos = at + "<![CDATA[".len() + (end + 3).min(cdata.len());
+        } else if rest.starts_with("<!--") {
+            pos = rest.find("-->").map_or(input.len(), |end| at + end + 3);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            pos = rest.find('>').map_or(input.len(), |end| at + end + 1);
+        } else if let Some(closing) = rest.strip_prefix("</") {
+            match closing.find('>') {
+                Some(end) => {
+                    tree.close(closing[..end].trim());
+                    pos = at + 2 + end + 1;
+                }
+                None => {
+                    tree.text(rest);
+                    pos = input.len();
+                }
+            }
+        } else if let Some((tag, end)) = open_tag(input, at) {
+            pos = end;
+            let raw_text = !tag.self_closing
+                && RAW_TEXT_ELEMENTS
+                    .iter()
+                    .any(|r| tag.name.eq_ignore_ascii_case(r));
+            let void = VOID_ELEMENTS
+                .iter()
+                .any(|v| tag.name.eq_ignore_ascii_case(v));
+            let element = Element {
+                name: tag.name,
+                attrs: tag.attrs,
+                children: Vec::new(),
+            };
+            if tag.self_closing || void {
+                tree.push(Node::Element(element));
+            } else if raw_text {
+                let close = format!("</{}", element.name.to_ascii_lowercase());
+                let body_end = input[pos..]
+                    .to_ascii_lowercase()
+                    .find(&close)
+                    .map_or(input.len(), |i| pos + i);
+                let mut element = element;
+                element
+                    .children
+                    .push(Node::Text(input[pos..body_end].to_string()));
+                tree.push(Node::Element(element));
+                pos = input[body_end..]
+                    .find('>')
+                    .map_or(input.len(), |i| body_end + i + 1);
+            } else {
+                tree.open.push(element);
+            }
+        } else {
+            tree.text("<");
+            pos = at + 1;
+        }
+    }
+    tree.text(&input[pos..]);
+    tree.finish()
+}
+
+#[derive(Default)]
+struct Tree {
+    root: Vec<Node>,
+    open: Vec<Element>,
+}
+
+impl Tree {
+    fn push(&mut self, node: Node) {
+        match self.open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.root.push(node),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.push(Node::Text(text.to_string()));
+        }
+    }
+
+    /// Closes `name` and anything left open inside it. A closing tag with
+    /// nothing open to match is ignored.
+    fn close(&mut self, name: &str) {
+        let Some(index) = self.open.iter().rposition(|e| e.is(name)) else {
+            return;
+        };
+        while self.open.len() > index {
+            let element = self.open.pop().expect("index is within the stack");
+            self.push(Node::Element(element));
+        }
+    }
+
+    fn finish(mut self) -> Vec<Node> {
+        while let Some(element) = self.open.pop() {
+            self.push(Node::Element(element));
+        }
+        self.root
+    }
+}
+
+struct OpenTag {
+    name: String,
+    attrs: Vec<(String, String)>,
+    self_closing: bool,
+}
+
+/// Reads the tag opening at `at`, returning it and the index just past it,
+/// or `None` when what follows the `<` is not a tag.
+fn open_tag(input: &str, at: usize) -> Option<(OpenTag, usize)> {
+    let bytes = input.as_bytes();
+    if !bytes.get(at + 1)?.is_ascii_alphabetic() {
+        return None;
+    }
+    let is_space = |b: u8| b.is_ascii_whitespace();
+    let mut i = at + 1;
+    while i < bytes.len() && !is_space(bytes[i]) && bytes[i] != b'/' && bytes[i] != b'>' {
+        i += 1;
+    }
+    let name = input[at + 1..i].to_string();
+    let mut attrs = Vec::new();
+
+    loop {
+        while i < bytes.len() && is_space(bytes[i]) {
+            i += 1;
+        }
+        match bytes.get(i)? {
+            b'>' => {
+                return Some((
+                    OpenTag {
+                        name,
+                        attrs,
+                        self_closing: false,
+                    },
+                    i + 1,
+                ));
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'>') => {
+                return Some((
+                    OpenTag {
+                        name,
+                        attrs,
+                        self_closing: true,
+                    },
+                    i + 2,
+                ));
+            }
+            b'/' | b'=' => {
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let start = i;
+        while i < bytes.len() && !is_space(bytes[i]) && !matches!(bytes[i], b'=' | b'>' | b'/') {
+            i += 1;
+        }
+        let attr_name = input[start..i].to_string();
+        while i < bytes.len() && is_space(bytes[i]) {
+            i += 1;
+        }
+        let mut value = String::new();
+        if bytes.get(i) == Some(&b'=') {
+            i += 1;
+            while i < bytes.len() && is_space(bytes[i]) {
+                i += 1;
+            }
+            match bytes.get(i)? {
+                quote @ (b'"' | b'\'') => {
+                    let close = input[i + 1..].find(*quote as char)?;
+                    value = input[i + 1..i + 1 + close].to_string();
+                    i += close + 2;
+                }
+                _ => {
+                    let start = i;
+                    while i < bytes.len() && !is_space(bytes[i]) && bytes[i] != b'>' {
+                        i += 1;
+                    }
+                    value = input[start..i].to_string();
+                }
+            }
+        }
+        attrs.push((attr_name, value));
+    }
+}
+
+/// Lifts the Confluence elements of an untyped node into their typed forms.
+fn typed(node: Node) -> Node {
+    let Node::Element(mut element) = node else {
+        return node;
+    };
+    let name = element.name.to_ascii_lowercase();
+    match name.as_str() {
+        "ac:structured-macro" | "ac:macro" => Node::Macro(typed_macro(element)),
+        "ac:link" => Node::Link(typed_link(element)),
+        "ac:image" => Node::Image(Image {
+            resource: element.elements().find_map(resource),
+            alt: element.attr("ac:alt"),
+        }),
+        "ac:emoticon" => Node::Emoticon(element.attr("ac:name").unwrap_or_default()),
+        "ac:task-list" => Node::TaskList(typed_tasks(element)),
+        _ => {
+            element.children = element.children.into_iter().map(typed).collect();
+            Node::Element(element)
+        }
+    }
+}
+
+fn typed_macro(element: Element) -> Macro {
+    let name = element.attr("ac:name").unwrap_or_default();
+    let mut params = Vec::new();
+    let mut body = MacroBody::None;
+
+    for child in element.children {
+        let Node::Element(child) = child else {
+            continue;
+        };
+        if child.is("ac:parameter") {
+            let key = child.attr("ac:name").unwrap_or_default();
+            let text = text_content(&child.children).trim().to_string();
+            // A parameter naming a page or user holds it as a resource, not
+            // text; the resource is what the value is.
+            let value = if text.is_empty() {
+                child
+                    .find(&|e| resource(e).is_some())
+                    .and_then(resource)
+                    .map(|r| match r {
+                        Resource::Page { title, .. } => title,
+                        Resource::User { account_id } => account_id,
+                        Resource::Attachment { filename } => filename,
+                        Resource::Url(url) => url,
+                    })
+                    .unwrap_or_default()
+            } else {
+                text
+            };
+            params.push((key, value));
+        } else if child.is("ac:rich-text-body") {
+            body = MacroBody::Rich(child.children.into_iter().map(typed).collect());
+        } else if child.is("ac:plain-text-body") {
+            body = MacroBody::Plain(text_content(&child.children));
+        }
+    }
+
+    Macro { name, params, body }
+}
+
+fn typed_link(element: Element) -> Link {
+    let resource = element.elements().find_map(resource);
+    let anchor = element.attr("ac:anchor");
+    let mut body = LinkBody::None;
+    for child in element.children {
+        let Node::Element(child) = child else {
+            continue;
+        };
+        if child.is("ac:link-body") {
+            body = LinkBody::Rich(child.children.into_iter().map(typed).collect());
+        } else if child.is("ac:plain-text-link-body") {
+            body = LinkBody::Plain(text_content(&child.children));
+        }
+    }
+    Link {
+        resource,
+        anchor,
+        body,
+    }
+}
+
+fn typed_tasks(element: Element) -> Vec<Task> {
+    element
+        .children
+        .into_iter()
+        .filter_map(|child| match child {
+            Node::Element(task) if task.is("ac:task") => Some(task),
+            _ => None,
+        })
+        .map(|task| {
+            let complete = task
+                .child("ac:task-status")
+                .is_some_and(|s| text_content(&s.children).trim() == "complete");
+            let body = task
+                .children
+                .into_iter()
+                .find_map(|child| match child {
+                    Node::Element(body) if body.is("ac:task-body") => Some(body.children),
+                    _ => None,
+                })
+                .unwrap_or_default();
+            Task {
+                complete,
+                body: body.into_iter().map(typed).collect(),
+            }
+        })
+        .collect()
+}
+
+/// The resource an `ri:` element identifies, if it is one this reader uses.
+fn resource(element: &Element) -> Option<Resource> {
+    let name = element.name.to_ascii_lowercase();
+    match name.as_str() {
+        "ri:page" | "ri:blog-post" => Some(Resource::Page {
+            space_key: element.attr("ri:space-key"),
+            title: element.attr("ri:content-title")?,
+        }),
+        "ri:user" => Some(Resource::User {
+            account_id: element
+                .attr("ri:account-id")
+                .or_else(|| element.attr("ri:userkey"))?,
+        }),
+        "ri:attachment" => Some(Resource::Attachment {
+            filename: element.attr("ri:filename")?,
+        }),
+        "ri:url" => Some(Resource::Url(element.attr("ri:value")?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(node: &Node) -> &Element {
+        match node {
+            Node::Element(e) => e,
+            other => panic!("not an element: {other:?}"),
+        }
+    }
+
+    fn only_macro(storage: &str) -> Macro {
+        match parse(storage).as_slice() {
+            [Node::Macro(m)] => m.clone(),
+            other => panic!("not one macro: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn elements_nest_by_their_tags() {
+        let tree = parse("<p>a <strong>b</strong></p><p>c</p>");
+        assert_eq!(tree.len(), 2);
+        let p = element(&tree[0]);
+        assert!(p.is("p"));
+        assert_eq!(text_content(&p.children), "a b");
+    }
+
+    #[test]
+    fn a_quoted_gt_does_not_end_the_tag() {
+        let tree = parse(r#"<a href="x?a>b" title='1 > 0'>t</a>"#);
+        let a = element(&tree[0]);
+        assert_eq!(a.attr("href").as_deref(), Some("x?a>b"));
+        assert_eq!(a.attr("title").as_deref(), Some("1 > 0"));
+        assert_eq!(text_content(&a.children), "t");
+    }
+
+    #[test]
+    fn a_nested_macro_keeps_its_own_body_and_parameters() {
+        let m = only_macro(
+            r#"<ac:structured-macro ac:name="expand"><ac:parameter ac:name="title">Outer</ac:parameter><ac:rich-text-body><ac:structured-macro ac:name="code"><ac:parameter ac:name="language">sql</ac:parameter><ac:plain-text-body><![CDATA[select 1]]></ac:plain-text-body></ac:structured-macro><p>after</p></ac:rich-text-body></ac:structured-macro>"#,
+        );
+        assert_eq!(m.name, "expand");
+        assert_eq!(m.params, vec![("title".to_string(), "Outer".to_string())]);
+        let MacroBody::Rich(body) = &m.body else {
+            panic!("{:?}", m.body)
+        };
+        let Node::Macro(inner) = &body[0] else {
+            panic!("{body:?}")
+        };
+        assert_eq!(inner.param("language"), Some("sql"));
+        assert_eq!(inner.body, MacroBody::Plain("select 1".to_string()));
+        assert_eq!(text_content(&body[1..]), "after");
+    }
+
+    #[test]
+    fn a_parameter_holding_a_resource_is_that_resource() {
+        let m = only_macro(
+            r#"<ac:structured-macro ac:name="include"><ac:parameter ac:name=""><ac:link><ri:page ri:content-title="Shared &amp; Co" /></ac:link></ac:parameter></ac:structured-macro>"#,
+        );
+        assert_eq!(m.param(""), Some("Shared & Co"));
+    }
+
+    #[test]
+    fn links_images_emoticons_and_tasks_are_typed() {
+        let tree = parse(
+            r#"<ac:link ac:anchor="top"><ri:page ri:space-key="ENG" ri:content-title="Home"/><ac:plain-text-link-body><![CDATA[go]]></ac:plain-text-link-body></ac:link><ac:image ac:alt="d"><ri:attachment ri:filename="d.png"/></ac:image><ac:emoticon ac:name="tick"/><ac:task-list><ac:task><ac:task-status>complete</ac:task-status><ac:task-body>done</ac:task-body></ac:task></ac:task-list>"#,
+        );
+        assert_eq!(
+            tree[0],
+            Node::Link(Link {
+                resource: Some(Resource::Page {
+                    space_key: Some("ENG".into()),
+                    title: "Home".into()
+                }),
+                anchor: Some("top".into()),
+                body: LinkBody::Plain("go".into()),
+            })
+        );
+        assert_eq!(
+            tree[1],
+            Node::Image(Image {
+                resource: Some(Resource::Attachment {
+                    filename: "d.png".into()
+                }),
+                alt: Some("d".into()),
+            })
+        );
+        assert_eq!(tree[2], Node::Emoticon("tick".into()));
+        let Node::TaskList(tasks) = &tree[3] else {
+            panic!("{tree:?}")
+        };
+        assert!(tasks[0].complete);
+        assert_eq!(text_content(&tasks[0].body), "done");
+    }
+
+    #[test]
+    fn malformed_markup_still_parses() {
+        // Unclosed elements close with their parent; stray closers and bare
+        // `<` are tolerated; an unterminated CDATA runs to the end.
+        let tree = parse("<div><p>open</div></span>a < b<![CDATA[tail");
+        let div = element(&tree[0]);
+        assert_eq!(text_content(&div.children), "open");
+        assert_eq!(text_content(&tree[1..]), "a < btail");
+    }
+
+    #[test]
+    fn void_and_raw_text_elements_take_no_markup_children() {
+        let tree = parse("<p>a<br>b<img src=x.png>c</p><style>p > a { }</style>");
+        let p = element(&tree[0]);
+        assert_eq!(p.children.len(), 5);
+        let style = element(&tree[1]);
+        assert_eq!(style.children, vec![Node::Text("p > a { }".into())]);
+    }
+
+    #[test]
+    fn comments_and_declarations_are_dropped() {
+        let tree = parse("<!DOCTYPE html><!-- note --><p>x</p>");
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn entities_decode_and_unknown_ones_stay() {
+        assert_eq!(
+            decode_entities("a &amp; b &lt;c&gt; &#65;&#x42; &hellip; &bogus; & x"),
+            "a & b <c> AB … &bogus; & x"
+        );
+    }
+}
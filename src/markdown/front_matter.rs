@@ -0,0 +1,77 @@
+use serde_json::Value;
+
+/// A Markdown document: `body` under a YAML front matter block built from
+/// `fields`, in the order given.
+///
+/// Strings are written as JSON strings, which YAML reads as double-quoted
+/// scalars — a title with a colon or a leading `#` cannot turn into a
+/// mapping or a comment. A list becomes a block sequence, an empty one `[]`.
+/// A field that is `null` is left out rather than written as `~`: a
+/// generator reading `assignee: ~` gets a value where there is none.
+pub fn with_front_matter(fields: &[(&str, Value)], body: &str) -> String {
+    let mut out = String::from("---\n");
+    for (key, value) in fields {
+        match value {
+            Value::Null => {}
+            Value::Array(items) if items.is_empty() => out.push_str(&format!("{key}: []\n")),
+            Value::Array(items) => {
+                out.push_str(&format!("{key}:\n"));
+                for item in items {
+                    out.push_str(&format!("  - {}\n", scalar(item)));
+                }
+            }
+            _ => out.push_str(&format!("{key}: {}\n", scalar(value))),
+        }
+    }
+    out.push_str("---\n");
+
+    let body = body.trim();
+    if !body.is_empty() {
+        out.push('\n');
+        out.push_str(body);
+        out.push('\n');
+    }
+    out
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => Value::String(s.clone()).to_string(),
+        Value::Number(_) | Value::Bool(_) => value.to_string(),
+        // Nested structures have no place in the flat metadata this writes;
+        // one that reaches here is kept as a JSON string, not dropped.
+        other => Value::String(other.to_string()).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scalars_lists_and_nulls() {
+        let doc = with_front_matter(
+            &[
+                ("title", json!("Deploy: the # runbook")),
+                ("version", json!(3)),
+                ("assignee", Value::Null),
+                ("labels", json!(["ops", "prod"])),
+                ("ancestors", json!([])),
+            ],
+            "Body\n",
+        );
+        assert_eq!(
+            doc,
+            "---\ntitle: \"Deploy: the # runbook\"\nversion: 3\nlabels:\n  - \"ops\"\n  - \"prod\"\nancestors: []\n---\n\nBody\n"
+        );
+    }
+
+    #[test]
+    fn test_an_empty_body_leaves_only_the_front_matter() {
+        assert_eq!(
+            with_front_matter(&[("key", json!("ABC-1"))], "  "),
+            "---\nkey: \"ABC-1\"\n---\n"
+        );
+    }
+}
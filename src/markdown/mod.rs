@@ -1,6 +1,18 @@
 pub mod adf;
+mod attachments;
 mod common;
 pub mod confluence;
+mod front_matter;
+mod users;
 
-pub use adf::adf_to_markdown;
-pub use confluence::confluence_to_markdown;
+pub use adf::{
+    MarkdownMode, adf_to_markdown, adf_to_markdown_lossless, has_media, link_media,
+    markdown_to_adf, name_mentions, unnamed_mentions,
+};
+pub use attachments::{Attachment, Attachments};
+pub use confluence::{
+    confluence_to_markdown, confluence_to_markdown_with, linked_attachments, linked_users,
+    markdown_to_storage,
+};
+pub use front_matter::with_front_matter;
+pub use users::UserNames;
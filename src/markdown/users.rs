@@ -0,0 +1,21 @@
+use std::collections::HashMap;
+
+/// Display names for Atlassian account ids, as far as they are known.
+///
+/// A body names a person by account id — an ADF `mention`, a storage
+/// `ri:user` link — and an id is useless to whoever reads the Markdown. The
+/// renderers take the names a caller looked up and fall back to the id for
+/// any they were not given, so a lookup that was refused or left someone out
+/// still reads.
+#[derive(Clone, Debug, Default)]
+pub struct UserNames(HashMap<String, String>);
+
+impl UserNames {
+    pub fn insert(&mut self, account_id: impl Into<String>, name: impl Into<String>) {
+        self.0.insert(account_id.into(), name.into());
+    }
+
+    pub fn get(&self, account_id: &str) -> Option<&str> {
+        self.0.get(account_id).map(String::as_str)
+    }
+}
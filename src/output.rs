@@ -0,0 +1,473 @@
+//! Writing an `{"items": [...]}` result as rows instead of JSON.
+//!
+//! Every list command answers with the same envelope (`list_envelope`,
+//! `v2_list_envelope`, the `--all` walks), so one projection serves all of
+//! them: a column is a JSON Pointer into each item, and a row is the values
+//! those pointers reach. Only the table's default columns look at what the
+//! items are; everything else takes the caller's columns as given.
+
+use crate::select::Selection;
+use anyhow::Result;
+use serde_json::Value;
+use std::io::Write;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The two delimited formats. They differ in more than the separator: CSV
+/// quotes a cell that needs it, while TSV has no quoting at all, so a tab or
+/// line break inside a value is replaced with a space rather than allowed to
+/// split the cell or the row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    Csv,
+    Tsv,
+}
+
+/// The columns of a result, each a JSON Pointer into one item.
+///
+/// A column written without a leading `/` is a top-level key (`key` is
+/// `/key`), which covers the common case without making the caller learn
+/// pointer syntax; anything nested is written as a pointer
+/// (`/fields/status/name`). The header row repeats the columns as the caller
+/// wrote them, so the file reads back in the caller's own vocabulary.
+#[derive(Debug, Clone)]
+pub struct Columns {
+    headers: Vec<String>,
+    pointers: Vec<String>,
+}
+
+impl Columns {
+    pub fn new(columns: &[String]) -> Result<Self> {
+        if columns.is_empty() {
+            anyhow::bail!("--columns needs at least one column");
+        }
+        let mut pointers = Vec::with_capacity(columns.len());
+        for column in columns {
+            let column = column.trim();
+            if column.is_empty() {
+                anyhow::bail!("--columns has an empty column; separate names with single commas");
+            }
+            pointers.push(if column.starts_with('/') {
+                column.to_string()
+            } else {
+                format!("/{}", column.replace('~', "~0").replace('/', "~1"))
+            });
+        }
+        Ok(Self {
+            headers: columns.iter().map(|c| c.trim().to_string()).collect(),
+            pointers,
+        })
+    }
+
+    /// The columns a reader scanning these items most likely wants, for when
+    /// none were named. Told apart by shape rather than by the command that
+    /// produced them, so an issue list reads the same from `search`, a
+    /// sprint, or an epic: issues by key, summary, status and assignee; pages
+    /// by id, title and version. Anything else gets the first item's scalar
+    /// fields, which is what a list of boards, projects or spaces is made of.
+    pub fn defaults_for(items: &[Value]) -> Self {
+        let labelled = |columns: &[(&str, &str)]| Self {
+            headers: columns.iter().map(|(h, _)| h.to_string()).collect(),
+            pointers: columns.iter().map(|(_, p)| p.to_string()).collect(),
+        };
+        let first = items.first().and_then(Value::as_object);
+        let has = |key: &str| first.is_some_and(|item| item.contains_key(key));
+
+        if has("key") && has("fields") {
+            labelled(&[
+                ("KEY", "/key"),
+                ("SUMMARY", "/fields/summary"),
+                ("STATUS", "/fields/status/name"),
+                ("ASSIGNEE", "/fields/assignee/displayName"),
+            ])
+        } else if has("id") && has("title") {
+            labelled(&[
+                ("ID", "/id"),
+                ("TITLE", "/title"),
+                ("VERSION", "/version/number"),
+            ])
+        } else {
+            let scalars: Vec<(String, String)> = first
+                .into_iter()
+                .flatten()
+                .filter(|(_, value)| !value.is_object() && !value.is_array())
+                .take(MAX_DEFAULT_COLUMNS)
+                .map(|(key, _)| {
+                    (
+                        key.to_uppercase(),
+                        format!("/{}", key.replace('~', "~0").replace('/', "~1")),
+                    )
+                })
+                .collect();
+            Self {
+                headers: scalars.iter().map(|(h, _)| h.clone()).collect(),
+                pointers: scalars.into_iter().map(|(_, p)| p).collect(),
+            }
+        }
+    }
+
+    /// One item's cells. A pointer that reaches nothing, or `null`, is an
+    /// empty cell: a sparse field is the normal case in a search, not an
+    /// error. An object or array is written as compact JSON rather than
+    /// dropped, so the cell still says what was there.
+    pub fn cells(&self, item: &Value) -> Vec<String> {
+        self.pointers
+            .iter()
+            .map(|pointer| match item.pointer(pointer) {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::String(s)) => s.clone(),
+                Some(other) => other.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Beyond this a fallback table is wider than it is readable; a caller who
+/// wants more names them with `--columns`.
+const MAX_DEFAULT_COLUMNS: usize = 6;
+
+/// A result written as delimited rows under a header.
+#[derive(Debug, Clone)]
+pub struct Rows {
+    delimiter: Delimiter,
+    columns: Columns,
+}
+
+impl Rows {
+    pub fn new(delimiter: Delimiter, columns: Columns) -> Self {
+        Self { delimiter, columns }
+    }
+
+    pub fn header(&self) -> String {
+        self.line(&self.columns.headers)
+    }
+
+    pub fn row(&self, item: &Value) -> String {
+        self.line(&self.columns.cells(item))
+    }
+
+    /// The header and one row per item, each line ending in a newline.
+    pub fn document(&self, items: &[Value]) -> String {
+        let mut out = self.header();
+        out.push('\n');
+        for item in items {
+            out.push_str(&self.row(item));
+            out.push('\n');
+        }
+        out
+    }
+
+    fn line(&self, cells: &[String]) -> String {
+        match self.delimiter {
+            Delimiter::Csv => csv_line(cells),
+            Delimiter::Tsv => cells
+                .iter()
+                .map(|cell| tsv_cell(cell))
+                .collect::<Vec<_>>()
+                .join("\t"),
+        }
+    }
+}
+
+/// A result as an aligned table for reading in a terminal.
+///
+/// Widths are display columns, not chars: a CJK character takes two cells,
+/// and counting it as one misaligns every row after a Korean or Japanese
+/// summary. When the table is wider than `max_width` the widest columns give
+/// way first, each cut short with `…`, so a long summary shrinks before a key
+/// or a status does.
+pub struct Table {
+    columns: Columns,
+    max_width: Option<usize>,
+}
+
+/// Between two columns.
+const GAP: &str = "  ";
+
+/// No column is narrowed below this: past it a cell says nothing, and the
+/// table is left wider than asked instead.
+const MIN_COLUMN_WIDTH: usize = 8;
+
+impl Table {
+    pub fn new(columns: Columns, max_width: Option<usize>) -> Self {
+        Self { columns, max_width }
+    }
+
+    pub fn render(&self, items: &[Value]) -> String {
+        let header: Vec<String> = self.columns.headers.clone();
+        let rows: Vec<Vec<String>> = items
+            .iter()
+            .map(|item| {
+                self.columns
+                    .cells(item)
+                    .iter()
+                    .map(|cell| single_line(cell))
+                    .collect()
+            })
+            .collect();
+
+        let mut widths: Vec<usize> = header.iter().map(|h| h.width()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.width());
+            }
+        }
+        if let Some(max_width) = self.max_width {
+            let floors: Vec<usize> = widths.iter().map(|w| (*w).min(MIN_COLUMN_WIDTH)).collect();
+            let gaps = GAP.len() * widths.len().saturating_sub(1);
+            while widths.iter().sum::<usize>() + gaps > max_width {
+                let Some((widest, _)) = widths
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, width)| **width > floors[*i])
+                    .max_by_key(|(i, width)| (**width, std::cmp::Reverse(*i)))
+                else {
+                    break;
+                };
+                widths[widest] -= 1;
+            }
+        }
+
+        let mut out = String::new();
+        for row in std::iter::once(&header).chain(&rows) {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| fit(cell, *width))
+                .collect();
+            out.push_str(cells.join(GAP).trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn single_line(cell: &str) -> String {
+    cell.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `cell` padded, or cut short with `…`, to exactly `width` display columns.
+fn fit(cell: &str, width: usize) -> String {
+    let cell_width = cell.width();
+    if cell_width <= width {
+        return format!("{cell}{}", " ".repeat(width - cell_width));
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in cell.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push('…');
+    used += 1;
+    // A wide character that did not fit can leave one column over.
+    out.push_str(&" ".repeat(width.saturating_sub(used)));
+    out
+}
+
+/// One CSV record, without its line ending. Written by the same crate
+/// `jira create --from` reads CSV with, so what this quotes is what a reader
+/// takes back as written: a lone `\r`, or the one empty cell of a
+/// one-column row, which left bare would read as a blank line and vanish.
+fn csv_line(cells: &[String]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(cells)
+        .expect("a record written to memory cannot fail");
+    let mut line = String::from_utf8(
+        writer
+            .into_inner()
+            .expect("a record written to memory cannot fail"),
+    )
+    .expect("every cell is a String");
+    line.pop();
+    line
+}
+
+fn tsv_cell(cell: &str) -> String {
+    cell.replace("\r\n", " ").replace(['\t', '\n', '\r'], " ")
+}
+
+/// Where an `--all --stream` walk writes each page as it arrives: one JSON
+/// object per line, or rows under a header written before the first page.
+/// With a selection, each item is cut down to it before it is written.
+pub struct Stream {
+    sink: Sink,
+    select: Option<Selection>,
+}
+
+enum Sink {
+    Jsonl,
+    Rows { rows: Rows, started: bool },
+}
+
+impl Stream {
+    pub fn jsonl() -> Self {
+        Self {
+            sink: Sink::Jsonl,
+            select: None,
+        }
+    }
+
+    pub fn rows(rows: Rows) -> Self {
+        Self {
+            sink: Sink::Rows {
+                rows,
+                started: false,
+            },
+            select: None,
+        }
+    }
+
+    /// `selection` is relative to one item — see `Selection::within_items`.
+    pub fn select(mut self, selection: Selection) -> Self {
+        self.select = Some(selection);
+        self
+    }
+
+    /// Writes one page and flushes, so a consumer sees it before the next
+    /// request goes out. The header goes with the first page even when that
+    /// page is empty: a walk that found nothing still produces a file with
+    /// its columns named.
+    pub fn write_page(&mut self, items: &[Value]) -> Result<()> {
+        let selected: Vec<Value>;
+        let items = match &self.select {
+            Some(selection) => {
+                selected = items.iter().map(|item| selection.apply(item)).collect();
+                &selected
+            }
+            None => items,
+        };
+
+        let mut stdout = std::io::stdout().lock();
+        match &mut self.sink {
+            Sink::Jsonl => {
+                for item in items {
+                    writeln!(stdout, "{}", serde_json::to_string(item)?)?;
+                }
+            }
+            Sink::Rows { rows, started } => {
+                if !*started {
+                    writeln!(stdout, "{}", rows.header())?;
+                    *started = true;
+                }
+                for item in items {
+                    writeln!(stdout, "{}", rows.row(item))?;
+                }
+            }
+        }
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn columns(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_csv_quotes_only_the_cells_that_need_it() {
+        let rows = Rows::new(
+            Delimiter::Csv,
+            Columns::new(&columns(&[
+                "key",
+                "/fields/summary",
+                "/fields/assignee/displayName",
+            ]))
+            .unwrap(),
+        );
+        let items = [
+            json!({"key": "ABC-1", "fields": {"summary": "Fix \"login\", again", "assignee": null}}),
+            json!({"key": "ABC-2", "fields": {"summary": "Plain"}}),
+        ];
+        assert_eq!(
+            rows.document(&items),
+            "key,/fields/summary,/fields/assignee/displayName\n\
+             ABC-1,\"Fix \"\"login\"\", again\",\n\
+             ABC-2,Plain,\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_reads_back_as_written() {
+        let rows = Rows::new(Delimiter::Csv, Columns::new(&columns(&["a", "b"])).unwrap());
+        let item = json!({"a": " leading space", "b": "lone\rreturn"});
+        let document = rows.document(&[item]);
+        let mut reader = csv::Reader::from_reader(document.as_bytes());
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(
+            record.iter().collect::<Vec<_>>(),
+            [" leading space", "lone\rreturn"]
+        );
+
+        // A one-column row with nothing in it must still be a row.
+        let rows = Rows::new(Delimiter::Csv, Columns::new(&columns(&["a"])).unwrap());
+        let document = rows.document(&[json!({}), json!({"a": "x"})]);
+        let reader = csv::Reader::from_reader(document.as_bytes());
+        assert_eq!(reader.into_records().count(), 2);
+    }
+
+    #[test]
+    fn test_tsv_flattens_tabs_and_line_breaks() {
+        let rows = Rows::new(
+            Delimiter::Tsv,
+            Columns::new(&columns(&["title", "/labels"])).unwrap(),
+        );
+        assert_eq!(
+            rows.row(&json!({"title": "a\tb\r\nc", "labels": ["x", "y"]})),
+            "a b c\t[\"x\",\"y\"]"
+        );
+    }
+
+    #[test]
+    fn test_a_bare_column_is_a_key_not_a_path() {
+        let rows = Rows::new(
+            Delimiter::Csv,
+            Columns::new(&columns(&["a/b", "id"])).unwrap(),
+        );
+        assert_eq!(rows.row(&json!({"a/b": 1, "id": 7})), "1,7");
+        assert!(Columns::new(&columns(&["id", " "])).is_err());
+    }
+
+    #[test]
+    fn test_issues_and_pages_get_their_own_default_columns() {
+        let issue = json!({"key": "ABC-1", "fields": {"summary": "S", "status": {"name": "Done"}}});
+        assert_eq!(
+            Columns::defaults_for(&[issue]).headers,
+            ["KEY", "SUMMARY", "STATUS", "ASSIGNEE"]
+        );
+        let page = json!({"id": "1", "title": "T", "version": {"number": 2}});
+        assert_eq!(
+            Columns::defaults_for(&[page]).headers,
+            ["ID", "TITLE", "VERSION"]
+        );
+        let board = json!({"id": 3, "name": "B", "location": {"projectKey": "ABC"}});
+        assert_eq!(Columns::defaults_for(&[board]).headers, ["ID", "NAME"]);
+    }
+
+    #[test]
+    fn test_a_table_aligns_wide_characters_and_truncates_to_fit() {
+        let columns = Columns::new(&columns(&["key", "summary"])).unwrap();
+        let items = [
+            json!({"key": "A-1", "summary": "배포 runbook 정리"}),
+            json!({"key": "A-22", "summary": "short"}),
+        ];
+        assert_eq!(
+            Table::new(columns.clone(), None).render(&items),
+            "key   summary\nA-1   배포 runbook 정리\nA-22  short\n"
+        );
+        // 4 + 2 + 10: the summary gives way, cut at a character boundary.
+        assert_eq!(
+            Table::new(columns, Some(16)).render(&items),
+            "key   summary\nA-1   배포 runb…\nA-22  short\n"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! `--select`: keep only the named paths of a result.
+//!
+//! Where `filter::apply` removes what is known to be noise, this keeps what
+//! the caller named and nothing else. The result keeps its shape — a path
+//! `items[].fields.status.name` answers `{"items": [{"fields": {"status":
+//! {"name": ...}}}]}` — so a consumer reads the same keys it would have found
+//! in the full answer, and several paths merge into one document.
+
+use anyhow::Result;
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    /// Every element of an array: `[]`.
+    Each,
+}
+
+/// The paths given to `--select`, parsed once.
+///
+/// Two spellings are accepted. A JSON Pointer (`/fields/status/name`), where
+/// a numeric segment on an array takes that element. Or a dotted path
+/// (`items[].fields.status.name`), where `[]` takes every element — the
+/// form a pointer has no way to say.
+#[derive(Debug, Clone)]
+pub struct Selection {
+    paths: Vec<Vec<Segment>>,
+}
+
+impl Selection {
+    pub fn new(paths: &[String]) -> Result<Self> {
+        if paths.is_empty() {
+            anyhow::bail!("--select needs at least one path");
+        }
+        let paths = paths
+            .iter()
+            .map(|path| parse(path.trim()))
+            .collect::<Result<_>>()?;
+        Ok(Self { paths })
+    }
+
+    /// The selection as seen from one item of an `items` list: the paths
+    /// under `items[]`, with that prefix taken off. A streamed walk writes
+    /// items one per line, so these are the only paths that reach anything
+    /// in it. `None` when no path goes through `items[]`.
+    pub fn within_items(&self) -> Option<Self> {
+        let paths: Vec<Vec<Segment>> = self
+            .paths
+            .iter()
+            .filter_map(|path| match path.as_slice() {
+                [Segment::Key(items), Segment::Each, rest @ ..] if items == "items" => {
+                    Some(rest.to_vec())
+                }
+                _ => None,
+            })
+            .collect();
+        (!paths.is_empty()).then_some(Self { paths })
+    }
+
+    /// `value` cut down to the selected paths. A path that reaches nothing
+    /// ends in `null` rather than disappearing, so every item of a list
+    /// answers with the same keys and "absent" is told apart from "not
+    /// selected".
+    pub fn apply(&self, value: &Value) -> Value {
+        self.paths
+            .iter()
+            .map(|path| project(value, path))
+            .reduce(merge)
+            .unwrap_or(Value::Null)
+    }
+}
+
+fn parse(path: &str) -> Result<Vec<Segment>> {
+    if path.is_empty() {
+        anyhow::bail!("--select has an empty path; separate paths with single commas");
+    }
+    if let Some(pointer) = path.strip_prefix('/') {
+        return Ok(pointer
+            .split('/')
+            .map(|s| Segment::Key(s.replace("~1", "/").replace("~0", "~")))
+            .collect());
+    }
+
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if key.is_empty() && rest.is_empty() {
+            anyhow::bail!("--select path '{path}' has an empty name");
+        }
+        if !key.is_empty() {
+            segments.push(Segment::Key(key.to_string()));
+        }
+        while !rest.is_empty() {
+            let Some(after) = rest.strip_prefix("[]") else {
+                anyhow::bail!(
+                    "--select path '{path}': only [] (every element) may follow a name; \
+                     take one element with a JSON Pointer such as /items/0/key"
+                );
+            };
+            segments.push(Segment::Each);
+            rest = after;
+        }
+    }
+    Ok(segments)
+}
+
+/// The part of `value` one path reaches, with the structure above it kept.
+fn project(value: &Value, path: &[Segment]) -> Value {
+    let Some((segment, rest)) = path.split_first() else {
+        return value.clone();
+    };
+    match (segment, value) {
+        (Segment::Key(key), Value::Object(map)) => {
+            let inner = project(map.get(key).unwrap_or(&Value::Null), rest);
+            Value::Object(Map::from_iter([(key.clone(), inner)]))
+        }
+        (Segment::Key(key), Value::Array(items)) => match key.parse::<usize>() {
+            // The element stays at its own index, the slots before it
+            // `null`: merged with another path into the same array, it has
+            // to line up with the element it is.
+            Ok(index) => match items.get(index) {
+                Some(item) => {
+                    let mut kept = vec![Value::Null; index];
+                    kept.push(project(item, rest));
+                    Value::Array(kept)
+                }
+                None => Value::Array(Vec::new()),
+            },
+            Err(_) => Value::Null,
+        },
+        (Segment::Each, Value::Array(items)) => {
+            Value::Array(items.iter().map(|item| project(item, rest)).collect())
+        }
+        _ => Value::Null,
+    }
+}
+
+/// Two projections of the same value as one. They can only disagree where
+/// one reached `null` and the other went further, so the fuller side wins.
+/// Arrays pair up by index; one cut short by a pointer to an earlier element
+/// is `null` past its end, not a reason to drop the other side.
+fn merge(a: Value, b: Value) -> Value {
+    match (a, b) {
+        (Value::Object(mut a), Value::Object(b)) => {
+            for (key, value) in b {
+                let merged = match a.remove(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => value,
+                };
+                a.insert(key, merged);
+            }
+            Value::Object(a)
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            let len = a.len().max(b.len());
+            let (mut a, mut b) = (a.into_iter(), b.into_iter());
+            Value::Array(
+                (0..len)
+                    .map(|_| {
+                        merge(
+                            a.next().unwrap_or(Value::Null),
+                            b.next().unwrap_or(Value::Null),
+                        )
+                    })
+                    .collect(),
+            )
+        }
+        (Value::Null, other) => other,
+        (other, Value::Null) => other,
+        (_, b) => b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn selection(paths: &[&str]) -> Selection {
+        Selection::new(&paths.iter().map(|p| p.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn test_paths_merge_into_one_document_of_the_same_shape() {
+        let result = json!({
+            "items": [
+                {"key": "A-1", "fields": {"summary": "S", "status": {"name": "Done", "id": "3"}}},
+                {"key": "A-2", "fields": {"summary": "T", "status": null}}
+            ],
+            "total": 2
+        });
+        assert_eq!(
+            selection(&["items[].key", "items[].fields.status.name", "/total"]).apply(&result),
+            json!({
+                "items": [
+                    {"key": "A-1", "fields": {"status": {"name": "Done"}}},
+                    {"key": "A-2", "fields": {"status": null}}
+                ],
+                "total": 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_pointers_keep_each_element_at_its_index() {
+        let result = json!({"items": [{"key": "A-1"}, {"key": "A-2"}], "a/b": 1});
+        assert_eq!(
+            selection(&["/items/0/key", "/items/1/key", "/a~1b"]).apply(&result),
+            json!({"items": [{"key": "A-1"}, {"key": "A-2"}], "a/b": 1})
+        );
+        assert_eq!(
+            selection(&["/items/1/key"]).apply(&result),
+            json!({"items": [null, {"key": "A-2"}]})
+        );
+    }
+
+    #[test]
+    fn test_a_pointer_and_every_element_merge_by_index() {
+        let result = json!({"items": [
+            {"key": "A-1", "id": "1"},
+            {"key": "A-2", "id": "2"},
+            {"key": "A-3", "id": "3"}
+        ]});
+        assert_eq!(
+            selection(&["/items/1/id", "items[].key"]).apply(&result),
+            json!({"items": [{"key": "A-1"}, {"key": "A-2", "id": "2"}, {"key": "A-3"}]})
+        );
+    }
+
+    #[test]
+    fn test_a_stream_sees_the_paths_under_items() {
+        let within = selection(&["items[].key", "total"]).within_items().unwrap();
+        assert_eq!(
+            within.apply(&json!({"key": "A-1", "id": "1"})),
+            json!({"key": "A-1"})
+        );
+        assert!(selection(&["total"]).within_items().is_none());
+        assert!(Selection::new(&["items[0].key".to_string()]).is_err());
+        assert!(Selection::new(&["items..key".to_string()]).is_err());
+    }
+}
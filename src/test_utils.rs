@@ -138,6 +138,45 @@ pub fn mock_client_with_method(base_url: impl Into<String>, method: AuthMethod)
     ApiClient::new_with_strategy(strategy, create_test_config())
 }
 
+/// A server that answers every request with `chunks` as a chunked body,
+/// sent one at a time `gap` apart — a download arriving slowly but steadily,
+/// which wiremock cannot serve: its delay holds back the whole response.
+/// Returns the base URL.
+#[cfg(test)]
+pub async fn slow_body_server(chunks: &'static [&'static str], gap: std::time::Duration) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let head = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n";
+                if socket.write_all(head.as_bytes()).await.is_err() {
+                    return;
+                }
+                for chunk in chunks {
+                    tokio::time::sleep(gap).await;
+                    let frame = format!("{:x}\r\n{chunk}\r\n", chunk.len());
+                    if socket.write_all(frame.as_bytes()).await.is_err() {
+                        return;
+                    }
+                }
+                let _ = socket.write_all(b"0\r\n\r\n").await;
+            });
+        }
+    });
+    base_url
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;